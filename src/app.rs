@@ -1,12 +1,20 @@
 use crate::*;
 use crate::widget::TaskbarWidget;
 use crate::system::set_widget_pointer;
-use crate::lyrics::{LyricsManager, LyricsServiceBuilder, LyricsEvent, LyricsState};
-use crate::system::{MediaInfo, MediaMonitor, MediaEvent, PlaybackTimer, PlaybackEvent};
+use crate::engine::{EngineCommand, EngineConfig, LyricsEngine};
+use crate::lyrics::{format_song_display, LyricsData, LyricsDisplayMode, LyricsSource, LyricsState, SongInfo, TimelineSource};
+use crate::settings::AppSettings;
+use crate::system::{MediaInfo, PlaybackTimer, TrayCommand, TrayIcon};
+#[cfg(feature = "settings-window")]
+use crate::system::SettingsWindow;
+use std::path::Path;
 use std::sync::Arc;
-use std::thread;
 use std::time::{Duration, Instant};
-use tokio::sync::{watch, RwLock};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// 找不到任务栏时重新初始化的间隔
+const INIT_RETRY_INTERVAL: Duration = Duration::from_secs(2);
 
 /// 应用程序状态
 #[derive(Debug, Clone)]
@@ -19,6 +27,12 @@ pub struct AppState {
     pub current_position: Duration,
     /// 最后更新时间
     pub last_updated: Instant,
+    /// 当前歌词行是否正在滚动（由UI线程每帧同步）
+    pub is_scrolling: bool,
+    /// 滚动进度（0.0 ~ 1.0）
+    pub scroll_progress: f32,
+    /// 后台服务多次重启失败后的错误信息
+    pub service_error: Option<String>,
 }
 
 impl Default for AppState {
@@ -28,6 +42,62 @@ impl Default for AppState {
             lyrics_state: LyricsState::default(),
             current_position: Duration::ZERO,
             last_updated: Instant::now(),
+            is_scrolling: false,
+            scroll_progress: 0.0,
+            service_error: None,
+        }
+    }
+}
+
+impl AppState {
+    /// 获取当前播放的歌曲信息
+    pub fn current_song(&self) -> Option<SongInfo> {
+        self.media_info.as_ref().map(|media| SongInfo::new(&media.title, &media.artist))
+    }
+}
+
+/// 当前会话的快照（歌曲、完整歌词、时间轴和播放位置），用于反馈匹配错误或时间轴问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    /// 导出时间
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    /// 程序版本
+    pub version: String,
+    /// 当前媒体信息
+    pub media_info: Option<MediaInfo>,
+    /// 当前歌曲
+    pub song: Option<SongInfo>,
+    /// 歌词来源
+    pub source: Option<LyricsSource>,
+    /// 获取到的完整歌词数据
+    pub lyrics: Option<LyricsData>,
+    /// 解析后的时间轴 (毫秒, 歌词)
+    pub timeline: Vec<(u64, String)>,
+    /// 当前播放位置（毫秒）
+    pub position_ms: u64,
+    /// 当前歌词行
+    pub current_line: Option<String>,
+}
+
+impl SessionSnapshot {
+    /// 从应用状态生成快照；状态中没有解析后的时间轴时按 LRC 从歌词数据解析
+    pub fn capture(state: &AppState) -> Self {
+        let lyrics = state.lyrics_state.current_lyrics.clone();
+        let timeline = match (&lyrics, state.lyrics_state.timeline.is_empty()) {
+            (Some(lyrics), true) => lyrics.timeline_lines(TimelineSource::Lrc),
+            _ => state.lyrics_state.timeline.to_vec(),
+        };
+
+        Self {
+            exported_at: chrono::Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            media_info: state.media_info.clone(),
+            song: state.lyrics_state.current_song.clone().or_else(|| state.current_song()),
+            source: lyrics.as_ref().map(|lyrics| lyrics.source.clone()),
+            lyrics,
+            timeline,
+            position_ms: state.current_position.as_millis() as u64,
+            current_line: state.lyrics_state.current_line.clone(),
         }
     }
 }
@@ -46,272 +116,209 @@ pub struct App {
     // 当前应用状态缓存
     current_state: AppState,
     
-    // 歌词管理器引用（用于获取下一句歌词时间）
-    lyrics_manager: Option<Arc<LyricsManager>>,
+    // 后台歌词引擎（用于发送控制命令）
+    engine: LyricsEngine,
+    
+    // 最近一次初始化失败的错误
+    init_error: Option<InitError>,
+    
+    // 下次重试初始化的时间（仅可重试的错误）
+    init_retry_at: Option<Instant>,
+    
+    // 通知区域图标（创建失败时为 None）
+    tray: Option<TrayIcon>,
+    
+    // 鼠标在窗口内的横坐标（用于判断点击的控制区域）
+    cursor_x: f64,
+    
+    // 当前生效的用户设置
+    settings: AppSettings,
+    
+    // 已打开的设置窗口
+    #[cfg(feature = "settings-window")]
+    settings_window: Option<SettingsWindow>,
 }
 
 impl App {
     pub fn new() -> Self {
-        // 创建播放定时器
-        let (playback_timer, playback_event_receiver) = PlaybackTimer::new();
-        let playback_timer = Arc::new(playback_timer);
-        
-        // 创建状态更新通道
-        let (state_update_sender, state_update_receiver) = watch::channel(AppState::default());
-        let app_state = Arc::new(RwLock::new(AppState::default()));
+        Self::with_config(EngineConfig::default())
+    }
+
+    /// 按指定的引擎配置创建应用（歌词源、缓存、媒体过滤等）
+    pub fn with_config(config: EngineConfig) -> Self {
+        // 启动后台歌词引擎
+        let engine = LyricsEngine::start(config);
         
-        let app = Self {
+        Self {
             widget: TaskbarWidget::new(),
             last_redraw_time: Instant::now(),
-            playback_timer: playback_timer.clone(),
-            state_update_receiver,
+            playback_timer: engine.playback_timer(),
+            state_update_receiver: engine.subscribe(),
             current_state: AppState::default(),
-            lyrics_manager: None, // 将在后台服务启动后设置
-        };
-        
-        // 启动后台服务
-        app.start_background_services(
-            playback_timer,
-            app_state,
-            state_update_sender,
-            playback_event_receiver,
-        );
-        
-        app
+            engine,
+            init_error: None,
+            init_retry_at: None,
+            tray: None,
+            cursor_x: 0.0,
+            settings: AppSettings::default(),
+            #[cfg(feature = "settings-window")]
+            settings_window: None,
+        }
     }
     
-    /// 设置歌词管理器引用（在后台服务启动后调用）
-    pub fn set_lyrics_manager(&mut self, lyrics_manager: Arc<LyricsManager>) {
-        self.lyrics_manager = Some(lyrics_manager);
+    /// 获取当前应用状态（包含UI线程同步的滚动状态）
+    pub fn current_state(&self) -> &AppState {
+        &self.current_state
     }
     
-    /// 根据播放状态获取合适的更新间隔
-    fn get_update_interval(playback_timer: &Arc<PlaybackTimer>) -> Duration {
-        if playback_timer.is_playing() {
-            Duration::from_millis(50) // 播放时高频更新
-        } else {
-            Duration::from_millis(500) // 非播放时低频更新
+    /// 完全重置（清空歌词、重置定时器并重新加载当前歌曲），供菜单或快捷键调用
+    pub fn reset(&mut self) {
+        self.engine.reset();
+        self.widget.scroll_offset = 0.0;
+        self.widget.is_scrolling = false;
+        self.widget.mark_content_changed();
+    }
+    
+    /// 执行托盘图标收到的命令，选择退出时返回 true
+    fn handle_tray_commands(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) -> bool {
+        let commands = self.tray.as_ref().map(TrayIcon::take_commands).unwrap_or_default();
+        for command in commands {
+            match command {
+                TrayCommand::Refresh => self.reset(),
+                TrayCommand::ToggleVisibility => {
+                    self.widget.user_hidden = !self.widget.user_hidden;
+                    self.widget.update_window_visibility();
+                    self.widget.mark_content_changed();
+                }
+                TrayCommand::SwitchSource => self.engine.send_command(EngineCommand::SwitchSource),
+                #[cfg(feature = "settings-window")]
+                TrayCommand::OpenSettings => self.open_settings(),
+                TrayCommand::Quit => {
+                    info!("从托盘菜单退出");
+                    event_loop.exit();
+                    return true;
+                }
+            }
         }
+        false
     }
     
-    /// 启动后台服务
-    fn start_background_services(
-        &self,
-        playback_timer: Arc<PlaybackTimer>,
-        app_state: Arc<RwLock<AppState>>,
-        state_update_sender: watch::Sender<AppState>,
-        playback_event_receiver: watch::Receiver<PlaybackEvent>,
-    ) {
-        // 启动事件处理循环
-        thread::spawn(move || {
-            Self::run_event_loop(
-                playback_timer,
-                app_state,
-                state_update_sender,
-                playback_event_receiver,
-            );
+    /// 应用用户设置：更新小组件显示，切换歌词源并修改歌词偏移
+    pub fn apply_settings(&mut self, settings: AppSettings) {
+        settings.apply_to_widget(&mut self.widget);
+        self.widget.reposition();
+        self.widget.update_window_visibility();
+        self.widget.mark_content_changed();
+        self.widget.request_redraw();
+        self.engine.send_command(EngineCommand::SetSources {
+            netease: settings.enable_netease,
+            qqmusic: settings.enable_qqmusic,
         });
+        self.engine.send_command(EngineCommand::SetLyricsOffset(settings.lyrics_offset_ms));
+        self.settings = settings;
     }
     
-    /// 事件处理循环
-    fn run_event_loop(
-        playback_timer: Arc<PlaybackTimer>,
-        app_state: Arc<RwLock<AppState>>,
-        state_update_sender: watch::Sender<AppState>,
-        mut playback_event_receiver: watch::Receiver<PlaybackEvent>,
-    ) {
-        let rt = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-        
-        rt.block_on(async {
-            // 初始化媒体监控
-            let (mut media_monitor, mut media_event_receiver) = MediaMonitor::new();
-            if let Err(_) = media_monitor.initialize().await {
-                return;
+    /// 当前生效的用户设置
+    pub fn settings(&self) -> &AppSettings {
+        &self.settings
+    }
+    
+    /// 打开设置窗口（已打开时切到前台）
+    #[cfg(feature = "settings-window")]
+    pub fn open_settings(&mut self) {
+        if let Some(window) = self.settings_window.as_ref().filter(|window| window.is_open()) {
+            window.focus();
+            return;
+        }
+        match SettingsWindow::open(&self.settings) {
+            Ok(window) => self.settings_window = Some(window),
+            Err(e) => warn!("{}", e),
+        }
+    }
+    
+    /// 应用并保存设置窗口中保存的设置
+    #[cfg(feature = "settings-window")]
+    fn handle_saved_settings(&mut self) {
+        let Some(settings) = self.settings_window.as_ref().and_then(SettingsWindow::take_saved) else {
+            return;
+        };
+        if let Err(e) = settings.save(AppSettings::default_path()) {
+            warn!("{}", e);
+        }
+        info!("已应用新的设置");
+        self.apply_settings(settings);
+    }
+    
+    /// 切换到下一个可用的歌词显示语言（供快捷键调用），立即重绘并短暂提示新的语言
+    pub fn cycle_display_mode(&mut self) -> LyricsDisplayMode {
+        let mode = self.widget.cycle_display_mode();
+        info!("歌词显示语言: {}", mode.display_name());
+        self.widget.request_redraw();
+        mode
+    }
+    
+    /// 把当前会话导出为 JSON 快照文件，便于提交匹配错误或时间轴问题
+    pub fn export_snapshot(&self, path: impl AsRef<Path>) -> std::result::Result<(), String> {
+        let snapshot = SessionSnapshot::capture(&self.current_state);
+        let content = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| format!("序列化快照失败: {}", e))?;
+
+        std::fs::write(path.as_ref(), content)
+            .map_err(|e| format!("写入快照失败: {}", e))?;
+        info!("已导出会话快照: {:?}", path.as_ref());
+        Ok(())
+    }
+    
+    /// 最近一次初始化失败的错误（初始化成功后为 None）
+    pub fn init_error(&self) -> Option<&InitError> {
+        self.init_error.as_ref()
+    }
+    
+    /// 初始化小组件，找不到任务栏时安排稍后重试
+    fn try_initialize(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if let Err(e) = self.widget.initialize(event_loop) {
+            error!("小组件初始化失败: {}", e);
+            // 其余错误重试也无法恢复，只有任务栏尚未创建（如开机时资源管理器未就绪）才重试
+            self.init_retry_at = Some(Instant::now() + INIT_RETRY_INTERVAL).filter(|_| e.is_retryable());
+            if let Some(retry_at) = self.init_retry_at {
+                event_loop.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(retry_at));
             }
-            
-            // 初始化歌词服务
-            let lyrics_service = match LyricsServiceBuilder::new()
-                .enable_netease(true)
-                .enable_qqmusic(true)
-                .with_search_timeout(30)
-                .build()
-            {
-                Ok(service) => service,
-                Err(_) => {
-                    return;
-                }
-            };
-            
-            // 创建歌词管理器
-            let (lyrics_manager, mut lyrics_event_receiver) = LyricsManager::new(lyrics_service);
-            let lyrics_manager = Arc::new(lyrics_manager);
-            
-            // 启动媒体监控
-            let _media_monitor_handle = {
-                let mut monitor = media_monitor;
-                tokio::spawn(async move {
-                    let _ = monitor.start_monitoring(Duration::from_secs(3)).await;
-                })
-            };
-            
-            // 启动播放位置更新循环（按需启动/停止）
-            let _position_update_handle = {
-                let timer = playback_timer.clone();
-                tokio::spawn(async move {
-                    let mut last_playing_state = false;
-                    let mut update_task: Option<tokio::task::JoinHandle<()>> = None;
-                    
-                    // 状态检查循环，频率较低
-                    let mut state_check_interval = tokio::time::interval(Duration::from_millis(500));
-                    
-                    loop {
-                        state_check_interval.tick().await;
-                        let is_playing = timer.is_playing();
-                        
-                        // 播放状态变化时启动或停止更新任务
-                        if is_playing != last_playing_state {
-                            if is_playing {
-                                // 开始播放，启动高频更新任务
-                                if update_task.is_none() {
-                                    let timer_clone = timer.clone();
-                                    update_task = Some(tokio::spawn(async move {
-                                        let mut interval = tokio::time::interval(Duration::from_millis(100));
-                                        
-                                        while timer_clone.is_playing() {
-                                            interval.tick().await;
-                                            timer_clone.update_internal_position();
-                                        }
-                                    }));
-                                }
-                            } else {
-                                // 停止播放，取消更新任务
-                                if let Some(task) = update_task.take() {
-                                    task.abort();
-                                }
-                            }
-                            last_playing_state = is_playing;
-                        }
-                    }
-                })
-            };
-            
-            // 主事件循环
-            loop {
-                tokio::select! {
-                    // 处理播放事件
-                    result = playback_event_receiver.changed() => {
-                        if result.is_ok() {
-                            let event = playback_event_receiver.borrow().clone();
-                            lyrics_manager.handle_playback_event(event).await;
-                        }
-                    }
-                    
-                    // 处理媒体事件
-                    result = media_event_receiver.changed() => {
-                        if result.is_ok() {
-                            let event = media_event_receiver.borrow().clone();
-                            match &event {
-                                MediaEvent::InfoUpdated(media_info) => {
-                                    // 同步播放位置到定时器
-                                    playback_timer.sync_with_media(media_info).await;
-                                    
-                                    // 更新应用状态
-                                    {
-                                        let mut state = app_state.write().await;
-                                        state.media_info = Some(media_info.clone());
-                                        state.current_position = playback_timer.get_current_position();
-                                        state.last_updated = Instant::now();
-                                    }
-                                }
-                                _ => {}
-                            }
-                            
-                            // 传递给歌词管理器
-                            lyrics_manager.handle_media_event(event).await;
-                        }
-                    }
-                    
-                    // 处理歌词事件
-                    result = lyrics_event_receiver.changed() => {
-                        if result.is_ok() {
-                            let event = lyrics_event_receiver.borrow().clone();
-                            match event {
-                                LyricsEvent::LoadingStarted { song_info: _ } => {
-                                    let mut state = app_state.write().await;
-                                    state.lyrics_state.is_loading = true;
-                                    // 清理当前歌词状态，防止显示旧数据
-                                    state.lyrics_state.current_lyrics = None;
-                                    state.lyrics_state.current_line = None;
-                                    state.last_updated = Instant::now();
-                                }
-                                LyricsEvent::LoadingCompleted { song_info: _, ref lyrics } => {
-                                    let mut state = app_state.write().await;
-                                    state.lyrics_state.current_lyrics = Some(lyrics.clone());
-                                    state.lyrics_state.is_loading = false;
-                                    state.last_updated = Instant::now();
-                                }
-                                LyricsEvent::LoadingFailed { song_info: _, error: _ } => {
-                                    let mut state = app_state.write().await;
-                                    state.lyrics_state.is_loading = false;
-                                    // 加载失败时清理歌词状态
-                                    state.lyrics_state.current_lyrics = None;
-                                    state.lyrics_state.current_line = None;
-                                    state.last_updated = Instant::now();
-                                }
-                                LyricsEvent::CurrentLineUpdated { ref line, position } => {
-                                    let mut state = app_state.write().await;
-                                    state.lyrics_state.current_line = line.clone();
-                                    state.current_position = position;
-                                    state.last_updated = Instant::now();
-                                }
-                                LyricsEvent::Cleared => {
-                                    let mut state = app_state.write().await;
-                                    state.lyrics_state = LyricsState::default();
-                                    state.last_updated = Instant::now();
-                                }
-                            }
-                        }
-                    }
-                    
-                    // 智能状态更新（根据播放状态调整更新频率）
-                    _ = tokio::time::sleep(Self::get_update_interval(&playback_timer)) => {
-                        let current_state = {
-                            let mut state = app_state.write().await;
-                            
-                            let is_playing = playback_timer.is_playing();
-                            
-                            // 只有在播放时才更新播放位置和歌词行
-                            if is_playing {
-                                state.current_position = playback_timer.get_current_position();
-                                
-                                // 实时更新歌词行（仅在播放时）
-                                if state.lyrics_state.current_lyrics.is_some() {
-                                    if let Some(ref lyrics) = state.lyrics_state.current_lyrics {
-                                        let current_line = crate::lyrics::LyricsData::get_current_lyrics_line(
-                                            lyrics, 
-                                            state.current_position
-                                        );
-                                        
-                                        // 只有在歌词行变化时才更新
-                                        if state.lyrics_state.current_line != current_line {
-                                            state.lyrics_state.current_line = current_line;
-                                        }
-                                    }
-                                }
-                                
-                                state.last_updated = Instant::now();
-                            }
-                            // 暂停或停止时不更新位置和歌词，保持当前状态
-                            
-                            state.clone()
-                        };
-                        
-                        // 发送状态更新
-                        let _ = state_update_sender.send(current_state);
-                    }
-                }
+            self.init_error = Some(e);
+            return;
+        }
+        self.init_error = None;
+        self.init_retry_at = None;
+        if let Some(e) = &self.widget.event_hook_error {
+            warn!("{}，改为每 {:?} 轮询刷新窗口位置和置顶", e, TaskbarWidget::POLLING_FALLBACK_INTERVAL);
+        }
+
+        // 在小组件初始化后设置全局指针供事件钩子使用
+        set_widget_pointer(&self.widget);
+        
+        // 窗口不在任务栏显示按钮，通过托盘图标提供控制入口
+        if self.tray.is_none() {
+            match TrayIcon::new("任务栏歌词") {
+                Ok(tray) => self.tray = Some(tray),
+                Err(e) => warn!("{}，将不显示托盘图标", e),
             }
-        });
+        }
+
+        // 立即触发重绘以显示内容
+        self.widget.request_redraw();
+        
+        // 设置事件循环为持续运行模式，20fps更新频率
+        let next_frame_time = Instant::now() + Duration::from_millis(50);
+        event_loop.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(next_frame_time));
+    }
+    
+    /// 获取当前媒体信息
+    pub fn current_media_info(&self) -> Option<MediaInfo> {
+        self.current_state.media_info.clone()
+    }
+    
+    /// 获取当前播放的歌曲信息
+    pub fn current_song(&self) -> Option<SongInfo> {
+        self.current_state.current_song()
     }
     
     /// 更新UI状态（从状态通道获取最新状态）
@@ -329,6 +336,9 @@ impl App {
             false
         };
         
+        // 暂停时冻结滚动，恢复时按配置继续或重新开始
+        self.widget.set_playing(is_playing);
+        
         // 只有在播放时才实时更新播放位置
         if is_playing {
             if let Some(ref mut media_info) = self.current_state.media_info {
@@ -340,18 +350,61 @@ impl App {
 
         // 更新widget状态
         let old_lyrics_line = self.widget.current_lyrics_line.clone();
+        let old_next_line = self.widget.next_lyrics_line.clone();
+        let old_line_index = self.widget.current_line_index;
         let old_media = self.widget.current_media.clone();
         let old_loading = self.widget.lyrics_loading;
+        let old_service_error = self.widget.service_error.clone();
+        let old_unsynced_text = self.widget.unsynced_full_text();
+        let had_lyrics = self.widget.current_lyrics.is_some();
         
+        self.widget.service_error = self.current_state.service_error.clone();
         self.widget.current_media = self.current_state.media_info.clone();
+        self.widget.track_playback_status();
         self.widget.current_lyrics = self.current_state.lyrics_state.current_lyrics.clone();
+        self.widget.sync_word_timing();
         self.widget.lyrics_loading = self.current_state.lyrics_state.is_loading;
         self.widget.current_lyrics_line = self.current_state.lyrics_state.current_line.clone();
+        self.widget.next_lyrics_line = self.current_state.lyrics_state.next_line.clone();
+        self.widget.current_line_index = self.current_state.lyrics_state.current_line_index;
+        self.widget.before_first_line = self.current_state.lyrics_state.before_first_line;
+        
+        // 记录切歌时间，用于切歌后短暂显示歌曲信息
+        let song_changed = match (&old_media, &self.widget.current_media) {
+            (Some(old), Some(new)) => SongInfo::new(&old.title, &old.artist) != SongInfo::new(&new.title, &new.artist),
+            (None, Some(_)) => true,
+            _ => false,
+        };
+        if song_changed {
+            self.widget.song_changed_at = Some(Instant::now());
+            if let (Some(tray), Some(media)) = (&self.tray, &self.widget.current_media) {
+                tray.set_tooltip(&format_song_display(&self.widget.song_display_template, &media.title, &media.artist));
+            }
+        }
+        
+        // 记录歌词加载完成时间，用于短暂显示匹配信息
+        if !had_lyrics && self.widget.current_lyrics.is_some() {
+            self.widget.lyrics_loaded_at = Some(Instant::now());
+        }
         
-        // 检查内容是否发生变化
+        // 同步加载开始时间，用于加载动画
+        if let Some(load_started_at) = self.current_state.lyrics_state.load_started_at {
+            self.widget.last_lyrics_update = load_started_at;
+        }
+        
+        // 检查内容是否发生变化（播放位置每帧都在推进，不算媒体信息变化）
+        let media_changed = match (&old_media, &self.widget.current_media) {
+            (Some(old), Some(new)) => !old.content_eq(new),
+            (old, new) => old.is_some() != new.is_some(),
+        };
+        // 重复行重新触发：文本相同但已是时间轴上的下一次出现
+        let line_retriggered = self.widget.current_line_index.is_some() && old_line_index != self.widget.current_line_index;
         let content_changed = old_lyrics_line != self.widget.current_lyrics_line ||
-                             old_media != self.widget.current_media ||
-                             old_loading != self.widget.lyrics_loading;
+                             line_retriggered ||
+                             old_next_line != self.widget.next_lyrics_line ||
+                             media_changed ||
+                             old_loading != self.widget.lyrics_loading ||
+                             old_service_error != self.widget.service_error;
         
         if content_changed {
             self.widget.mark_content_changed();
@@ -362,41 +415,31 @@ impl App {
             // 只有在歌词内容真正变化时才重新初始化滚动
             let should_init_scroll = if let Some(current_line) = &self.widget.current_lyrics_line {
                 // 检查是否是新的歌词行（避免重复初始化）
-                old_lyrics_line.as_ref() != Some(current_line)
+                old_lyrics_line.as_ref() != Some(current_line) || line_retriggered
             } else {
                 false
             };
             
             if should_init_scroll {
-                let current_line = self.widget.current_lyrics_line.clone().unwrap();
                 // 使用固定时间作为滚动时间（后续可以优化为动态获取）
-                let time_to_next_line = Some(Duration::from_secs(8)); // 8秒滚动时间
-                self.widget.init_scroll_for_text(&current_line, time_to_next_line);
+                self.widget.init_scroll_for_current_line(Some(TaskbarWidget::LINE_SCROLL_DURATION));
+            } else if let Some(text) = self.widget.unsynced_full_text().filter(|text| old_unsynced_text.as_ref() != Some(text)) {
+                // 纯文本歌词在整首歌的时长内滚动完
+                let song_duration = self.widget.current_media.as_ref().and_then(|media| media.duration);
+                self.widget.init_scroll_for_text(&text, song_duration);
             }
         }
         
-        // 根据播放状态更新窗口可见性
+        // 根据播放状态和桌面预览状态更新窗口可见性
+        self.widget.update_desktop_peek();
         self.widget.update_window_visibility();
     }
 }
 
 impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-        
         // 初始化小组件
-        if let Err(_) = self.widget.initialize(event_loop) {
-            return;
-        }
-
-        // 在小组件初始化后设置全局指针供事件钩子使用
-        set_widget_pointer(&self.widget);
-
-        // 立即触发重绘以显示内容
-        self.widget.request_redraw();
-        
-        // 设置事件循环为持续运行模式，20fps更新频率
-        let next_frame_time = Instant::now() + Duration::from_millis(50);
-        event_loop.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(next_frame_time));
+        self.try_initialize(event_loop);
     }
 
     fn window_event(
@@ -407,14 +450,31 @@ impl ApplicationHandler for App {
     ) {
         match event {
             WindowEvent::CloseRequested => {
+                let stats = self.widget.topmost_stats();
+                info!("置顶统计: 重新置顶 {} 次，多余 {} 次 ({:.1}%)",
+                      stats.reasserted, stats.redundant, stats.reassert_ratio() * 100.0);
                 self.widget.cleanup();
                 event_loop.exit();
             }
             WindowEvent::RedrawRequested => {
                 let _ = self.widget.draw_content();
             }
-            WindowEvent::MouseInput { .. } => {
-                // 处理鼠标点击
+            WindowEvent::ScaleFactorChanged { scale_factor, mut inner_size_writer } => {
+                info!("显示器缩放比例变化: {} -> {}", self.widget.scale_factor, scale_factor);
+                let (width, height) = self.widget.apply_scale_factor(scale_factor);
+                // 使用按任务栏计算的尺寸，而不是 winit 建议的尺寸
+                let _ = inner_size_writer.request_inner_size(PhysicalSize::new(width, height));
+                self.widget.request_redraw();
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_x = position.x;
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                // 左键松开时按点击区域控制播放，命令交给后台运行时执行
+                if state == ElementState::Released && button == MouseButton::Left {
+                    let control = self.widget.media_control_at(self.cursor_x);
+                    self.engine.send_command(EngineCommand::Media(control));
+                }
                 self.widget.ensure_topmost();
             }
             _ => {}
@@ -424,14 +484,40 @@ impl ApplicationHandler for App {
     fn about_to_wait(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         let now = Instant::now();
         
+        // 等待重试初始化
+        if let Some(retry_at) = self.init_retry_at {
+            if now >= retry_at {
+                self.try_initialize(event_loop);
+            } else {
+                event_loop.set_control_flow(winit::event_loop::ControlFlow::WaitUntil(retry_at));
+            }
+            return;
+        }
+        
+        // 处理托盘菜单命令
+        if self.handle_tray_commands(event_loop) {
+            return;
+        }
+        #[cfg(feature = "settings-window")]
+        self.handle_saved_settings();
+        
         // 更新UI状态（从状态通道获取）
         self.update_ui_state();
         
+        // 电源切换后按新的省电状态重绘
+        if self.widget.update_power_source() {
+            info!("供电状态变化: {}", if self.widget.on_battery { "电池" } else { "交流电源" });
+        }
+        
         // 如果widget正在滚动，需要更新滚动状态
         if self.widget.is_scrolling {
             self.widget.update_scroll();
         }
         
+        // 同步滚动状态，供外部读取
+        self.current_state.is_scrolling = self.widget.is_scrolling;
+        self.current_state.scroll_progress = self.widget.get_scroll_progress();
+        
         // 获取当前播放状态
         let is_playing = self.playback_timer.is_playing();
         
@@ -451,10 +537,16 @@ impl ApplicationHandler for App {
             if self.widget.position_update_pending {
                 self.widget.schedule_position_update();
             }
+
+            // 处理节流期间积压的置顶请求
+            self.widget.flush_pending_topmost();
+
+            // 没有事件钩子时定时刷新位置和置顶
+            self.widget.poll_position_fallback();
             
             // 根据播放状态和滚动状态调整更新频率
             let next_frame_time = if is_playing || self.widget.is_scrolling {
-                now + Duration::from_millis(50) // 播放或滚动时使用高频率（20fps）等待下次更新
+                now + self.widget.frame_interval() // 播放或滚动时使用高频率（20fps，省电时 10fps）等待下次更新
             } else {
                 now + Duration::from_millis(500) // 暂停且未滚动时使用低频率（2fps）
             };
@@ -471,4 +563,39 @@ impl Default for App {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_includes_parsed_timeline() {
+        let mut state = AppState {
+            media_info: Some(MediaInfo {
+                title: "Song".to_string(),
+                artist: "Artist".to_string(),
+                ..Default::default()
+            }),
+            current_position: Duration::from_millis(1500),
+            ..Default::default()
+        };
+        state.lyrics_state.current_line = Some("one".to_string());
+        state.lyrics_state.current_lyrics = Some(LyricsData {
+            original: Some("[00:01.00]one\n[00:02.00]two".to_string()),
+            has_lyrics: true,
+            source: LyricsSource::QQMusic,
+            ..Default::default()
+        });
+
+        let snapshot = SessionSnapshot::capture(&state);
+        assert_eq!(snapshot.song, Some(SongInfo::new("Song", "Artist")));
+        assert_eq!(snapshot.source, Some(LyricsSource::QQMusic));
+        assert_eq!(snapshot.timeline, vec![(1000, "one".to_string()), (2000, "two".to_string())]);
+        assert_eq!(snapshot.position_ms, 1500);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: SessionSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.current_line.as_deref(), Some("one"));
+    }
+}
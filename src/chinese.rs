@@ -0,0 +1,172 @@
+//! 简繁中文转换
+//!
+//! 大陆歌词接口返回的多为简体歌词，港台用户可选择转为繁体显示（或反之）。
+//! 对照表为内置的常用字逐字映射，加上逐字映射会转错的常用词（如「头发」「皇后」「公里」），
+//! 随 `chinese-conversion` 特性编译；关闭该特性时转换不生效。
+
+/// 显示歌词时的简繁转换方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChineseConversion {
+    /// 不转换
+    #[default]
+    None,
+    /// 转为简体
+    ToSimplified,
+    /// 转为繁体
+    ToTraditional,
+}
+
+impl ChineseConversion {
+    /// 按转换方式处理文本（对照表之外的字符原样保留）
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            ChineseConversion::None => text.to_string(),
+            ChineseConversion::ToSimplified => to_simplified(text),
+            ChineseConversion::ToTraditional => to_traditional(text),
+        }
+    }
+}
+
+/// 转为简体
+#[cfg(feature = "chinese-conversion")]
+pub fn to_simplified(text: &str) -> String {
+    table::convert(text, &table::TO_SIMPLIFIED, &table::PHRASES_TO_SIMPLIFIED)
+}
+
+/// 转为繁体
+#[cfg(feature = "chinese-conversion")]
+pub fn to_traditional(text: &str) -> String {
+    table::convert(text, &table::TO_TRADITIONAL, &table::PHRASES)
+}
+
+/// 未启用 `chinese-conversion` 特性时原样返回
+#[cfg(not(feature = "chinese-conversion"))]
+pub fn to_simplified(text: &str) -> String {
+    text.to_string()
+}
+
+/// 未启用 `chinese-conversion` 特性时原样返回
+#[cfg(not(feature = "chinese-conversion"))]
+pub fn to_traditional(text: &str) -> String {
+    text.to_string()
+}
+
+#[cfg(feature = "chinese-conversion")]
+mod table {
+    use std::collections::HashMap;
+    use std::sync::LazyLock;
+
+    /// 简体到繁体逐字对照（与 `TRADITIONAL` 按位置一一对应，一简对多繁的字取最常用的写法）
+    const SIMPLIFIED: &str = "\
+        爱碍罢摆败办帮宝报备贝笔边变标别宾补参惨蚕灿仓层产长尝场厂车彻尘陈衬称惩诚迟齿冲\
+        虫丑处础传创纯词辞聪从丛错达带单担胆当党导灯邓敌递点电垫调钓顶东动冻斗独读断对队\
+        顿夺鹅儿尔发罚范饭访纺飞废费纷坟奋愤丰风疯凤妇复负该盖赶刚钢纲岗个给沟构购够顾关\
+        观馆惯广归龟规贵国过还汉号红后护华画话划怀欢环换唤黄挥辉汇会绘浑获货祸击机积鸡极\
+        级几纪记际继济价驾坚间艰检减剑见荐将奖讲酱胶骄娇脚较阶节结洁紧尽进仅惊经静镜竞旧\
+        举剧据决觉军开凯课恳夸块宽旷矿亏扩阔来蓝栏拦烂劳乐泪类离礼丽历厉励连联怜恋练炼脸\
+        两辆凉谅疗辽邻临灵岭领刘龙楼芦卢陆录驴虑乱论轮罗萝锣骆妈马骂买卖迈满猫门们梦弥谜\
+        绵庙灭鸣铭谋亩纳难脑恼闹内腻鸟宁农浓欧盘赔鹏凭评齐骑岂启气弃铅谦钱浅枪墙抢桥乔侨\
+        亲轻倾顷庆穷区驱躯权劝确让饶热认荣软锐润洒伞丧扫涩杀纱晒闪伤赏烧绍设摄绅审声绳胜\
+        圣师诗时识实势适释视试饰寿书输术树数帅双谁税顺说丝诉随岁孙损锁态坛叹谈汤涛讨腾题\
+        体条铁听厅头图团涂颓袜弯湾万网为违围伟卫纬稳问闻窝乌无误务雾牺习戏细虾吓县现线宪\
+        乡详响项萧晓啸协胁写谢兴选学寻训讯压鸦鸭亚烟严颜盐艳验扬阳杨养样摇遥药爷页业叶医\
+        仪遗亿忆艺议义异阴银隐饮应营赢拥优忧犹邮鱼渔与语狱预誉园员圆缘远愿约跃阅云运韵杂\
+        灾载赞脏枣责则泽贼赠闸战张涨帐账胀赵这针侦诊阵镇争挣睁证郑织职执纸质钟终种众肿轴\
+        昼皱猪烛嘱筑专砖转赚装壮状妆准浊资总纵钻么吗飘请温烦绝缠绕续于挂牵诺毁献着里没恶\
+        闭闲阁哗晕忏悬挤缝编缩绿组维综诀译谊谎谱讽诸谨谣贫贯贴贱贸赐赖赌赎趋迁迹逊销锋锦\
+        键闯险须顽颗频额饥饱饿馒驶鲜鸽龄呜吴呐厌厕袭觅览誊轨轰辈辑舰舱苏荡莱莲蒋坏坝垄扑\
+        扰抚拣拟择挡捞捡搅携摊撑泼洼浏测涌涧渐湿滚滞滤滥滨潜炉焕狮猎玛琐疮痒痴瘫盏盗睐矫\
+        祷窃窍竖笋笼签简粮纤纠纫纹纽绊绎绑绒络绞统绢绩绪绸绽缀缓缔缚缤缴";
+
+    /// 繁体写法
+    const TRADITIONAL: &str = "\
+        愛礙罷擺敗辦幫寶報備貝筆邊變標別賓補參慘蠶燦倉層產長嘗場廠車徹塵陳襯稱懲誠遲齒衝\
+        蟲醜處礎傳創純詞辭聰從叢錯達帶單擔膽當黨導燈鄧敵遞點電墊調釣頂東動凍鬥獨讀斷對隊\
+        頓奪鵝兒爾發罰範飯訪紡飛廢費紛墳奮憤豐風瘋鳳婦復負該蓋趕剛鋼綱崗個給溝構購夠顧關\
+        觀館慣廣歸龜規貴國過還漢號紅後護華畫話劃懷歡環換喚黃揮輝匯會繪渾獲貨禍擊機積雞極\
+        級幾紀記際繼濟價駕堅間艱檢減劍見薦將獎講醬膠驕嬌腳較階節結潔緊盡進僅驚經靜鏡競舊\
+        舉劇據決覺軍開凱課懇誇塊寬曠礦虧擴闊來藍欄攔爛勞樂淚類離禮麗歷厲勵連聯憐戀練煉臉\
+        兩輛涼諒療遼鄰臨靈嶺領劉龍樓蘆盧陸錄驢慮亂論輪羅蘿鑼駱媽馬罵買賣邁滿貓門們夢彌謎\
+        綿廟滅鳴銘謀畝納難腦惱鬧內膩鳥寧農濃歐盤賠鵬憑評齊騎豈啟氣棄鉛謙錢淺槍牆搶橋喬僑\
+        親輕傾頃慶窮區驅軀權勸確讓饒熱認榮軟銳潤灑傘喪掃澀殺紗曬閃傷賞燒紹設攝紳審聲繩勝\
+        聖師詩時識實勢適釋視試飾壽書輸術樹數帥雙誰稅順說絲訴隨歲孫損鎖態壇嘆談湯濤討騰題\
+        體條鐵聽廳頭圖團塗頹襪彎灣萬網為違圍偉衛緯穩問聞窩烏無誤務霧犧習戲細蝦嚇縣現線憲\
+        鄉詳響項蕭曉嘯協脅寫謝興選學尋訓訊壓鴉鴨亞煙嚴顏鹽艷驗揚陽楊養樣搖遙藥爺頁業葉醫\
+        儀遺億憶藝議義異陰銀隱飲應營贏擁優憂猶郵魚漁與語獄預譽園員圓緣遠願約躍閱雲運韻雜\
+        災載讚髒棗責則澤賊贈閘戰張漲帳賬脹趙這針偵診陣鎮爭掙睜證鄭織職執紙質鐘終種眾腫軸\
+        晝皺豬燭囑築專磚轉賺裝壯狀妝準濁資總縱鑽麼嗎飄請溫煩絕纏繞續於掛牽諾毀獻著裡沒惡\
+        閉閑閣嘩暈懺懸擠縫編縮綠組維綜訣譯誼謊譜諷諸謹謠貧貫貼賤貿賜賴賭贖趨遷跡遜銷鋒錦\
+        鍵闖險須頑顆頻額飢飽餓饅駛鮮鴿齡嗚吳吶厭廁襲覓覽謄軌轟輩輯艦艙蘇蕩萊蓮蔣壞壩壟撲\
+        擾撫揀擬擇擋撈撿攪攜攤撐潑窪瀏測湧澗漸濕滾滯濾濫濱潛爐煥獅獵瑪瑣瘡癢癡癱盞盜睞矯\
+        禱竊竅豎筍籠簽簡糧纖糾紉紋紐絆繹綁絨絡絞統絹績緒綢綻綴緩締縛繽繳";
+
+    pub(super) static TO_TRADITIONAL: LazyLock<HashMap<char, char>> =
+        LazyLock::new(|| SIMPLIFIED.chars().zip(TRADITIONAL.chars()).collect());
+
+    pub(super) static TO_SIMPLIFIED: LazyLock<HashMap<char, char>> =
+        LazyLock::new(|| TRADITIONAL.chars().zip(SIMPLIFIED.chars()).collect());
+
+    /// 逐字对照会转错的词（简体, 繁体），优先于逐字对照：发、后、里在这些词中不是發、後、裡
+    pub(super) const PHRASES: [(&str, &str); 25] = [
+        ("头发", "頭髮"), ("理发", "理髮"), ("白发", "白髮"), ("黑发", "黑髮"), ("金发", "金髮"),
+        ("长发", "長髮"), ("短发", "短髮"), ("秀发", "秀髮"), ("毛发", "毛髮"), ("发型", "髮型"),
+        ("发丝", "髮絲"), ("皇后", "皇后"), ("王后", "王后"), ("太后", "太后"), ("天后", "天后"),
+        ("后妃", "后妃"), ("公里", "公里"), ("英里", "英里"), ("海里", "海里"), ("千里", "千里"),
+        ("万里", "萬里"), ("里程", "里程"), ("故里", "故里"), ("邻里", "鄰里"), ("乡里", "鄉里"),
+    ];
+
+    /// 繁体转简体时的词表（`PHRASES` 反向）
+    pub(super) static PHRASES_TO_SIMPLIFIED: LazyLock<Vec<(&str, &str)>> =
+        LazyLock::new(|| PHRASES.iter().map(|&(simplified, traditional)| (traditional, simplified)).collect());
+
+    /// 按词表和逐字对照转换：当前位置以词表中的词开头时整词替换，否则逐字转换
+    pub(super) fn convert(text: &str, chars: &HashMap<char, char>, phrases: &[(&str, &str)]) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            match phrases.iter().find(|(from, _)| rest.starts_with(from)) {
+                Some((from, to)) => {
+                    result.push_str(to);
+                    rest = &rest[from.len()..];
+                }
+                None => {
+                    result.push(chars.get(&c).copied().unwrap_or(c));
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        result
+    }
+
+    #[cfg(test)]
+    pub(super) fn is_aligned() -> bool {
+        SIMPLIFIED.chars().count() == TRADITIONAL.chars().count()
+    }
+}
+
+#[cfg(all(test, feature = "chinese-conversion"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_round_trip() {
+        assert!(table::is_aligned());
+        assert_eq!(ChineseConversion::ToTraditional.apply("后来我们说爱你"), "後來我們說愛你");
+        assert_eq!(ChineseConversion::ToSimplified.apply("後來我們說愛你"), "后来我们说爱你");
+        assert_eq!(ChineseConversion::ToTraditional.apply("Hello 你好"), "Hello 你好");
+        assert_eq!(ChineseConversion::None.apply("后来"), "后来");
+    }
+
+    #[test]
+    fn test_phrases_take_precedence() {
+        assert_eq!(to_traditional("头发"), "頭髮");
+        assert_eq!(to_traditional("皇后"), "皇后");
+        assert_eq!(to_traditional("公里"), "公里");
+        // 同一句中的其他用法仍按逐字对照转换
+        assert_eq!(to_traditional("然后剪了头发，走了一公里回家里"), "然後剪了頭髮，走了一公里回家裡");
+        assert_eq!(to_traditional("发现"), "發現");
+
+        assert_eq!(to_simplified("頭髮"), "头发");
+        assert_eq!(to_simplified("皇后走了萬里"), "皇后走了万里");
+    }
+}
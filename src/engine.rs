@@ -0,0 +1,555 @@
+use crate::app::AppState;
+use crate::lyrics::{LyricsManager, LyricsManagerConfig, LyricsService, LyricsServiceConfig, LyricsEvent, LyricsState};
+use crate::system::{MediaControl, MediaMonitor, MediaMonitorConfig, MediaEvent, PlaybackTimer, PlaybackEvent};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, RwLock};
+use tracing::{error, info, warn};
+
+/// 后台服务异常退出后的最大重启次数
+const MAX_SERVICE_RESTARTS: u32 = 3;
+
+/// 后台服务连续正常运行超过该时长后清零重启计数（偶发故障不会累积到放弃重启）
+const HEALTHY_RUN_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+/// 媒体信息的兜底轮询间隔（变化由系统媒体事件即时推送）
+const MEDIA_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 歌词引擎配置，后台服务每次（重新）启动时按此创建
+#[derive(Debug, Clone, Default)]
+pub struct EngineConfig {
+    /// 歌词服务配置（歌词源、缓存、代理、本地目录、歌词包等）
+    pub service: LyricsServiceConfig,
+    /// 歌词管理器配置
+    pub manager: LyricsManagerConfig,
+    /// 媒体监测配置
+    pub media: MediaMonitorConfig,
+}
+
+/// 发送给后台管线的控制命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineCommand {
+    /// 清空歌词、重置定时器并重新检测当前媒体
+    Reset,
+    /// 控制当前播放器（播放/暂停、切歌）
+    Media(MediaControl),
+    /// 启用或停用网络歌词源并重新加载当前歌词
+    SetSources { netease: bool, qqmusic: bool },
+    /// 修改歌词时间轴整体偏移（毫秒）并重新加载当前歌词
+    SetLyricsOffset(i64),
+    /// 切换到下一个网络歌词源并重新加载当前歌词
+    SwitchSource,
+}
+
+/// 歌词引擎
+/// 负责媒体监控、播放定时和歌词管理的后台管线，不依赖任何窗口或渲染组件
+pub struct LyricsEngine {
+    /// 播放定时器（用于实时获取播放位置）
+    playback_timer: Arc<PlaybackTimer>,
+    /// 状态更新接收器
+    state_update_receiver: watch::Receiver<AppState>,
+    /// 控制命令发送器
+    command_sender: mpsc::UnboundedSender<EngineCommand>,
+}
+
+impl LyricsEngine {
+    /// 创建引擎并在后台线程中按配置启动所有服务
+    pub fn start(config: EngineConfig) -> Self {
+        // 创建播放定时器
+        let (playback_timer, playback_event_receiver) = PlaybackTimer::new();
+        let playback_timer = Arc::new(playback_timer);
+        
+        // 创建状态更新通道
+        let (state_update_sender, state_update_receiver) = watch::channel(AppState::default());
+        let app_state = Arc::new(RwLock::new(AppState::default()));
+        
+        // 创建控制命令通道
+        let (command_sender, command_receiver) = mpsc::unbounded_channel();
+        
+        // 启动事件处理循环（由看门狗监控，异常退出时自动重启）
+        let timer = playback_timer.clone();
+        thread::spawn(move || {
+            Self::run_with_watchdog(
+                config,
+                timer,
+                app_state,
+                state_update_sender,
+                playback_event_receiver,
+                command_receiver,
+            );
+        });
+        
+        Self {
+            playback_timer,
+            state_update_receiver,
+            command_sender,
+        }
+    }
+    
+    /// 发送控制命令
+    pub fn send_command(&self, command: EngineCommand) {
+        let _ = self.command_sender.send(command);
+    }
+    
+    /// 完全重置：清空歌词和定时器，并为当前播放的歌曲重新加载歌词
+    pub fn reset(&self) {
+        self.send_command(EngineCommand::Reset);
+    }
+    
+    /// 获取播放定时器
+    pub fn playback_timer(&self) -> Arc<PlaybackTimer> {
+        self.playback_timer.clone()
+    }
+    
+    /// 订阅状态更新
+    pub fn subscribe(&self) -> watch::Receiver<AppState> {
+        self.state_update_receiver.clone()
+    }
+    
+    /// 根据播放状态获取合适的更新间隔
+    fn get_update_interval(playback_timer: &Arc<PlaybackTimer>) -> Duration {
+        if playback_timer.is_playing() {
+            Duration::from_millis(50) // 播放时高频更新
+        } else {
+            Duration::from_millis(500) // 非播放时低频更新
+        }
+    }
+    
+    /// 看门狗：运行事件处理循环，panic 或异常退出时有限次重启，放弃时写入 `service_error`
+    fn run_with_watchdog(
+        config: EngineConfig,
+        playback_timer: Arc<PlaybackTimer>,
+        app_state: Arc<RwLock<AppState>>,
+        state_update_sender: watch::Sender<AppState>,
+        playback_event_receiver: watch::Receiver<PlaybackEvent>,
+        mut command_receiver: mpsc::UnboundedReceiver<EngineCommand>,
+    ) {
+        let mut restarts = 0;
+        
+        loop {
+            let started_at = Instant::now();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                Self::run_event_loop(
+                    &config,
+                    playback_timer.clone(),
+                    app_state.clone(),
+                    &state_update_sender,
+                    playback_event_receiver.clone(),
+                    &mut command_receiver,
+                )
+            }));
+            
+            // 事件循环正常情况下不会退出，任何返回都视为故障
+            let reason = match result {
+                Ok(Ok(())) => "后台服务意外退出".to_string(),
+                Ok(Err(e)) => e,
+                Err(payload) => format!("后台服务崩溃: {}", Self::panic_message(payload.as_ref())),
+            };
+            
+            if started_at.elapsed() >= HEALTHY_RUN_PERIOD {
+                restarts = 0;
+            }
+            
+            if restarts >= MAX_SERVICE_RESTARTS {
+                error!("{}，已重启 {} 次，放弃重启", reason, restarts);
+                let state = {
+                    let mut state = app_state.blocking_write();
+                    state.service_error = Some(reason);
+                    state.last_updated = Instant::now();
+                    state.clone()
+                };
+                let _ = state_update_sender.send(state);
+                return;
+            }
+            
+            restarts += 1;
+            warn!("{}，{} 秒后重启 ({}/{})", reason, restarts, restarts, MAX_SERVICE_RESTARTS);
+            thread::sleep(Duration::from_secs(restarts as u64));
+        }
+    }
+    
+    /// 提取 panic 信息
+    fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+        payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "未知错误".to_string())
+    }
+    
+    /// 后台任务结束的原因（任务正常情况下不会退出，任何结束都视为故障）
+    fn task_exit_reason(task: &str, result: Result<(), tokio::task::JoinError>) -> String {
+        match result {
+            Ok(()) => format!("{}任务意外退出", task),
+            Err(e) if e.is_panic() => format!("{}任务崩溃: {}", task, Self::panic_message(e.into_panic().as_ref())),
+            Err(e) => format!("{}任务被取消: {}", task, e),
+        }
+    }
+    
+    /// 事件处理循环
+    fn run_event_loop(
+        config: &EngineConfig,
+        playback_timer: Arc<PlaybackTimer>,
+        app_state: Arc<RwLock<AppState>>,
+        state_update_sender: &watch::Sender<AppState>,
+        mut playback_event_receiver: watch::Receiver<PlaybackEvent>,
+        command_receiver: &mut mpsc::UnboundedReceiver<EngineCommand>,
+    ) -> Result<(), String> {
+        let rt = tokio::runtime::Runtime::new()
+            .map_err(|e| format!("创建异步运行时失败: {}", e))?;
+        
+        rt.block_on(async {
+            // 初始化媒体监控
+            let (media_monitor, mut media_event_receiver) = MediaMonitor::new();
+            let mut media_monitor = media_monitor.with_config(config.media.clone())
+                .with_allowed_apps(config.manager.media_filter.allowed_apps.clone());
+            media_monitor.initialize().await
+                .map_err(|e| format!("媒体监控初始化失败: {}", e))?;
+            
+            // 初始化歌词服务
+            let lyrics_service = match LyricsService::new(config.service.clone()) {
+                Ok(service) => service,
+                Err(e) => {
+                    return Err(format!("歌词服务初始化失败: {}", e));
+                }
+            };
+            
+            // 创建歌词管理器
+            let (lyrics_manager, mut lyrics_event_receiver) = LyricsManager::with_config(lyrics_service, config.manager.clone());
+            let lyrics_manager = Arc::new(lyrics_manager);
+            
+            // 后台预热歌词源连接，减少首次切歌的请求延迟
+            {
+                let manager = lyrics_manager.clone();
+                tokio::spawn(async move {
+                    manager.warm_connection().await;
+                });
+            }
+            
+            // 启动媒体监控（播放控制命令转发给监控循环执行）
+            let media_control_sender = media_monitor.control_sender();
+            let mut media_monitor_handle = {
+                let mut monitor = media_monitor;
+                tokio::spawn(async move {
+                    let _ = monitor.start_monitoring(MEDIA_POLL_INTERVAL).await;
+                })
+            };
+            
+            // 启动播放位置更新循环（按需启动/停止）
+            let mut position_update_handle = {
+                let timer = playback_timer.clone();
+                tokio::spawn(async move {
+                    let mut last_playing_state = false;
+                    let mut update_task: Option<tokio::task::JoinHandle<()>> = None;
+                    
+                    // 状态检查循环，频率较低
+                    let mut state_check_interval = tokio::time::interval(Duration::from_millis(500));
+                    
+                    loop {
+                        state_check_interval.tick().await;
+                        let is_playing = timer.is_playing();
+                        
+                        // 播放状态变化时启动或停止更新任务
+                        if is_playing != last_playing_state {
+                            if is_playing {
+                                // 开始播放，启动高频更新任务
+                                if update_task.is_none() {
+                                    let timer_clone = timer.clone();
+                                    update_task = Some(tokio::spawn(async move {
+                                        let mut interval = tokio::time::interval(Duration::from_millis(100));
+                                        
+                                        while timer_clone.is_playing() {
+                                            interval.tick().await;
+                                            timer_clone.update_internal_position();
+                                        }
+                                    }));
+                                }
+                            } else {
+                                // 停止播放，取消更新任务
+                                if let Some(task) = update_task.take() {
+                                    task.abort();
+                                }
+                            }
+                            last_playing_state = is_playing;
+                        }
+                    }
+                })
+            };
+            
+            // 主事件循环（后台任务结束时返回错误，由看门狗重启整个管线）
+            loop {
+                tokio::select! {
+                    result = &mut media_monitor_handle => {
+                        return Err(Self::task_exit_reason("媒体监控", result));
+                    }
+                    result = &mut position_update_handle => {
+                        return Err(Self::task_exit_reason("播放位置更新", result));
+                    }
+                    
+                    // 处理控制命令
+                    Some(command) = command_receiver.recv() => {
+                        match command {
+                            EngineCommand::Reset => {
+                                info!("收到重置命令");
+                                lyrics_manager.reset().await;
+                                playback_timer.reset().await;
+                                
+                                // 使用最近一次媒体信息重新检测，触发当前歌曲的歌词加载
+                                let latest_event = media_event_receiver.borrow().clone();
+                                if let MediaEvent::InfoUpdated(media_info) = &latest_event {
+                                    playback_timer.sync_with_media(media_info).await;
+                                }
+                                lyrics_manager.handle_media_event(latest_event).await;
+                            }
+                            EngineCommand::Media(control) => {
+                                let _ = media_control_sender.send(control);
+                            }
+                            EngineCommand::SetSources { netease, qqmusic } => {
+                                lyrics_manager.set_network_sources(netease, qqmusic).await;
+                            }
+                            EngineCommand::SetLyricsOffset(offset_ms) => {
+                                lyrics_manager.set_lyrics_offset(offset_ms).await;
+                            }
+                            EngineCommand::SwitchSource => {
+                                lyrics_manager.switch_source().await;
+                            }
+                        }
+                    }
+                    
+                    // 处理播放事件
+                    result = playback_event_receiver.changed() => {
+                        if result.is_ok() {
+                            let event = playback_event_receiver.borrow().clone();
+                            lyrics_manager.handle_playback_event(event).await;
+                        }
+                    }
+                    
+                    // 处理媒体事件
+                    result = media_event_receiver.changed() => {
+                        if result.is_ok() {
+                            let event = media_event_receiver.borrow().clone();
+                            match &event {
+                                MediaEvent::InfoUpdated(media_info) => {
+                                    // 同步播放位置到定时器
+                                    playback_timer.sync_with_media(media_info).await;
+                                    
+                                    // 更新应用状态
+                                    {
+                                        let mut state = app_state.write().await;
+                                        state.media_info = Some(media_info.clone());
+                                        state.lyrics_state.is_stopped = false;
+                                        state.current_position = playback_timer.get_current_position();
+                                        state.last_updated = Instant::now();
+                                    }
+                                }
+                                _ => {}
+                            }
+                            
+                            // 传递给歌词管理器
+                            lyrics_manager.handle_media_event(event).await;
+                        }
+                    }
+                    
+                    // 处理歌词事件
+                    result = lyrics_event_receiver.changed() => {
+                        if result.is_ok() {
+                            let event = lyrics_event_receiver.borrow().clone();
+                            let mut state = app_state.write().await;
+                            if Self::apply_lyrics_event(&mut state, event, &lyrics_manager, &playback_timer).await {
+                                let _ = state_update_sender.send(state.clone());
+                            }
+                        }
+                    }
+                    
+                    // 智能状态更新（根据播放状态调整更新频率）
+                    _ = tokio::time::sleep(Self::get_update_interval(&playback_timer)) => {
+                        let current_state = {
+                            let mut state = app_state.write().await;
+                            
+                            let is_playing = playback_timer.is_playing();
+                            
+                            // 只有在播放时才更新播放位置和歌词行
+                            if is_playing {
+                                state.current_position = playback_timer.get_current_position();
+                                
+                                // 实时更新歌词行（仅在播放时），复用已解析的时间轴，一次遍历得到所有行信息
+                                if state.lyrics_state.current_lyrics.is_some() {
+                                    let location = lyrics_manager.locate_line(
+                                        &state.lyrics_state.timeline,
+                                        state.current_position,
+                                        &state.lyrics_state.current_line,
+                                        state.lyrics_state.current_line_index,
+                                    ).await;
+                                    
+                                    // 只有在歌词行变化时才更新
+                                    if state.lyrics_state.current_line != location.line {
+                                        state.lyrics_state.current_line = location.line;
+                                    }
+                                    state.lyrics_state.current_line_index = location.index;
+                                    state.lyrics_state.before_first_line = location.before_first_line;
+                                    state.lyrics_state.next_line = location.next_line;
+                                }
+                                
+                                state.last_updated = Instant::now();
+                            }
+                            // 暂停或停止时不更新位置和歌词，保持当前状态
+                            
+                            state.clone()
+                        };
+                        
+                        // 发送状态更新
+                        let _ = state_update_sender.send(current_state);
+                    }
+                }
+            }
+        })
+    }
+
+    /// 把歌词事件应用到应用状态，返回是否需要立即推送状态更新（歌词加载完成时）
+    async fn apply_lyrics_event(
+        state: &mut AppState,
+        event: LyricsEvent,
+        lyrics_manager: &LyricsManager,
+        playback_timer: &PlaybackTimer,
+    ) -> bool {
+        let push_now = matches!(event, LyricsEvent::LoadingCompleted { .. });
+        match event {
+            LyricsEvent::LoadingStarted { song_info: _ } => {
+                state.lyrics_state.is_loading = true;
+                state.lyrics_state.load_started_at = Some(Instant::now());
+                // 清理当前歌词状态，防止显示旧数据
+                state.lyrics_state.current_lyrics = None;
+                state.lyrics_state.timeline = Arc::default();
+                state.lyrics_state.is_synced = false;
+                state.lyrics_state.current_line = None;
+                state.lyrics_state.next_line = None;
+                state.lyrics_state.current_line_index = None;
+                state.lyrics_state.before_first_line = false;
+                state.last_updated = Instant::now();
+            }
+            LyricsEvent::LoadingCompleted { song_info: _, ref lyrics } => {
+                // 按当前播放位置立即定位歌词行（手动更正匹配后无需等待下一次定时更新）
+                state.current_position = playback_timer.get_current_position();
+                // 使用歌词管理器按配置（时间轴来源、偏移）解析好的时间轴，定时更新和会话快照共用
+                state.lyrics_state.timeline = lyrics_manager.current_timeline().await;
+                let location = lyrics_manager
+                    .locate_line(&state.lyrics_state.timeline, state.current_position, &None, None)
+                    .await;
+                state.lyrics_state.current_line = location.line;
+                state.lyrics_state.current_line_index = location.index;
+                state.lyrics_state.next_line = location.next_line;
+                state.lyrics_state.before_first_line = location.before_first_line;
+                state.lyrics_state.is_synced = lyrics.is_synced;
+                state.lyrics_state.current_lyrics = Some(lyrics.clone());
+                state.lyrics_state.is_loading = false;
+                state.lyrics_state.load_started_at = None;
+                state.last_updated = Instant::now();
+            }
+            LyricsEvent::LoadingFailed { song_info: _, error: _ } => {
+                state.lyrics_state.is_loading = false;
+                state.lyrics_state.load_started_at = None;
+                // 加载失败时清理歌词状态
+                state.lyrics_state.current_lyrics = None;
+                state.lyrics_state.timeline = Arc::default();
+                state.lyrics_state.is_synced = false;
+                state.lyrics_state.current_line = None;
+                state.lyrics_state.next_line = None;
+                state.lyrics_state.current_line_index = None;
+                state.lyrics_state.before_first_line = false;
+                state.last_updated = Instant::now();
+            }
+            LyricsEvent::CurrentLineUpdated { ref line, position } => {
+                // 加载完成事件可能被紧随其后的行更新覆盖，此时从歌词管理器补齐歌词
+                if state.lyrics_state.current_lyrics.is_none() {
+                    state.lyrics_state.current_lyrics = lyrics_manager.get_current_lyrics().await;
+                    state.lyrics_state.timeline = lyrics_manager.current_timeline().await;
+                    state.lyrics_state.is_synced = state.lyrics_state.current_lyrics
+                        .as_ref()
+                        .is_some_and(|lyrics| lyrics.is_synced);
+                    state.lyrics_state.is_loading = false;
+                    state.lyrics_state.load_started_at = None;
+                }
+                state.current_position = position;
+                let location = lyrics_manager.locate_displayed(&state.lyrics_state.timeline, position, line.clone(), None);
+                state.lyrics_state.current_line = location.line;
+                state.lyrics_state.current_line_index = location.index;
+                state.lyrics_state.next_line = location.next_line;
+                state.lyrics_state.before_first_line = location.before_first_line;
+                state.last_updated = Instant::now();
+            }
+            LyricsEvent::Stopped => {
+                // 保留歌词，仅标记为已停止
+                state.lyrics_state.is_stopped = true;
+                state.last_updated = Instant::now();
+            }
+            LyricsEvent::Cleared => {
+                state.lyrics_state = LyricsState::default();
+                state.last_updated = Instant::now();
+            }
+        }
+        push_now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::SessionSnapshot;
+    use crate::lyrics::cache::LyricsCache;
+    use crate::lyrics::{CacheConfig, LyricsData, LyricsServiceBuilder, LyricsSource, SongInfo, TimelineSource};
+    use crate::system::{MediaInfo, PlaybackStatus};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_loading_completed_exports_manager_timeline() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache_config = CacheConfig { cache_dir: temp_dir.path().join("cache"), ..Default::default() };
+        // 缓存中的逐字歌词与 LRC 的时间戳不同，可以区分实际使用的时间轴
+        let lyrics = LyricsData {
+            original: Some("[00:01.00]one\n[00:03.00]two".to_string()),
+            yrc: Some("[900,1000](900,1000,0)one\n[2900,1000](2900,1000,0)two".to_string()),
+            source: LyricsSource::NetEase,
+            has_lyrics: true,
+            is_synced: true,
+            ..Default::default()
+        };
+        LyricsCache::new(cache_config.clone()).unwrap()
+            .put(SongInfo::new("title", "artist"), lyrics)
+            .await
+            .unwrap();
+
+        let service = LyricsServiceBuilder::new()
+            .with_cache_config(cache_config)
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .build()
+            .unwrap();
+        let manager_config = LyricsManagerConfig { timeline_source: TimelineSource::Yrc, ..Default::default() };
+        let (lyrics_manager, _events) = LyricsManager::with_config(service, manager_config);
+        lyrics_manager.handle_media_event(MediaEvent::InfoUpdated(MediaInfo {
+            title: "title".to_string(),
+            artist: "artist".to_string(),
+            position: Some(Duration::from_secs(1)),
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        })).await;
+
+        let lyrics_state = lyrics_manager.get_current_state().await;
+        let event = LyricsEvent::LoadingCompleted {
+            song_info: lyrics_state.current_song.unwrap(),
+            lyrics: lyrics_state.current_lyrics.unwrap(),
+        };
+        let (playback_timer, _playback_events) = PlaybackTimer::new();
+        let mut state = AppState::default();
+        assert!(LyricsEngine::apply_lyrics_event(&mut state, event, &lyrics_manager, &playback_timer).await);
+
+        let expected = vec![(900, "one".to_string()), (2900, "two".to_string())];
+        assert_eq!(*state.lyrics_state.timeline, expected);
+        assert!(Arc::ptr_eq(&state.lyrics_state.timeline, &lyrics_manager.current_timeline().await));
+        assert_eq!(SessionSnapshot::capture(&state).timeline, expected);
+
+        // 重新加载时清空时间轴，避免快照导出上一首歌的时间轴
+        let song_info = SongInfo::new("title", "artist");
+        LyricsEngine::apply_lyrics_event(&mut state, LyricsEvent::LoadingStarted { song_info }, &lyrics_manager, &playback_timer).await;
+        assert!(state.lyrics_state.timeline.is_empty());
+    }
+}
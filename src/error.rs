@@ -0,0 +1,43 @@
+use thiserror::Error;
+
+/// 小组件初始化错误类型
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InitError {
+    #[error("找不到任务栏: {0}")]
+    TaskbarNotFound(String),
+
+    #[error("创建窗口失败: {0}")]
+    WindowCreation(String),
+
+    #[error("初始化渲染器失败: {0}")]
+    RendererInit(String),
+
+    #[error("没有可用的系统字体")]
+    FontLoad,
+
+    #[error("设置系统事件钩子失败: {0}")]
+    EventHook(String),
+}
+
+impl InitError {
+    /// 是否值得稍后重试（如开机时资源管理器尚未创建任务栏）
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, InitError::TaskbarNotFound(_))
+    }
+}
+
+/// 初始化结果类型
+pub type InitResult<T> = std::result::Result<T, InitError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_missing_taskbar_is_retryable() {
+        assert!(InitError::TaskbarNotFound("找不到任务栏窗口".to_string()).is_retryable());
+        assert!(!InitError::WindowCreation("os error".to_string()).is_retryable());
+        assert!(!InitError::FontLoad.is_retryable());
+        assert_eq!(InitError::EventHook("x".to_string()).to_string(), "设置系统事件钩子失败: x");
+    }
+}
@@ -1,3 +1,5 @@
 mod renderer;
+mod style;
 
 pub use renderer::*;
+pub use style::*;
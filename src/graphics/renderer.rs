@@ -1,10 +1,150 @@
 use crate::*;
 use crate::font::FontManager;
+use crate::graphics::RenderStyle;
+use tracing::warn;
+
+/// 窗口的左右边缘
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EdgeSide {
+    Left,
+    #[default]
+    Right,
+}
+
+/// 第二行（如下一句歌词）相对当前歌词的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SecondaryPlacement {
+    /// 当前歌词下方（窗口分为上下两个区域）
+    #[default]
+    Below,
+    /// 当前歌词右侧的同一行（两句放不下时只显示当前歌词）
+    Beside,
+}
+
+/// 叠加在歌词之上的附加内容
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameDecorations<'a> {
+    /// 显示在窗口边缘的小号文字（如播放时间）
+    pub edge_label: Option<(&'a str, EdgeSide)>,
+    /// 播放进度（0.0 ~ 1.0），为 None 时不绘制进度条
+    pub progress: Option<f32>,
+    /// 变暗显示的第二行（如下一句歌词预览）
+    pub secondary_line: Option<&'a str>,
+    /// 第二行的位置
+    pub secondary_placement: SecondaryPlacement,
+    /// 当前歌词已唱部分占文本宽度的比例（0.0 ~ 1.0），为 None 时不高亮
+    pub highlight: Option<f32>,
+}
+
+/// 一帧的绘制内容：当前歌词及其字号、样式、边距、滚动状态和附加内容
+#[derive(Debug, Clone, Copy)]
+pub struct FrameParams<'a> {
+    /// 当前歌词
+    pub text: &'a str,
+    pub font_size: f32,
+    pub style: &'a RenderStyle,
+    pub margin: u32,
+    /// 当前歌词的滚动偏移（像素）
+    pub scroll_offset: f32,
+    /// 为 true 时即使文本未超出宽度也应用滚动偏移（跑马灯模式）
+    pub force_scroll: bool,
+    pub decorations: FrameDecorations<'a>,
+}
+
+/// 第二行占窗口高度的比例
+const SECONDARY_ROW_RATIO: f32 = 0.4;
+/// 第二行文字的不透明度倍数
+const SECONDARY_LINE_OPACITY: f32 = 0.55;
+/// 并排显示时第二行的字号（相对当前歌词）
+const BESIDE_FONT_RATIO: f32 = 0.8;
+/// 并排显示时两句之间的间距（相对当前歌词的字号）
+const BESIDE_GAP_RATIO: f32 = 1.0;
+
+/// 并排显示两句歌词时的布局
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BesideLayout {
+    primary_x: i32,
+    primary_width: f32,
+    secondary_x: i32,
+}
+
+impl BesideLayout {
+    /// 把两句连同间距作为一个整体在窗口中居中，超出可用宽度（窗口宽度减去两侧边距）时返回 None
+    fn fit(primary_width: f32, secondary_width: f32, gap: f32, window_width: u32, margin: u32) -> Option<Self> {
+        let total_width = primary_width + gap + secondary_width;
+        let available_width = window_width as f32 - margin as f32 * 2.0;
+        if secondary_width <= 0.0 || total_width > available_width {
+            return None;
+        }
+
+        let primary_x = ((window_width as f32 - total_width) / 2.0) as i32;
+        Some(Self {
+            primary_x,
+            primary_width,
+            secondary_x: primary_x + (primary_width + gap) as i32,
+        })
+    }
+}
+
+/// 获取缓冲区失败时两次警告之间的最短间隔
+const BUFFER_WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 限制警告频率：间隔内的重复失败只计数，下次警告时一并报告
+#[derive(Debug, Default)]
+struct WarningThrottle {
+    last_warned_at: Option<Instant>,
+    suppressed: u32,
+}
+
+impl WarningThrottle {
+    /// 记录一次失败，需要输出警告时返回期间被抑制的次数
+    fn record(&mut self, now: Instant) -> Option<u32> {
+        if self.last_warned_at.is_some_and(|at| now.duration_since(at) < BUFFER_WARNING_INTERVAL) {
+            self.suppressed += 1;
+            return None;
+        }
+        self.last_warned_at = Some(now);
+        Some(std::mem::take(&mut self.suppressed))
+    }
+}
+
+/// 文字绘制参数：文字/背景颜色（预乘 alpha）及按 gamma 校正的覆盖率查找表
+struct GlyphPaint {
+    color: u32,
+    background: u32,
+    coverage: [u8; 256],
+    /// 已唱部分的文字颜色
+    highlight_color: u32,
+    /// 该横坐标左侧的像素使用高亮颜色（None 表示不高亮）
+    highlight_until_x: Option<i32>,
+}
+
+/// 一段文本的绘制方式：颜色和 gamma 覆盖率表，以及已唱部分的比例（0.0 ~ 1.0，为 None 时不高亮）
+#[derive(Clone, Copy)]
+struct TextPaint<'a> {
+    glyph: &'a GlyphPaint,
+    highlight: Option<f32>,
+}
+
+/// 文本绘制区域：尺寸、边距和滚动状态
+#[derive(Debug, Clone, Copy)]
+struct TextArea {
+    width: u32,
+    height: u32,
+    margin: u32,
+    scroll_offset: f32,
+    /// 为 true 时即使文本未超出宽度也应用滚动偏移（跑马灯模式）
+    force_scroll: bool,
+}
 
 /// 图形渲染器，负责处理所有的绘制操作
 pub struct Renderer {
     surface: Option<Surface<Rc<Window>, Rc<Window>>>,
     context: Option<Context<Rc<Window>>>,
+    /// 覆盖率查找表缓存（按 `text_gamma` 的位模式区分）
+    coverage_cache: Option<(u32, [u8; 256])>,
+    /// 获取缓冲区失败的警告频率限制
+    buffer_warning: WarningThrottle,
 }
 
 impl Renderer {
@@ -12,16 +152,55 @@ impl Renderer {
         Self {
             surface: None,
             context: None,
+            coverage_cache: None,
+            buffer_warning: WarningThrottle::default(),
+        }
+    }
+
+    /// 渲染表面是否已创建
+    pub fn is_initialized(&self) -> bool {
+        self.surface.is_some()
+    }
+
+    /// 清空绘制缓存（字体重新加载、显示器或缩放比例变化后调用，可重复调用）
+    pub fn clear_caches(&mut self) {
+        self.coverage_cache = None;
+    }
+
+    /// 释放渲染表面和上下文（可重复调用）
+    pub fn release(&mut self) {
+        self.clear_caches();
+        // 表面依赖上下文，先释放表面
+        self.surface = None;
+        self.context = None;
+    }
+
+    /// 重新创建渲染表面和上下文（如显示器变化后表面失效）
+    pub fn reinitialize(&mut self, window: &Rc<Window>) -> InitResult<()> {
+        self.release();
+        self.initialize(window)
+    }
+
+    /// 获取按样式 gamma 计算的覆盖率查找表（使用缓存）
+    fn coverage_table(&mut self, style: &RenderStyle) -> [u8; 256] {
+        let key = style.text_gamma.to_bits();
+        match self.coverage_cache {
+            Some((cached_key, table)) if cached_key == key => table,
+            _ => {
+                let table = style.coverage_table();
+                self.coverage_cache = Some((key, table));
+                table
+            }
         }
     }
 
     /// 初始化渲染器
-    pub fn initialize(&mut self, window: &Rc<Window>) -> std::result::Result<(), String> {
+    pub fn initialize(&mut self, window: &Rc<Window>) -> InitResult<()> {
         let context = Context::new(window.clone())
-            .map_err(|e| format!("创建渲染上下文失败: {}", e))?;
+            .map_err(|e| InitError::RendererInit(format!("创建渲染上下文失败: {}", e)))?;
             
         let surface = Surface::new(&context, window.clone())
-            .map_err(|e| format!("创建渲染表面失败: {}", e))?;
+            .map_err(|e| InitError::RendererInit(format!("创建渲染表面失败: {}", e)))?;
 
         self.context = Some(context);
         self.surface = Some(surface);
@@ -30,46 +209,139 @@ impl Renderer {
     }
 
     /// 绘制一帧内容
+    ///
+    /// 缓冲区像素为预乘 alpha 的 0xAARRGGBB（与 `RenderStyle` 的颜色格式一致），
+    /// 背景默认清为全透明的 0x00000000，文字按覆盖率与其下方的像素混合
     pub fn draw_frame(
         &mut self,
-        text: &str,
         font_manager: &FontManager,
-        font_size: f32,
-        color: u32,
         window_width: u32,
         window_height: u32,
-        margin: u32,
-        scroll_offset: f32,
+        frame: &FrameParams,
     ) -> std::result::Result<(), String> {
+        let &FrameParams { text, font_size, style, margin, scroll_offset, force_scroll, ref decorations } = frame;
+        // 先应用整体不透明度（如暂停时变暗）
+        let secondary_color = style.dimmed(SECONDARY_LINE_OPACITY).resolved().text_color;
+        let style = &style.resolved();
+        let paint = GlyphPaint {
+            color: style.text_color,
+            background: style.background_color,
+            coverage: self.coverage_table(style),
+            highlight_color: style.highlight_color,
+            highlight_until_x: None,
+        };
+
         let surface = self.surface.as_mut()
             .ok_or("渲染表面未初始化")?;
 
-        let width = NonZeroU32::new(window_width).unwrap();
-        let height = NonZeroU32::new(window_height).unwrap();
+        // 最小化等情况下窗口尺寸为 0，跳过这一帧
+        let (Some(width), Some(height)) = (NonZeroU32::new(window_width), NonZeroU32::new(window_height)) else {
+            return Ok(());
+        };
         
         // 调整缓冲区大小
         surface.resize(width, height)
             .map_err(|e| format!("调整缓冲区失败: {}", e))?;
 
-        // 获取缓冲区
-        let mut buffer = surface.buffer_mut()
-            .map_err(|e| format!("获取缓冲区失败: {}", e))?;
-
-        // 清空背景为透明
-        buffer.fill(0x00000000);
-
-        // 绘制文本
-        Self::draw_text_impl(
-            &mut buffer,
-            text,
-            font_manager,
-            font_size,
-            color,
-            window_width,
-            window_height,
-            margin,
-            scroll_offset,
-        );
+        // 获取缓冲区（窗口尺寸频繁变化时可能暂时失败，跳过这一帧等待下次重绘）
+        let mut buffer = match surface.buffer_mut() {
+            Ok(buffer) => buffer,
+            Err(e) => {
+                if let Some(suppressed) = self.buffer_warning.record(Instant::now()) {
+                    warn!("获取缓冲区失败，跳过本帧: {} (上次警告后又失败 {} 次)", e, suppressed);
+                }
+                return Ok(());
+            }
+        };
+
+        // 使用背景色清空（默认透明）
+        buffer.fill(style.background_color);
+
+        // 并排显示时两句的横坐标（跑马灯或宽度放不下时为 None，只显示当前歌词）
+        let beside = match (decorations.secondary_line, decorations.secondary_placement, font_manager.get_font()) {
+            (Some(secondary_line), SecondaryPlacement::Beside, Some(font)) if !force_scroll => {
+                Self::beside_layout(text, secondary_line, font, font_size, window_width, margin)
+                    .map(|layout| (secondary_line, font, layout))
+            }
+            _ => None,
+        };
+
+        // 绘制文本（第二行在下方时各占上下两个区域，按行切分缓冲区分别居中绘制）
+        match decorations.secondary_line {
+            Some(secondary_line) if decorations.secondary_placement == SecondaryPlacement::Below => {
+                let secondary_height = (window_height as f32 * SECONDARY_ROW_RATIO) as u32;
+                let primary_height = window_height - secondary_height;
+                let (primary_rows, secondary_rows) = buffer.split_at_mut((primary_height * window_width) as usize);
+
+                Self::draw_text_impl(
+                    primary_rows,
+                    text,
+                    font_manager,
+                    font_size,
+                    &TextPaint { glyph: &paint, highlight: decorations.highlight },
+                    TextArea {
+                        width: window_width,
+                        height: primary_height,
+                        margin: style.secondary_margin_for(primary_height),
+                        scroll_offset,
+                        force_scroll,
+                    },
+                );
+                Self::draw_text_impl(
+                    secondary_rows,
+                    secondary_line,
+                    font_manager,
+                    style.secondary_font_size_for(font_size),
+                    &TextPaint { glyph: &GlyphPaint { color: secondary_color, ..paint }, highlight: None },
+                    TextArea { width: window_width, height: secondary_height, margin: 0, scroll_offset: 0.0, force_scroll: false },
+                );
+            }
+            _ => match beside {
+                Some((secondary_line, font, layout)) => {
+                    let primary_paint = GlyphPaint {
+                        highlight_until_x: decorations.highlight
+                            .map(|fraction| layout.primary_x + (fraction.clamp(0.0, 1.0) * layout.primary_width) as i32),
+                        ..paint
+                    };
+                    Self::draw_line_at(&mut buffer, text, font, font_size, &primary_paint, layout.primary_x, (window_width, window_height));
+                    Self::draw_line_at(
+                        &mut buffer,
+                        secondary_line,
+                        font,
+                        font_size * BESIDE_FONT_RATIO,
+                        &GlyphPaint { color: secondary_color, ..paint },
+                        layout.secondary_x,
+                        (window_width, window_height),
+                    );
+                }
+                None => Self::draw_text_impl(
+                    &mut buffer,
+                    text,
+                    font_manager,
+                    font_size,
+                    &TextPaint { glyph: &paint, highlight: decorations.highlight },
+                    TextArea { width: window_width, height: window_height, margin, scroll_offset, force_scroll },
+                ),
+            },
+        }
+
+        // 绘制边缘标签（覆盖在歌词之上）
+        if let (Some(edge_label), Some(font)) = (decorations.edge_label, font_manager.get_font()) {
+            Self::draw_edge_label(
+                &mut buffer,
+                edge_label,
+                font,
+                font_size * 0.75,
+                &paint,
+                window_width,
+                window_height,
+            );
+        }
+
+        // 绘制底部进度条
+        if let Some(progress) = decorations.progress {
+            Self::draw_progress_bar(&mut buffer, progress, style.progress_color, window_width, window_height);
+        }
 
         // 呈现缓冲区
         buffer.present().map_err(|e| format!("呈现缓冲区失败: {}", e))?;
@@ -78,31 +350,19 @@ impl Renderer {
     }
 
 
-    /// 绘制文本
+    /// 绘制文本（像素字体不支持高亮）
     fn draw_text_impl(
         buffer: &mut [u32],
         text: &str,
         font_manager: &FontManager,
         font_size: f32,
-        color: u32,
-        window_width: u32,
-        window_height: u32,
-        margin: u32,
-        scroll_offset: f32,
+        paint: &TextPaint,
+        area: TextArea,
     ) {
+        let TextArea { width: window_width, height: window_height, margin, scroll_offset, force_scroll } = area;
         if let Some(font) = font_manager.get_font() {
             // 使用真实字体渲染
-            Self::draw_text_with_font(
-                buffer,
-                text,
-                font,
-                font_size,
-                color,
-                window_width,
-                window_height,
-                margin,
-                scroll_offset,
-            );
+            Self::draw_text_with_font(buffer, text, font, font_size, paint, area);
         } else {
             // 使用像素字体备选方案
             let char_height = font_size as u32;
@@ -120,30 +380,30 @@ impl Renderer {
                 text,
                 margin,
                 text_y,
-                color,
+                paint.glyph.color,
                 window_width,
                 window_height,
                 char_width,
                 char_height,
                 scroll_offset,
+                force_scroll,
             );
         }
     }
 
-    /// 使用真实字体渲染文本（使用 Layout API）
+    /// 使用真实字体渲染文本（使用 Layout API），已唱比例以内的部分使用高亮颜色
     fn draw_text_with_font(
         buffer: &mut [u32],
         text: &str,
         font: &Font,
         font_size: f32,
-        color: u32,
-        window_width: u32,
-        window_height: u32,
-        margin: u32,
-        scroll_offset: f32,
+        paint: &TextPaint,
+        area: TextArea,
     ) {
         use crate::font::layout_text;
         
+        let TextArea { width: window_width, height: window_height, margin, scroll_offset, force_scroll } = area;
+        
         let (glyphs, text_width, text_height) = layout_text(font, text, font_size);
         
         if glyphs.is_empty() {
@@ -152,7 +412,7 @@ impl Renderer {
         
         // 计算文本的整体位置
         let available_width = window_width as f32 - (margin as f32 * 2.0);
-        let text_x = if text_width <= available_width {
+        let text_x = if text_width <= available_width && !force_scroll {
             // 文本小于窗口宽度，居中显示
             ((window_width as f32 - text_width) / 2.0) as i32
         } else {
@@ -168,6 +428,12 @@ impl Renderer {
             margin as f32
         };
         
+        // 已唱部分的右边界（随滚动一起移动）
+        let paint = &GlyphPaint {
+            highlight_until_x: paint.highlight.map(|fraction| text_x + (fraction.clamp(0.0, 1.0) * text_width) as i32),
+            ..*paint.glyph
+        };
+        
         // 渲染每个字符（只渲染在窗口内的字符）
         for glyph in glyphs {
             let char_x = text_x + glyph.x as i32;
@@ -183,7 +449,7 @@ impl Renderer {
                     &metrics,
                     char_x,
                     char_y,
-                    color,
+                    paint,
                     window_width,
                     window_height,
                 );
@@ -191,6 +457,123 @@ impl Renderer {
         }
     }
 
+    /// 计算当前歌词与右侧下一句并排居中时各自的位置，两句加间距超出可用宽度时返回 None
+    fn beside_layout(
+        text: &str,
+        secondary_line: &str,
+        font: &Font,
+        font_size: f32,
+        window_width: u32,
+        margin: u32,
+    ) -> Option<BesideLayout> {
+        use crate::font::get_layout_text_width;
+
+        let primary_width = get_layout_text_width(font, text, font_size);
+        let secondary_width = get_layout_text_width(font, secondary_line, font_size * BESIDE_FONT_RATIO);
+        BesideLayout::fit(primary_width, secondary_width, font_size * BESIDE_GAP_RATIO, window_width, margin)
+    }
+
+    /// 从横坐标 `x` 开始绘制一行文本（垂直居中，不滚动）
+    fn draw_line_at(
+        buffer: &mut [u32],
+        text: &str,
+        font: &Font,
+        font_size: f32,
+        paint: &GlyphPaint,
+        x: i32,
+        (window_width, window_height): (u32, u32),
+    ) {
+        use crate::font::layout_text;
+
+        let (glyphs, _, text_height) = layout_text(font, text, font_size);
+        let text_y = ((window_height as f32 - text_height) / 2.0).max(0.0) as i32;
+        for glyph in glyphs {
+            let (metrics, bitmap) = font.rasterize(glyph.parent, glyph.key.px);
+            Self::draw_character_bitmap(
+                buffer,
+                &bitmap,
+                &metrics,
+                x + glyph.x as i32,
+                text_y + glyph.y as i32,
+                paint,
+                window_width,
+                window_height,
+            );
+        }
+    }
+
+    /// 在窗口底部绘制进度条（高度 2px）
+    fn draw_progress_bar(
+        buffer: &mut [u32],
+        progress: f32,
+        color: u32,
+        window_width: u32,
+        window_height: u32,
+    ) {
+        const BAR_HEIGHT: u32 = 2;
+        
+        let filled_width = (window_width as f32 * progress.clamp(0.0, 1.0)) as usize;
+        let first_row = window_height.saturating_sub(BAR_HEIGHT);
+        
+        for row in buffer.chunks_mut(window_width as usize)
+            .take(window_height as usize)
+            .skip(first_row as usize)
+        {
+            for pixel in row.iter_mut().take(filled_width) {
+                *pixel = color;
+            }
+        }
+    }
+
+    /// 在窗口边缘绘制小号标签，并用背景色清空标签区域避免与歌词重叠
+    fn draw_edge_label(
+        buffer: &mut [u32],
+        (label, side): (&str, EdgeSide),
+        font: &Font,
+        font_size: f32,
+        paint: &GlyphPaint,
+        window_width: u32,
+        window_height: u32,
+    ) {
+        use crate::font::layout_text;
+        
+        let (glyphs, label_width, label_height) = layout_text(font, label, font_size);
+        if glyphs.is_empty() {
+            return;
+        }
+        
+        let padding = (font_size / 2.0) as i32;
+        let label_width = label_width.ceil() as i32;
+        let label_x = match side {
+            EdgeSide::Left => padding,
+            EdgeSide::Right => window_width as i32 - padding - label_width,
+        };
+        let label_y = ((window_height as f32 - label_height) / 2.0).max(0.0) as i32;
+        
+        // 清空标签区域（左右各留出 padding）
+        let clear_start = (label_x - padding).max(0) as u32;
+        let clear_end = ((label_x + label_width + padding).max(0) as u32).min(window_width);
+        for row in buffer.chunks_mut(window_width as usize).take(window_height as usize) {
+            for pixel in &mut row[clear_start as usize..clear_end as usize] {
+                *pixel = paint.background;
+            }
+        }
+        
+        for glyph in glyphs {
+            let (metrics, bitmap) = font.rasterize(glyph.parent, glyph.key.px);
+            Self::draw_character_bitmap(
+                buffer,
+                &bitmap,
+                &metrics,
+                label_x + glyph.x as i32,
+                label_y + glyph.y as i32,
+                paint,
+                window_width,
+                window_height,
+            );
+        }
+    }
+
     /// 绘制字符位图
     fn draw_character_bitmap(
         buffer: &mut [u32],
@@ -198,7 +581,7 @@ impl Renderer {
         metrics: &fontdue::Metrics,
         char_x: i32,
         char_y: i32,
-        color: u32,
+        paint: &GlyphPaint,
         window_width: u32,
         window_height: u32,
     ) {
@@ -211,11 +594,15 @@ impl Renderer {
                    pixel_y >= 0 && pixel_y < window_height as i32 {
                     let bitmap_index = y * metrics.width + x;
                     if bitmap_index < bitmap.len() {
-                        let alpha = bitmap[bitmap_index];
+                        let alpha = paint.coverage[bitmap[bitmap_index] as usize];
                         if alpha > 0 {
                             let buffer_index = (pixel_y as u32 * window_width + pixel_x as u32) as usize;
+                            let color = match paint.highlight_until_x {
+                                Some(until_x) if pixel_x < until_x => paint.highlight_color,
+                                _ => paint.color,
+                            };
                             if buffer_index < buffer.len() {
-                                buffer[buffer_index] = color;
+                                buffer[buffer_index] = Self::blend_pixel(buffer[buffer_index], color, alpha);
                             }
                         }
                     }
@@ -224,6 +611,21 @@ impl Renderer {
         }
     }
 
+    /// 按覆盖率把颜色叠加到像素上（均为预乘 alpha，逐通道线性插值）
+    fn blend_pixel(dst: u32, src: u32, alpha: u8) -> u32 {
+        if alpha == u8::MAX {
+            return src;
+        }
+
+        let alpha = alpha as u32;
+        [24, 16, 8, 0].iter().fold(0, |result, shift| {
+            let src_channel = (src >> shift) & 0xFF;
+            let dst_channel = (dst >> shift) & 0xFF;
+            let channel = (src_channel * alpha + dst_channel * (255 - alpha) + 127) / 255;
+            result | (channel << shift)
+        })
+    }
+
     /// 使用像素字体绘制文本（备选方案）
     fn draw_pixel_text(
         buffer: &mut [u32],
@@ -236,12 +638,13 @@ impl Renderer {
         char_width: u32,
         char_height: u32,
         scroll_offset: f32,
+        force_scroll: bool,
     ) {
         let chars = text.chars().collect::<Vec<_>>();
         let total_text_width = chars.len() as f32 * char_width as f32;
         let available_width = window_width as f32 - (x as f32 * 2.0);
         
-        let start_x = if total_text_width <= available_width {
+        let start_x = if total_text_width <= available_width && !force_scroll {
             // 文本小于窗口宽度，居中显示
             ((window_width as f32 - total_text_width) / 2.0) as u32
         } else {
@@ -345,3 +748,81 @@ impl Default for Renderer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clear_caches_and_release_are_idempotent() {
+        let mut renderer = Renderer::new();
+        let style = RenderStyle::DARK_TASKBAR;
+        assert_eq!(renderer.coverage_table(&style), style.coverage_table());
+        assert!(renderer.coverage_cache.is_some());
+
+        // gamma 变化时重新计算
+        let linear = style.with_text_gamma(1.0);
+        assert_eq!(renderer.coverage_table(&linear), linear.coverage_table());
+
+        renderer.clear_caches();
+        renderer.clear_caches();
+        assert!(renderer.coverage_cache.is_none());
+
+        renderer.release();
+        renderer.release();
+        assert!(!renderer.is_initialized());
+    }
+
+    #[test]
+    fn test_beside_layout_needs_room_for_both_lines() {
+        let layout = BesideLayout::fit(100.0, 60.0, 20.0, 300, 10).unwrap();
+        assert_eq!(layout.primary_x, 60);
+        assert_eq!(layout.secondary_x, 180);
+
+        // 两句加间距超出可用宽度时不并排
+        assert_eq!(BesideLayout::fit(200.0, 80.0, 20.0, 300, 10), None);
+        // 没有可显示的下一句
+        assert_eq!(BesideLayout::fit(100.0, 0.0, 20.0, 300, 10), None);
+    }
+
+    #[test]
+    fn test_glyph_edges_are_blended() {
+        // 一个 3x1 的字形：透明、半覆盖、全覆盖
+        let bitmap = [0u8, 128, 255];
+        let metrics = fontdue::Metrics {
+            xmin: 0,
+            ymin: 0,
+            width: 3,
+            height: 1,
+            advance_width: 3.0,
+            advance_height: 0.0,
+            bounds: fontdue::OutlineBounds { xmin: 0.0, ymin: 0.0, width: 3.0, height: 1.0 },
+        };
+        let style = RenderStyle::LIGHT_TASKBAR.with_text_gamma(1.0);
+        let paint = GlyphPaint {
+            color: style.text_color,
+            background: style.background_color,
+            coverage: style.coverage_table(),
+            highlight_color: style.highlight_color,
+            highlight_until_x: None,
+        };
+
+        let mut buffer = [0x00000000u32; 3];
+        Renderer::draw_character_bitmap(&mut buffer, &bitmap, &metrics, 0, 0, &paint, 3, 1);
+
+        let alphas: Vec<u32> = buffer.iter().map(|pixel| pixel >> 24).collect();
+        assert_eq!(alphas, vec![0x00, 0x80, 0xFF]);
+        // 透明背景保持不变
+        assert_eq!(buffer[0], 0x00000000);
+    }
+
+    #[test]
+    fn test_buffer_warning_is_throttled() {
+        let mut throttle = WarningThrottle::default();
+        let now = Instant::now();
+        assert_eq!(throttle.record(now), Some(0));
+        assert_eq!(throttle.record(now + Duration::from_secs(1)), None);
+        assert_eq!(throttle.record(now + Duration::from_secs(2)), None);
+        assert_eq!(throttle.record(now + BUFFER_WARNING_INTERVAL), Some(2));
+    }
+}
@@ -0,0 +1,288 @@
+/// 渲染样式（颜色均为 0xAARRGGBB 格式）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderStyle {
+    /// 文字颜色
+    pub text_color: u32,
+    /// 背景颜色（默认全透明）
+    pub background_color: u32,
+    /// 进度条颜色
+    pub progress_color: u32,
+    /// 逐字歌词中已唱部分的文字颜色
+    pub highlight_color: u32,
+    /// 整体不透明度（0.0 ~ 1.0），绘制时作用于所有颜色
+    pub opacity: f32,
+    /// 文字抗锯齿的 gamma（1.0 为线性混合，越大笔画越粗）
+    pub text_gamma: f32,
+    /// 字号占窗口高度的比例
+    pub font_size_ratio: f32,
+    /// 文字左右边距占窗口高度的比例
+    pub margin_ratio: f32,
+    /// 第二行显示在下方时的字号（相对当前歌词的字号）
+    pub secondary_font_ratio: f32,
+    /// 第二行显示在下方时当前歌词的左右边距占其所在区域高度的比例
+    pub secondary_margin_ratio: f32,
+    /// 按逐字时间轴高亮已唱部分（每唱到一个字都要重绘）
+    pub word_highlight: bool,
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        Self::LIGHT_TASKBAR
+    }
+}
+
+impl RenderStyle {
+    /// 默认的文字抗锯齿 gamma
+    pub const DEFAULT_TEXT_GAMMA: f32 = 2.2;
+    /// 默认的字号比例（比窗口高度稍小一些，适应歌词）
+    pub const DEFAULT_FONT_SIZE_RATIO: f32 = 0.4;
+    /// 默认的边距比例
+    pub const DEFAULT_MARGIN_RATIO: f32 = 0.25;
+    /// 默认的第二行字号比例
+    pub const DEFAULT_SECONDARY_FONT_RATIO: f32 = 0.7;
+    /// 默认的两行显示时的边距比例
+    pub const DEFAULT_SECONDARY_MARGIN_RATIO: f32 = 0.05;
+
+    /// 浅色任务栏：黑色文字，透明背景
+    pub const LIGHT_TASKBAR: Self = Self {
+        text_color: 0xFF000000,
+        background_color: 0x00000000,
+        progress_color: 0xFF0078D4,
+        highlight_color: 0xFF0078D4,
+        opacity: 1.0,
+        text_gamma: Self::DEFAULT_TEXT_GAMMA,
+        font_size_ratio: Self::DEFAULT_FONT_SIZE_RATIO,
+        margin_ratio: Self::DEFAULT_MARGIN_RATIO,
+        secondary_font_ratio: Self::DEFAULT_SECONDARY_FONT_RATIO,
+        secondary_margin_ratio: Self::DEFAULT_SECONDARY_MARGIN_RATIO,
+        word_highlight: true,
+    };
+
+    /// 深色任务栏：白色文字，透明背景
+    pub const DARK_TASKBAR: Self = Self {
+        text_color: 0xFFFFFFFF,
+        background_color: 0x00000000,
+        progress_color: 0xFF60CDFF,
+        highlight_color: 0xFF60CDFF,
+        opacity: 1.0,
+        text_gamma: Self::DEFAULT_TEXT_GAMMA,
+        font_size_ratio: Self::DEFAULT_FONT_SIZE_RATIO,
+        margin_ratio: Self::DEFAULT_MARGIN_RATIO,
+        secondary_font_ratio: Self::DEFAULT_SECONDARY_FONT_RATIO,
+        secondary_margin_ratio: Self::DEFAULT_SECONDARY_MARGIN_RATIO,
+        word_highlight: true,
+    };
+
+    /// 高对比度：白色文字，不透明黑色底板
+    pub const HIGH_CONTRAST: Self = Self {
+        text_color: 0xFFFFFFFF,
+        background_color: 0xFF000000,
+        progress_color: 0xFFFFFF00,
+        highlight_color: 0xFFFFFF00,
+        opacity: 1.0,
+        text_gamma: Self::DEFAULT_TEXT_GAMMA,
+        font_size_ratio: Self::DEFAULT_FONT_SIZE_RATIO,
+        margin_ratio: Self::DEFAULT_MARGIN_RATIO,
+        secondary_font_ratio: Self::DEFAULT_SECONDARY_FONT_RATIO,
+        secondary_margin_ratio: Self::DEFAULT_SECONDARY_MARGIN_RATIO,
+        word_highlight: true,
+    };
+
+    /// 按名称获取预设样式（不区分大小写，支持 "light" / "dark" / "high-contrast" 等写法）
+    pub fn preset(name: &str) -> Option<Self> {
+        let normalized = name.trim().to_lowercase().replace(['_', ' '], "-");
+        let normalized = normalized.strip_suffix("-taskbar").unwrap_or(&normalized);
+
+        match normalized {
+            "light" => Some(Self::LIGHT_TASKBAR),
+            "dark" => Some(Self::DARK_TASKBAR),
+            "high-contrast" | "contrast" => Some(Self::HIGH_CONTRAST),
+            _ => None,
+        }
+    }
+
+    /// 省电样式：关闭逐字高亮，按逐行而不是逐字重绘
+    pub fn power_saving(self) -> Self {
+        Self {
+            word_highlight: false,
+            ..self
+        }
+    }
+
+    /// 以指定倍数降低整体不透明度
+    pub fn dimmed(self, factor: f32) -> Self {
+        Self {
+            opacity: self.opacity * factor.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// 把整体不透明度应用到各颜色（预乘 alpha），返回不透明度为 1.0 的等效样式
+    pub fn resolved(&self) -> Self {
+        let opacity = self.opacity.clamp(0.0, 1.0);
+        if opacity >= 1.0 {
+            return *self;
+        }
+
+        let scale = |color: u32| -> u32 {
+            [24, 16, 8, 0].iter().fold(0, |result, shift| {
+                let channel = ((color >> shift) & 0xFF) as f32 * opacity;
+                result | ((channel.round() as u32) << shift)
+            })
+        };
+
+        Self {
+            text_color: scale(self.text_color),
+            background_color: scale(self.background_color),
+            progress_color: scale(self.progress_color),
+            highlight_color: scale(self.highlight_color),
+            opacity: 1.0,
+            ..*self
+        }
+    }
+
+    /// 设置字号和边距占窗口高度的比例
+    pub fn with_ratios(self, font_size_ratio: f32, margin_ratio: f32) -> Self {
+        Self {
+            font_size_ratio,
+            margin_ratio,
+            ..self
+        }
+    }
+
+    /// 设置第二行显示在下方时的字号比例和当前歌词的边距比例
+    pub fn with_secondary_ratios(self, secondary_font_ratio: f32, secondary_margin_ratio: f32) -> Self {
+        Self {
+            secondary_font_ratio,
+            secondary_margin_ratio,
+            ..self
+        }
+    }
+
+    /// 按窗口高度换算字号（比例为负数或 NaN 时视为 0）
+    pub fn font_size_for(&self, window_height: u32) -> f32 {
+        window_height as f32 * self.font_size_ratio.max(0.0)
+    }
+
+    /// 按窗口高度换算文字左右边距
+    pub fn margin_for(&self, window_height: u32) -> u32 {
+        (window_height as f32 * self.margin_ratio.max(0.0)) as u32
+    }
+
+    /// 第二行显示在下方时的字号
+    pub fn secondary_font_size_for(&self, font_size: f32) -> f32 {
+        font_size * self.secondary_font_ratio.max(0.0)
+    }
+
+    /// 第二行显示在下方时，按当前歌词区域的高度换算其左右边距
+    pub fn secondary_margin_for(&self, row_height: u32) -> u32 {
+        (row_height as f32 * self.secondary_margin_ratio.max(0.0)) as u32
+    }
+
+    /// 设置文字抗锯齿 gamma
+    pub fn with_text_gamma(self, gamma: f32) -> Self {
+        Self {
+            text_gamma: gamma,
+            ..self
+        }
+    }
+
+    /// 字形覆盖率到混合比例的查找表（按 `text_gamma` 校正）
+    pub fn coverage_table(&self) -> [u8; 256] {
+        // 非正数或 NaN 视为线性
+        let gamma = if self.text_gamma > 0.0 { self.text_gamma } else { 1.0 };
+        let mut table = [0u8; 256];
+        for (coverage, entry) in table.iter_mut().enumerate() {
+            let corrected = (coverage as f32 / 255.0).powf(1.0 / gamma);
+            *entry = (corrected * 255.0).round() as u8;
+        }
+        table
+    }
+
+    /// 所有可用的预设名称
+    pub fn preset_names() -> &'static [&'static str] {
+        &["light", "dark", "high-contrast"]
+    }
+}
+
+/// 按位哈希浮点字段，用于判断渲染结果是否可能变化
+impl std::hash::Hash for RenderStyle {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.text_color.hash(state);
+        self.background_color.hash(state);
+        self.progress_color.hash(state);
+        self.highlight_color.hash(state);
+        self.opacity.to_bits().hash(state);
+        self.text_gamma.to_bits().hash(state);
+        self.font_size_ratio.to_bits().hash(state);
+        self.margin_ratio.to_bits().hash(state);
+        self.secondary_font_ratio.to_bits().hash(state);
+        self.secondary_margin_ratio.to_bits().hash(state);
+        self.word_highlight.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_lookup() {
+        assert_eq!(RenderStyle::preset("Light taskbar"), Some(RenderStyle::LIGHT_TASKBAR));
+        assert_eq!(RenderStyle::preset("dark_taskbar"), Some(RenderStyle::DARK_TASKBAR));
+        assert_eq!(RenderStyle::preset("High Contrast"), Some(RenderStyle::HIGH_CONTRAST));
+        assert_eq!(RenderStyle::preset("neon"), None);
+
+        for name in RenderStyle::preset_names() {
+            assert!(RenderStyle::preset(name).is_some());
+        }
+    }
+
+    #[test]
+    fn test_dimmed_style_scales_all_channels() {
+        assert_eq!(RenderStyle::DARK_TASKBAR.resolved(), RenderStyle::DARK_TASKBAR);
+
+        let dimmed = RenderStyle::DARK_TASKBAR.dimmed(0.5).resolved();
+        assert_eq!(dimmed.text_color, 0x80808080);
+        assert_eq!(dimmed.background_color, 0x00000000);
+        assert_eq!(dimmed.opacity, 1.0);
+        assert_eq!(dimmed.text_gamma, RenderStyle::DEFAULT_TEXT_GAMMA);
+    }
+
+    #[test]
+    fn test_ratios_scale_with_window_height() {
+        let style = RenderStyle::default();
+        assert_eq!(style.font_size_for(40), 16.0);
+        assert_eq!(style.margin_for(40), 10);
+
+        let style = style.with_ratios(0.5, -1.0);
+        assert_eq!(style.font_size_for(40), 20.0);
+        assert_eq!(style.margin_for(40), 0);
+    }
+
+    #[test]
+    fn test_secondary_ratios() {
+        // 默认值与原先两行布局的尺寸一致
+        let style = RenderStyle::default();
+        assert!((style.secondary_font_size_for(20.0) - 14.0).abs() < 1e-4);
+        assert_eq!(style.secondary_margin_for(40), 2);
+
+        let style = style.with_secondary_ratios(0.5, -1.0);
+        assert_eq!(style.secondary_font_size_for(20.0), 10.0);
+        assert_eq!(style.secondary_margin_for(40), 0);
+    }
+
+    #[test]
+    fn test_coverage_table_gamma() {
+        let linear = RenderStyle::LIGHT_TASKBAR.with_text_gamma(1.0).coverage_table();
+        assert_eq!(linear[128], 128);
+
+        let table = RenderStyle::LIGHT_TASKBAR.coverage_table();
+        assert_eq!((table[0], table[255]), (0, 255));
+        // gamma 大于 1 时半透明边缘更实
+        assert!(table[64] > 64);
+        assert!(table.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        assert_eq!(RenderStyle::LIGHT_TASKBAR.with_text_gamma(0.0).coverage_table(), linear);
+    }
+}
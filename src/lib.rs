@@ -4,11 +4,19 @@ pub mod graphics;
 pub mod font;
 pub mod system;
 pub mod app;
+pub mod engine;
 pub mod lyrics;
+pub mod error;
+pub mod chinese;
+pub mod settings;
 
 // 导出主要的公共类型
-pub use widget::TaskbarWidget;
-pub use app::App;
+pub use widget::{TaskbarWidget, IntroDisplay, ScrollMode, ScrollResume, TextTransform, TimeDisplay, UnsyncedDisplay, WidthMode, identity_transform};
+pub use app::{App, SessionSnapshot};
+pub use engine::{EngineCommand, EngineConfig, LyricsEngine};
+pub use error::{InitError, InitResult};
+pub use chinese::ChineseConversion;
+pub use settings::AppSettings;
 
 // 重新导出常用的 Windows API 类型
 pub use windows::{
@@ -21,7 +29,7 @@ pub use windows::{
 // 重新导出 winit 相关类型
 pub use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
+    event::{ElementState, MouseButton, WindowEvent},
     event_loop::EventLoop,
     window::{Window, WindowLevel},
     dpi::{PhysicalPosition, PhysicalSize},
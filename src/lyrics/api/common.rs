@@ -1,5 +1,12 @@
+use std::time::Duration;
 use url::form_urlencoded;
-use crate::lyrics::{LyricsResult, LyricsError};
+use crate::lyrics::{LyricsResult, LyricsError, SongInfo};
+
+/// 每次搜索获取的候选结果数量
+pub const DEFAULT_SEARCH_LIMIT: usize = 5;
+
+/// 时长相差超过该值时不再因时长加分
+const DURATION_TOLERANCE: Duration = Duration::from_secs(30);
 
 /// URL编码工具
 pub fn url_encode(input: &str) -> String {
@@ -101,6 +108,189 @@ pub fn build_query(title: &str, artist: &str) -> String {
     format!("{} {}", title.trim(), artist.trim())
 }
 
+/// 按顺序尝试的搜索关键词：先用清理后的歌名和艺术家，与原文不同时再用原文
+pub fn search_queries(title: &str, artist: &str) -> Vec<String> {
+    let cleaned = build_query(&clean_title(title), &clean_artist(artist));
+    let original = build_query(title, artist);
+    if cleaned == original {
+        vec![original]
+    } else {
+        vec![cleaned, original]
+    }
+}
+
+/// 清理媒体会话中的歌名：去掉括号内的版本说明（如 `(Live)`、`[Remastered 2011]`）、
+/// `feat.` / `ft.` 合作者和结尾的 `- Topic`，并把全角字符转为半角
+///
+/// 清理后为空时返回规范化后的原文
+pub fn clean_title(title: &str) -> String {
+    clean_field(title)
+}
+
+/// 清理艺术家名（规则同 `clean_title`，如 `周杰伦 - Topic` → `周杰伦`）
+pub fn clean_artist(artist: &str) -> String {
+    clean_field(artist)
+}
+
+fn clean_field(value: &str) -> String {
+    let normalized = to_half_width(value);
+
+    let mut cleaned = strip_brackets(&normalized);
+    if let Some(pos) = feat_position(&cleaned) {
+        cleaned.truncate(pos);
+    }
+    let cleaned = strip_topic_suffix(&cleaned);
+    let cleaned = collapse_whitespace(cleaned);
+
+    if cleaned.is_empty() {
+        collapse_whitespace(&normalized)
+    } else {
+        cleaned
+    }
+}
+
+/// 全角 ASCII 字符和全角空格转为半角
+fn to_half_width(value: &str) -> String {
+    value.chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// 括号内出现这些单词时视为版本说明（按整词匹配，不区分大小写）
+const QUALIFIER_WORDS: [&str; 32] = [
+    "live", "remix", "mix", "feat", "ft", "featuring", "remaster", "remastered", "version", "ver",
+    "edit", "mv", "pv", "instrumental", "inst", "acoustic", "cover", "demo", "explicit", "radio",
+    "mono", "stereo", "official", "lyric", "lyrics", "audio", "video", "karaoke", "bonus", "extended",
+    "unplugged", "from",
+];
+
+/// 括号内含有这些文字时视为版本说明
+const QUALIFIER_TEXTS: [&str; 10] = ["现场", "伴奏", "官方", "纯音乐", "翻唱", "混音", "版", "ライブ", "カバー", "リミックス"];
+
+/// 删除括号中的版本说明（如 `(Live)`、`(feat. B)`、`[Remastered 2011]`、`【官方MV】`，支持嵌套）
+///
+/// 其他括号内容原样保留：日文歌名常用「」『』书写标题本身，不做处理
+fn strip_brackets(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut group = String::new();
+    let mut depth = 0usize;
+    for c in value.chars() {
+        match c {
+            '(' | '[' | '{' | '【' => {
+                depth += 1;
+                group.push(c);
+            }
+            ')' | ']' | '}' | '】' if depth > 0 => {
+                depth -= 1;
+                group.push(c);
+                if depth == 0 {
+                    if !is_qualifier(&group) {
+                        result.push_str(&group);
+                    }
+                    group.clear();
+                }
+            }
+            _ if depth > 0 => group.push(c),
+            _ => result.push(c),
+        }
+    }
+    // 未闭合的括号原样保留
+    result.push_str(&group);
+    result
+}
+
+/// 括号内容是否为版本说明
+fn is_qualifier(group: &str) -> bool {
+    let lower = group.to_lowercase();
+    lower.split(|c: char| !c.is_ascii_alphanumeric()).any(|word| QUALIFIER_WORDS.contains(&word))
+        || QUALIFIER_TEXTS.iter().any(|text| lower.contains(text))
+}
+
+/// 合作者标记（`feat.`、`ft.`、`featuring`）开始的位置
+fn feat_position(value: &str) -> Option<usize> {
+    // 只转换 ASCII 大小写，字节位置与原文一致
+    let lower = format!(" {}", value.to_ascii_lowercase());
+    [" feat.", " feat ", " ft.", " ft ", " featuring "]
+        .iter()
+        .filter_map(|marker| lower.find(marker))
+        .min()
+}
+
+/// 删除结尾的 `- Topic`（视频网站自动生成的艺术家频道）
+fn strip_topic_suffix(value: &str) -> &str {
+    let trimmed = value.trim_end();
+    let lower = trimmed.to_ascii_lowercase();
+    match lower.strip_suffix("topic").map(str::trim_end) {
+        Some(rest) if rest.ends_with('-') => &trimmed[..rest.len() - 1],
+        _ => trimmed,
+    }
+}
+
+fn collapse_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 搜索结果与歌曲的匹配分数（0.0 ~ 1.0）：歌名相似度占 0.5，艺术家 0.2，时长接近程度 0.3
+///
+/// 任一方时长未知时时长一项按一半计分，不影响候选之间的排序
+pub fn score_candidate(song_info: &SongInfo, title: &str, artist: &str, duration: Option<Duration>) -> f64 {
+    let title_score = similarity(&clean_title(&song_info.title), &clean_title(title));
+    // 多位艺术家以 ", " 连接，取最相近的一位
+    let expected_artist = clean_artist(&song_info.artist);
+    let artist_score = std::iter::once(artist)
+        .chain(artist.split(", "))
+        .map(|candidate| similarity(&expected_artist, &clean_artist(candidate)))
+        .fold(0.0, f64::max);
+    let duration_score = match (song_info.duration, duration) {
+        (Some(expected), Some(actual)) => {
+            let diff = expected.abs_diff(actual).as_secs_f64();
+            (1.0 - diff / DURATION_TOLERANCE.as_secs_f64()).max(0.0)
+        }
+        _ => 0.5,
+    };
+
+    title_score * 0.5 + artist_score * 0.2 + duration_score * 0.3
+}
+
+/// 挑选分数最高的候选（分数相同时保留靠前的，即搜索引擎排序更高的）
+pub fn pick_best<T>(candidates: &[T], score: impl Fn(&T) -> f64) -> Option<&T> {
+    candidates.iter()
+        .map(|candidate| (score(candidate), candidate))
+        .fold(None, |best: Option<(f64, &T)>, (score, candidate)| match best {
+            Some((best_score, _)) if best_score >= score => best,
+            _ => Some((score, candidate)),
+        })
+        .map(|(_, candidate)| candidate)
+}
+
+/// 按编辑距离计算的字符串相似度（忽略大小写，1.0 表示相同）
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().flat_map(char::to_lowercase).collect();
+    let b: Vec<char> = b.chars().flat_map(char::to_lowercase).collect();
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    // 单行滚动的 Levenshtein 距离
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    1.0 - row[b.len()] as f64 / max_len as f64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,4 +313,66 @@ mod tests {
     fn test_build_query() {
         assert_eq!(build_query("  Song Title  ", "  Artist Name  "), "Song Title Artist Name");
     }
+
+    #[test]
+    fn test_clean_title_and_artist() {
+        assert_eq!(clean_title("Song (Live) feat. B"), "Song");
+        assert_eq!(clean_title("Hey Jude [Remastered 2011]"), "Hey Jude");
+        assert_eq!(clean_title("Song Ft. Someone"), "Song");
+        assert_eq!(clean_title("Ｓｏｎｇ（Ｌｉｖｅ）"), "Song");
+        assert_eq!(clean_title("晴天【官方MV】"), "晴天");
+        assert_eq!(clean_title("Left Feature"), "Left Feature");
+        // 整个歌名都在括号里时保留原文
+        assert_eq!(clean_title("(Intro)"), "(Intro)");
+
+        // 只删除版本说明，「」和其他括号内容属于歌名本身
+        assert_eq!(clean_title("Song (Remix)"), "Song");
+        assert_eq!(clean_title("夜に駆ける (Live ver.)"), "夜に駆ける");
+        assert_eq!(clean_title("「一番の宝物」"), "「一番の宝物」");
+        assert_eq!(clean_title("残響散歌「TVアニメ」"), "残響散歌「TVアニメ」");
+        assert_eq!(clean_title("Song (Alive)"), "Song (Alive)");
+        assert_eq!(clean_title("Song (Part 2) [Acoustic Version]"), "Song (Part 2)");
+
+        assert_eq!(clean_artist("周杰伦 - Topic"), "周杰伦");
+        assert_eq!(clean_artist("Artist feat. Other"), "Artist");
+    }
+
+    #[test]
+    fn test_score_candidate_prefers_matching_duration() {
+        let song = SongInfo::new("Song", "Artist").with_duration(Some(Duration::from_secs(212)));
+        let close = score_candidate(&song, "Song", "Artist", Some(Duration::from_secs(210)));
+        let far = score_candidate(&song, "Song", "Artist", Some(Duration::from_secs(400)));
+        assert!(close > far);
+
+        // 歌名不同的翻唱即使时长接近也排在后面
+        let cover = score_candidate(&song, "Another Song", "Someone", Some(Duration::from_secs(212)));
+        assert!(close > cover);
+
+        let candidates = [(400, "Song"), (210, "Song")];
+        let best = pick_best(&candidates, |(secs, title)| {
+            score_candidate(&song, title, "Artist", Some(Duration::from_secs(*secs)))
+        });
+        assert_eq!(best, Some(&(210, "Song")));
+
+        // 没有时长信息时保持原有顺序
+        let unknown = SongInfo::new("Song", "Artist");
+        let best = pick_best(&candidates, |(secs, title)| {
+            score_candidate(&unknown, title, "Artist", Some(Duration::from_secs(*secs)))
+        });
+        assert_eq!(best, Some(&(400, "Song")));
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("Song", "song"), 1.0);
+        assert_eq!(similarity("", ""), 1.0);
+        assert_eq!(similarity("kitten", "sitting"), 1.0 - 3.0 / 7.0);
+        assert_eq!(similarity("晴天", "雨天"), 0.5);
+    }
+
+    #[test]
+    fn test_search_queries_fall_back_to_original() {
+        assert_eq!(search_queries("Song", "Artist"), vec!["Song Artist"]);
+        assert_eq!(search_queries("Song (Live)", "Artist - Topic"), vec!["Song Artist", "Song (Live) Artist - Topic"]);
+    }
 }
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::Utc;
+use tracing::{debug, warn};
+
+use crate::lyrics::{LyricsResult, LyricsError, LyricsData, LyricsSource, SongInfo};
+
+/// 本地 `.lrc` 歌词文件（如本地播放器的歌曲旁的同名歌词）
+///
+/// 在指定目录下依次查找 `艺术家 - 标题.lrc` 和 `标题.lrc`，文件编码自动识别 UTF-8 / GBK
+pub struct LocalLrcSource {
+    dir: PathBuf,
+}
+
+impl LocalLrcSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 查找的目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 按歌曲信息查找本地歌词
+    pub fn get_lyrics(&self, song_info: &SongInfo) -> LyricsResult<LyricsData> {
+        let path = self.candidate_paths(song_info)
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or(LyricsError::LyricsNotFound)?;
+
+        debug!("找到本地歌词文件: {:?}", path);
+        let bytes = fs::read(&path).map_err(|e| {
+            warn!("读取本地歌词文件失败: {:?} - {}", path, e);
+            LyricsError::LyricsNotFound
+        })?;
+
+        let mut lyrics = LyricsData {
+            original: Some(decode_lrc(&bytes)),
+            has_lyrics: true,
+            source: LyricsSource::Local,
+            fetched_at: Utc::now(),
+            ..Default::default()
+        };
+        lyrics.is_synced = lyrics.has_timestamps();
+        Some(lyrics)
+            .filter(LyricsData::has_any_content)
+            .ok_or(LyricsError::LyricsNotFound)
+    }
+
+    /// 按优先顺序排列的候选文件路径
+    fn candidate_paths(&self, song_info: &SongInfo) -> Vec<PathBuf> {
+        let title = sanitize_file_name(&song_info.title);
+        let artist = sanitize_file_name(&song_info.artist);
+
+        let mut names = Vec::new();
+        if !artist.is_empty() {
+            names.push(format!("{} - {}.lrc", artist, title));
+        }
+        names.push(format!("{}.lrc", title));
+        names.into_iter().map(|name| self.dir.join(name)).collect()
+    }
+}
+
+/// 把文件名中不允许出现的字符替换为 `_`（如 `AC/DC`）
+fn sanitize_file_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| if matches!(c, '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+        .collect()
+}
+
+/// 解码歌词文件：有 BOM 时按 BOM，其次是合法的 UTF-8，否则按 GBK 解码
+fn decode_lrc(bytes: &[u8]) -> String {
+    if let Some((encoding, bom_len)) = encoding_rs::Encoding::for_bom(bytes) {
+        return encoding.decode_without_bom_handling(&bytes[bom_len..]).0.into_owned();
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => {
+            let (text, _, had_errors) = encoding_rs::GBK.decode(bytes);
+            if had_errors {
+                // 也不是 GBK，按 UTF-8 尽量保留可读内容
+                String::from_utf8_lossy(bytes).into_owned()
+            } else {
+                text.into_owned()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_local_lrc_lookup_prefers_artist_and_title() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = LocalLrcSource::new(temp_dir.path());
+        fs::write(temp_dir.path().join("Song.lrc"), "[00:01.00]title only").unwrap();
+
+        let lyrics = source.get_lyrics(&SongInfo::new("Song", "Artist")).unwrap();
+        assert_eq!(lyrics.source, LyricsSource::Local);
+        assert!(lyrics.is_synced);
+        assert_eq!(lyrics.original.as_deref(), Some("[00:01.00]title only"));
+
+        fs::write(temp_dir.path().join("Artist - Song.lrc"), "[00:01.00]artist and title").unwrap();
+        let lyrics = source.get_lyrics(&SongInfo::new("Song", "Artist")).unwrap();
+        assert_eq!(lyrics.original.as_deref(), Some("[00:01.00]artist and title"));
+
+        assert!(matches!(source.get_lyrics(&SongInfo::new("Missing", "Artist")), Err(LyricsError::LyricsNotFound)));
+    }
+
+    #[test]
+    fn test_decode_lrc_detects_gbk() {
+        let (gbk, _, _) = encoding_rs::GBK.encode("[00:01.00]故事的小黄花");
+        assert_eq!(decode_lrc(&gbk), "[00:01.00]故事的小黄花");
+        assert_eq!(decode_lrc("[00:01.00]晴天".as_bytes()), "[00:01.00]晴天");
+        assert_eq!(decode_lrc(b"\xEF\xBB\xBF[00:01.00]bom"), "[00:01.00]bom");
+        assert_eq!(sanitize_file_name("AC/DC"), "AC_DC");
+    }
+}
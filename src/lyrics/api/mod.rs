@@ -1,7 +1,9 @@
 pub mod netease;
 pub mod qqmusic;
 pub mod common;
+pub mod local;
 
 pub use netease::NetEaseApi;
 pub use qqmusic::QQMusicApi;
+pub use local::LocalLrcSource;
 pub use common::*;
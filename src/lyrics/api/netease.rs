@@ -3,7 +3,7 @@ use crate::lyrics::{
     NetEaseSearchResponse, NetEaseLyricsResponse,
     http_client::HttpClient,
 };
-use super::common::{url_encode, build_query};
+use super::common::{url_encode, search_queries, score_candidate, pick_best, DEFAULT_SEARCH_LIMIT};
 use tracing::{debug, warn};
 
 /// 网易云音乐API客户端
@@ -11,6 +11,8 @@ pub struct NetEaseApi {
     http_client: HttpClient,
     base_search_url: String,
     base_lyrics_url: String,
+    /// 每次搜索获取的候选数量
+    search_limit: usize,
 }
 
 impl NetEaseApi {
@@ -20,27 +22,50 @@ impl NetEaseApi {
             http_client,
             base_search_url: "https://music.163.com/api/search/get/web".to_string(),
             base_lyrics_url: "https://api.vkeys.cn/v2/music/netease/lyric".to_string(),
+            search_limit: DEFAULT_SEARCH_LIMIT,
         }
     }
 
-    /// 搜索歌曲
+    /// 设置每次搜索获取的候选数量（至少为 1）
+    pub fn with_search_limit(mut self, limit: usize) -> Self {
+        self.search_limit = limit.max(1);
+        self
+    }
+
+    /// 预热搜索和歌词接口所在主机的连接
+    pub async fn warm_connection(&self) -> LyricsResult<()> {
+        self.http_client.warm_up(&self.base_search_url).await?;
+        self.http_client.warm_up(&self.base_lyrics_url).await
+    }
+
+    /// 搜索歌曲，按歌名、艺术家和时长从候选中挑选最匹配的结果
     pub async fn search_song(&self, song_info: &SongInfo) -> LyricsResult<Option<SearchResult>> {
         if !song_info.is_valid() {
             return Err(LyricsError::InvalidSongInfo);
         }
 
-        let query = build_query(&song_info.title, &song_info.artist);
-        let encoded_query = url_encode(&query);
+        // 清理后的关键词搜不到时再用原始歌名和艺术家搜索
+        for query in search_queries(&song_info.title, &song_info.artist) {
+            if let Some(result) = self.search_query(&query, song_info).await? {
+                return Ok(Some(result));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 按关键词搜索，返回与歌曲信息最匹配的候选（按歌名、艺术家和时长评分）
+    async fn search_query(&self, query: &str, song_info: &SongInfo) -> LyricsResult<Option<SearchResult>> {
+        let encoded_query = url_encode(query);
 
         let search_url = format!(
-            "{}?csrf_token=hlpretag=&hlposttag=&s={}&type=1&offset=0&total=true&limit=1",
-            self.base_search_url, encoded_query
+            "{}?csrf_token=hlpretag=&hlposttag=&s={}&type=1&offset=0&total=true&limit={}",
+            self.base_search_url, encoded_query, self.search_limit
         );
 
         debug!("网易云搜索URL: {}", search_url);
 
         let response_text = self.http_client.get(&search_url).await?;
-        self.parse_search_response(&response_text)
+        self.parse_search_response(&response_text, song_info)
     }
 
     /// 获取歌词
@@ -64,7 +89,9 @@ impl NetEaseApi {
         match search_result {
             Some(result) => {
                 debug!("找到歌曲: {} (ID: {})", result.title, result.id);
-                self.get_lyrics(&result.id).await
+                let mut lyrics = self.get_lyrics(&result.id).await?;
+                lyrics.matched = Some(result);
+                Ok(lyrics)
             }
             None => {
                 warn!("未找到歌曲: {}", song_info);
@@ -73,25 +100,43 @@ impl NetEaseApi {
         }
     }
 
+    /// 由原始响应文本构建歌词数据（测试用，跳过网络请求）
+    #[cfg(test)]
+    pub(crate) fn lyrics_from_raw_responses(&self, search_response: &str, lyrics_response: &str) -> LyricsResult<LyricsData> {
+        match self.parse_search_response(search_response, &SongInfo::new("", ""))? {
+            Some(_) => self.parse_lyrics_response(lyrics_response),
+            None => Err(LyricsError::SongNotFound),
+        }
+    }
+
     /// 解析搜索响应
-    fn parse_search_response(&self, response: &str) -> LyricsResult<Option<SearchResult>> {
+    pub(crate) fn parse_search_response(&self, response: &str, song_info: &SongInfo) -> LyricsResult<Option<SearchResult>> {
         // 尝试使用serde_json解析
         match serde_json::from_str::<NetEaseSearchResponse>(response) {
             Ok(parsed) => {
                 if let Some(result) = parsed.result {
                     if let Some(songs) = result.songs {
-                        if let Some(first_song) = songs.first() {
-                            let artist_names: Vec<String> = first_song.ar
-                                .iter()
-                                .map(|artist| artist.name.clone())
-                                .collect();
-                            
-                            return Ok(Some(SearchResult {
-                                id: first_song.id.to_string(),
-                                title: first_song.name.clone(),
-                                artist: artist_names.join(", "),
-                                duration: first_song.dt.map(|ms| std::time::Duration::from_millis(ms)),
-                            }));
+                        let results: Vec<SearchResult> = songs.iter()
+                            .map(|song| {
+                                let artist_names: Vec<String> = song.ar
+                                    .iter()
+                                    .map(|artist| artist.name.clone())
+                                    .collect();
+
+                                SearchResult {
+                                    id: song.id.to_string(),
+                                    title: song.name.clone(),
+                                    artist: artist_names.join(", "),
+                                    duration: song.dt.map(std::time::Duration::from_millis),
+                                }
+                            })
+                            .collect();
+
+                        let best = pick_best(&results, |result| {
+                            score_candidate(song_info, &result.title, &result.artist, result.duration)
+                        });
+                        if let Some(best) = best {
+                            return Ok(Some(best.clone()));
                         }
                     }
                 }
@@ -203,7 +248,7 @@ impl NetEaseApi {
     }
 
     /// 解析歌词响应
-    fn parse_lyrics_response(&self, response: &str) -> LyricsResult<LyricsData> {
+    pub(crate) fn parse_lyrics_response(&self, response: &str) -> LyricsResult<LyricsData> {
         match serde_json::from_str::<NetEaseLyricsResponse>(response) {
             Ok(api_response) => {
                 let lyrics_data = LyricsData::from_netease_response(api_response);
@@ -246,4 +291,50 @@ mod tests {
         // 这个测试可能需要根据实际的解析逻辑调整
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_parse_search_response_picks_closest_duration() {
+        let http_client = HttpClient::new(HttpClientConfig::default()).unwrap();
+        let api = NetEaseApi::new(http_client).with_search_limit(0);
+        assert_eq!(api.search_limit, 1);
+
+        let response = r#"{"result":{"songs":[
+            {"id":1,"name":"Song","ar":[{"name":"Artist"}],"dt":400000},
+            {"id":2,"name":"Song","ar":[{"name":"Artist"}],"dt":210000}
+        ]}}"#;
+        let song = SongInfo::new("Song", "Artist").with_duration(Some(std::time::Duration::from_secs(212)));
+        let result = api.parse_search_response(response, &song).unwrap().unwrap();
+        assert_eq!(result.id, "2");
+
+        // 不知道时长时取第一个结果
+        let result = api.parse_search_response(response, &SongInfo::new("Song", "Artist")).unwrap().unwrap();
+        assert_eq!(result.id, "1");
+    }
+
+    #[test]
+    fn test_lyrics_from_raw_responses() {
+        let http_client = HttpClient::new(HttpClientConfig::default()).unwrap();
+        let api = NetEaseApi::new(http_client);
+
+        let search_response = r#"{"result":{"songs":[{"id":186016,"name":"晴天","ar":[{"name":"周杰伦"}],"dt":269000}]}}"#;
+        let lyrics_response = r#"{"code":200,"message":"请求成功","data":{"lrc":"[00:01.00]第一句\n[00:05.00]第二句","yrc":null},"time":"2025-01-01 00:00:00","tips":""}"#;
+
+        let lyrics = api.lyrics_from_raw_responses(search_response, lyrics_response).unwrap();
+        assert!(lyrics.has_lyrics);
+        assert_eq!(lyrics.source, crate::lyrics::LyricsSource::NetEase);
+        assert_eq!(
+            LyricsData::parse_lrc_for_time(lyrics.original.as_ref().unwrap(), std::time::Duration::from_secs(6)),
+            Some("第二句".to_string())
+        );
+    }
+
+    #[test]
+    fn test_lyrics_from_raw_responses_song_not_found() {
+        let http_client = HttpClient::new(HttpClientConfig::default()).unwrap();
+        let api = NetEaseApi::new(http_client);
+
+        let search_response = r#"{"result":{"songs":[]}}"#;
+        let result = api.lyrics_from_raw_responses(search_response, "");
+        assert!(matches!(result, Err(LyricsError::SongNotFound)));
+    }
 }
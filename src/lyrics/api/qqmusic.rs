@@ -1,9 +1,9 @@
 use crate::lyrics::{
-    LyricsResult, LyricsError, LyricsData, SongInfo, QQSearchResult,
+    LyricsResult, LyricsError, LyricsData, SongInfo, QQSearchResult, SearchResult,
     QQSearchResponse, QQMusicLyricsResponse,
     http_client::HttpClient,
 };
-use super::common::{url_encode, build_query};
+use super::common::{url_encode, search_queries, score_candidate, pick_best, DEFAULT_SEARCH_LIMIT};
 use tracing::{debug, warn};
 
 /// QQ音乐API客户端
@@ -11,6 +11,8 @@ pub struct QQMusicApi {
     http_client: HttpClient,
     base_search_url: String,
     base_lyrics_url: String,
+    /// 每次搜索获取的候选数量
+    search_limit: usize,
 }
 
 impl QQMusicApi {
@@ -20,27 +22,50 @@ impl QQMusicApi {
             http_client,
             base_search_url: "http://c.y.qq.com/soso/fcgi-bin/search_cp".to_string(),
             base_lyrics_url: "https://api.vkeys.cn/v2/music/tencent/lyric".to_string(),
+            search_limit: DEFAULT_SEARCH_LIMIT,
         }
     }
 
-    /// 搜索歌曲
+    /// 设置每次搜索获取的候选数量（至少为 1）
+    pub fn with_search_limit(mut self, limit: usize) -> Self {
+        self.search_limit = limit.max(1);
+        self
+    }
+
+    /// 预热搜索和歌词接口所在主机的连接
+    pub async fn warm_connection(&self) -> LyricsResult<()> {
+        self.http_client.warm_up(&self.base_search_url).await?;
+        self.http_client.warm_up(&self.base_lyrics_url).await
+    }
+
+    /// 搜索歌曲，按歌名、艺术家和时长从候选中挑选最匹配的结果
     pub async fn search_song(&self, song_info: &SongInfo) -> LyricsResult<Option<QQSearchResult>> {
         if !song_info.is_valid() {
             return Err(LyricsError::InvalidSongInfo);
         }
 
-        let query = build_query(&song_info.title, &song_info.artist);
-        let encoded_query = url_encode(&query);
+        // 清理后的关键词搜不到时再用原始歌名和艺术家搜索
+        for query in search_queries(&song_info.title, &song_info.artist) {
+            if let Some(result) = self.search_query(&query, song_info).await? {
+                return Ok(Some(result));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 按关键词搜索，返回与歌曲信息最匹配的候选（按歌名、艺术家和时长评分）
+    async fn search_query(&self, query: &str, song_info: &SongInfo) -> LyricsResult<Option<QQSearchResult>> {
+        let encoded_query = url_encode(query);
 
         let search_url = format!(
-            "{}?t=0&aggr=1&cr=1&catZhida=1&lossless=0&flag_qc=0&p=1&w={}&n=1&g_tk=938407465&loginUin=0&hostUin=0&format=json&inCharset=utf8&outCharset=utf-8&notice=0&platform=yqq&needNewCode=0",
-            self.base_search_url, encoded_query
+            "{}?t=0&aggr=1&cr=1&catZhida=1&lossless=0&flag_qc=0&p=1&w={}&n={}&g_tk=938407465&loginUin=0&hostUin=0&format=json&inCharset=utf8&outCharset=utf-8&notice=0&platform=yqq&needNewCode=0",
+            self.base_search_url, encoded_query, self.search_limit
         );
 
         debug!("QQ音乐搜索URL: {}", search_url);
 
         let response_text = self.http_client.get(&search_url).await?;
-        self.parse_search_response(&response_text)
+        self.parse_search_response(&response_text, song_info)
     }
 
     /// 获取歌词
@@ -70,7 +95,14 @@ impl QQMusicApi {
         match search_result {
             Some(result) => {
                 debug!("找到QQ音乐歌曲: {} (ID: {}, MID: {})", result.title, result.song_id, result.song_mid);
-                self.get_lyrics(&result.song_id, &result.song_mid).await
+                let mut lyrics = self.get_lyrics(&result.song_id, &result.song_mid).await?;
+                lyrics.matched = Some(SearchResult {
+                    id: result.song_id,
+                    title: result.title,
+                    artist: result.artist,
+                    duration: result.duration,
+                });
+                Ok(lyrics)
             }
             None => {
                 warn!("未找到QQ音乐歌曲: {}", song_info);
@@ -79,26 +111,45 @@ impl QQMusicApi {
         }
     }
 
+    /// 由原始响应文本构建歌词数据（测试用，跳过网络请求）
+    #[cfg(test)]
+    pub(crate) fn lyrics_from_raw_responses(&self, search_response: &str, lyrics_response: &str) -> LyricsResult<LyricsData> {
+        match self.parse_search_response(search_response, &SongInfo::new("", ""))? {
+            Some(_) => self.parse_lyrics_response(lyrics_response),
+            None => Err(LyricsError::SongNotFound),
+        }
+    }
+
     /// 解析搜索响应
-    fn parse_search_response(&self, response: &str) -> LyricsResult<Option<QQSearchResult>> {
+    pub(crate) fn parse_search_response(&self, response: &str, song_info: &SongInfo) -> LyricsResult<Option<QQSearchResult>> {
         // 尝试使用serde_json解析
         match serde_json::from_str::<QQSearchResponse>(response) {
             Ok(parsed) => {
                 if let Some(data) = parsed.data {
                     if let Some(song_data) = data.song {
                         if let Some(songs) = song_data.list {
-                            if let Some(first_song) = songs.first() {
-                                let artist_names: Vec<String> = first_song.singer
-                                    .iter()
-                                    .map(|singer| singer.name.clone())
-                                    .collect();
-                                
-                                return Ok(Some(QQSearchResult {
-                                    song_id: first_song.songid.to_string(),
-                                    song_mid: first_song.songmid.clone(),
-                                    title: first_song.songname.clone(),
-                                    artist: artist_names.join(", "),
-                                }));
+                            let results: Vec<QQSearchResult> = songs.iter()
+                                .map(|song| {
+                                    let artist_names: Vec<String> = song.singer
+                                        .iter()
+                                        .map(|singer| singer.name.clone())
+                                        .collect();
+
+                                    QQSearchResult {
+                                        song_id: song.songid.to_string(),
+                                        song_mid: song.songmid.clone(),
+                                        title: song.songname.clone(),
+                                        artist: artist_names.join(", "),
+                                        duration: song.interval.map(std::time::Duration::from_secs),
+                                    }
+                                })
+                                .collect();
+
+                            let best = pick_best(&results, |result| {
+                                score_candidate(song_info, &result.title, &result.artist, result.duration)
+                            });
+                            if let Some(best) = best {
+                                return Ok(Some(best.clone()));
                             }
                         }
                     }
@@ -166,6 +217,7 @@ impl QQMusicApi {
                 song_mid,
                 title: "Unknown".to_string(), // 简化处理
                 artist: "Unknown".to_string(),
+                duration: None,
             }))
         } else {
             Ok(None)
@@ -232,7 +284,7 @@ impl QQMusicApi {
     }
 
     /// 解析歌词响应
-    fn parse_lyrics_response(&self, response: &str) -> LyricsResult<LyricsData> {
+    pub(crate) fn parse_lyrics_response(&self, response: &str) -> LyricsResult<LyricsData> {
         match serde_json::from_str::<QQMusicLyricsResponse>(response) {
             Ok(api_response) => {
                 let lyrics_data = LyricsData::from_qqmusic_response(api_response);
@@ -283,4 +335,23 @@ mod tests {
         let result = api.extract_string_field(json, "songmid").unwrap();
         assert_eq!(result, "abc123");
     }
+
+    #[test]
+    fn test_lyrics_from_raw_responses() {
+        let http_client = HttpClient::new(HttpClientConfig::default()).unwrap();
+        let api = QQMusicApi::new(http_client);
+
+        let search_response = r#"{"data":{"song":{"list":[{"songid":97773,"songmid":"0039MnYb0qxYhV","songname":"晴天","singer":[{"name":"周杰伦"}],"interval":269}]}}}"#;
+        let lyrics_response = r#"{"code":200,"message":"请求成功","data":{"lrc":"[00:01.00]第一句\n[00:05.00]第二句","trans":"[00:01.00]first\n[00:05.00]second","yrc":null,"roma":""},"time":"2025-01-01 00:00:00","pid":1,"tips":""}"#;
+
+        let lyrics = api.lyrics_from_raw_responses(search_response, lyrics_response).unwrap();
+        assert!(lyrics.has_lyrics);
+        assert_eq!(lyrics.source, crate::lyrics::LyricsSource::QQMusic);
+        assert!(lyrics.translated.is_some());
+        assert!(lyrics.romanized.is_none());
+        assert_eq!(
+            LyricsData::parse_lrc_for_time(lyrics.original.as_ref().unwrap(), std::time::Duration::from_secs(2)),
+            Some("第一句".to_string())
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use std::fs;
 use chrono::{DateTime, Utc, Duration as ChronoDuration};
@@ -6,26 +7,89 @@ use tracing::{debug, warn, error};
 
 use crate::lyrics::{LyricsResult, LyricsError, LyricsData, SongInfo};
 
+/// 当前的缓存条目格式版本（没有版本字段的旧条目视为版本 1）
+const CACHE_VERSION: u32 = 2;
+
+fn legacy_version() -> u32 {
+    1
+}
+
 /// 缓存条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CacheEntry {
+    /// 条目格式版本
+    #[serde(default = "legacy_version")]
+    version: u32,
     song_info: SongInfo,
     lyrics_data: LyricsData,
     expires_at: DateTime<Utc>,
+    /// 是否为"没有歌词"的标记（过期前不再向歌词源搜索）
+    #[serde(default)]
+    not_found: bool,
 }
 
 impl CacheEntry {
     fn new(song_info: SongInfo, lyrics_data: LyricsData, ttl: ChronoDuration) -> Self {
         Self {
+            version: CACHE_VERSION,
             song_info,
             lyrics_data,
             expires_at: Utc::now() + ttl,
+            not_found: false,
+        }
+    }
+
+    /// 没有歌词的标记条目
+    fn not_found(song_info: SongInfo, ttl: ChronoDuration) -> Self {
+        Self {
+            not_found: true,
+            ..Self::new(song_info, LyricsData::default(), ttl)
         }
     }
 
     fn is_expired(&self) -> bool {
         Utc::now() > self.expires_at
     }
+
+    /// 解析缓存文件内容，旧版本条目先升级到当前格式（返回的 `version` 仍为文件中的版本）
+    fn from_json(content: &str) -> LyricsResult<Self> {
+        let mut value: serde_json::Value = serde_json::from_str(content)?;
+        let version = Self::version_of(&value);
+        if version > CACHE_VERSION {
+            return Err(LyricsError::CacheError(format!("缓存条目版本 {} 高于当前支持的版本 {}", version, CACHE_VERSION)));
+        }
+        if version < 2 {
+            migrate_v1(&mut value);
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// 条目的格式版本
+    fn version_of(value: &serde_json::Value) -> u32 {
+        value.get("version")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(legacy_version(), |version| version as u32)
+    }
+
+    /// 是否由更新版本的程序写入（降级运行时保留这类文件，不当作损坏删除）
+    fn is_newer_version(content: &str) -> bool {
+        serde_json::from_str::<serde_json::Value>(content)
+            .is_ok_and(|value| Self::version_of(&value) > CACHE_VERSION)
+    }
+}
+
+/// 版本 1 → 2：补齐早期条目中可能缺失的必需字段
+fn migrate_v1(value: &mut serde_json::Value) {
+    let Some(lyrics) = value.get_mut("lyrics_data").and_then(serde_json::Value::as_object_mut) else {
+        return;
+    };
+
+    let has_content = ["original", "translated", "romanized"].iter().any(|key| {
+        lyrics.get(*key).and_then(serde_json::Value::as_str).is_some_and(|text| !text.trim().is_empty())
+    });
+    lyrics.entry("has_lyrics").or_insert(has_content.into());
+    lyrics.entry("source").or_insert("Unknown".into());
+    lyrics.entry("fetched_at").or_insert(Utc::now().to_rfc3339().into());
 }
 
 /// 缓存配置
@@ -39,6 +103,10 @@ pub struct CacheConfig {
     pub max_files: usize,
     /// 清理过期文件的间隔（小时）
     pub cleanup_interval_hours: u64,
+    /// 内存中保留的最近使用条目数（0 表示只使用磁盘缓存）
+    pub memory_entries: usize,
+    /// "没有歌词"标记的生存时间（较短，以便歌词源之后收录时能重新找到）
+    pub negative_ttl: ChronoDuration,
 }
 
 impl Default for CacheConfig {
@@ -51,6 +119,8 @@ impl Default for CacheConfig {
             cache_dir,
             max_files: 5000,
             cleanup_interval_hours: 6, // 每6小时清理一次
+            memory_entries: 32,
+            negative_ttl: ChronoDuration::hours(1),
         }
     }
 }
@@ -66,10 +136,77 @@ impl CacheConfig {
     }
 }
 
-/// 歌词缓存（仅文件缓存）
+/// 有容量上限的内存缓存，按最近使用顺序淘汰
+struct MemoryCache {
+    entries: HashMap<String, CacheEntry>,
+    /// 使用顺序，队首为最久未使用的缓存键
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl MemoryCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// 获取条目并标记为最近使用
+    fn get(&mut self, cache_key: &str) -> Option<CacheEntry> {
+        let entry = self.entries.get(cache_key)?.clone();
+        self.touch(cache_key);
+        Some(entry)
+    }
+
+    fn insert(&mut self, cache_key: String, entry: CacheEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.touch(&cache_key);
+        self.entries.insert(cache_key, entry);
+
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn remove(&mut self, cache_key: &str) {
+        self.order.retain(|key| key != cache_key);
+        self.entries.remove(cache_key);
+    }
+
+    /// 删除所有已过期的条目
+    fn remove_expired(&mut self) {
+        self.entries.retain(|_, entry| !entry.is_expired());
+        let entries = &self.entries;
+        self.order.retain(|key| entries.contains_key(key));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn touch(&mut self, cache_key: &str) {
+        self.order.retain(|key| key != cache_key);
+        self.order.push_back(cache_key.to_string());
+    }
+}
+
+/// 歌词缓存（磁盘文件缓存，最近使用的条目同时保留在内存中）
 pub struct LyricsCache {
     config: CacheConfig,
     last_cleanup: std::sync::Mutex<Option<DateTime<Utc>>>,
+    /// 磁盘缓存之上的内存层，避免反复读取和解析同一文件
+    memory: std::sync::Mutex<MemoryCache>,
 }
 
 impl LyricsCache {
@@ -82,6 +219,7 @@ impl LyricsCache {
         debug!("歌词缓存目录: {:?}", config.cache_dir);
 
         Ok(Self {
+            memory: std::sync::Mutex::new(MemoryCache::new(config.memory_entries)),
             config,
             last_cleanup: std::sync::Mutex::new(None),
         })
@@ -92,18 +230,39 @@ impl LyricsCache {
         Self::new(CacheConfig::default())
     }
 
-    /// 获取歌词
+    /// 获取歌词（"没有歌词"的标记不算命中）
     pub async fn get(&self, song_info: &SongInfo) -> Option<LyricsData> {
+        self.get_entry(song_info).await
+            .filter(|entry| !entry.not_found)
+            .map(|entry| entry.lyrics_data)
+    }
+
+    /// 歌曲是否有未过期的"没有歌词"标记
+    pub async fn is_marked_not_found(&self, song_info: &SongInfo) -> bool {
+        self.get_entry(song_info).await.is_some_and(|entry| entry.not_found)
+    }
+
+    /// 获取未过期的缓存条目（包括"没有歌词"的标记）
+    async fn get_entry(&self, song_info: &SongInfo) -> Option<CacheEntry> {
         let cache_key = song_info.cache_key();
         
         // 检查是否需要清理
         self.maybe_cleanup().await;
+
+        // 先查内存层
+        if let Some(entry) = self.memory_get(&cache_key) {
+            if !entry.is_expired() {
+                return Some(entry);
+            }
+            self.memory_remove(&cache_key);
+        }
         
         match self.get_from_disk(&cache_key).await {
             Ok(Some(entry)) => {
                 if !entry.is_expired() {
                     debug!("从缓存获取歌词: {}", song_info);
-                    Some(entry.lyrics_data)
+                    self.memory_insert(cache_key, entry.clone());
+                    Some(entry)
                 } else {
                     // 过期则删除文件
                     debug!("缓存过期，删除文件: {}", song_info);
@@ -124,18 +283,41 @@ impl LyricsCache {
         let cache_key = song_info.cache_key();
         let entry = CacheEntry::new(song_info.clone(), lyrics_data, self.config.ttl);
 
-        self.put_to_disk(&cache_key, &entry).await?;
+        self.put_entry(cache_key, entry).await?;
         debug!("缓存歌词: {}", song_info);
+        Ok(())
+    }
+
+    /// 记录歌曲没有歌词（按 `negative_ttl` 过期，之后成功获取的歌词会覆盖该标记）
+    pub async fn put_not_found(&self, song_info: SongInfo) -> LyricsResult<()> {
+        let cache_key = song_info.cache_key();
+        debug!("记录没有歌词: {}", song_info);
+        self.put_entry(cache_key, CacheEntry::not_found(song_info, self.config.negative_ttl)).await
+    }
+
+    /// 写入磁盘和内存层
+    async fn put_entry(&self, cache_key: String, entry: CacheEntry) -> LyricsResult<()> {
+        self.put_to_disk(&cache_key, &entry).await?;
+        self.memory_insert(cache_key, entry);
 
         // 检查是否需要清理缓存
-        self.cleanup_if_needed().await?;
+        self.cleanup_if_needed().await
+    }
 
-        Ok(())
+    /// 删除指定歌曲的缓存
+    pub async fn remove(&self, song_info: &SongInfo) -> LyricsResult<()> {
+        let cache_key = song_info.cache_key();
+        self.memory_remove(&cache_key);
+        self.remove_from_disk(&cache_key).await
     }
 
     /// 清理过期缓存
     pub async fn cleanup_expired(&self) -> LyricsResult<()> {
         debug!("开始清理过期缓存");
+
+        if let Ok(mut memory) = self.memory.lock() {
+            memory.remove_expired();
+        }
         
         if !self.config.cache_dir.exists() {
             return Ok(());
@@ -153,7 +335,7 @@ impl LyricsCache {
             if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
                 match fs::read_to_string(&path) {
                     Ok(content) => {
-                        match serde_json::from_str::<CacheEntry>(&content) {
+                        match CacheEntry::from_json(&content) {
                             Ok(cache_entry) => {
                                 if cache_entry.is_expired() {
                                     if let Err(e) = fs::remove_file(&path) {
@@ -163,6 +345,9 @@ impl LyricsCache {
                                     }
                                 }
                             }
+                            Err(e) if CacheEntry::is_newer_version(&content) => {
+                                debug!("跳过新版本写入的缓存文件: {} - {}", path.display(), e);
+                            }
                             Err(e) => {
                                 warn!("解析缓存文件失败，删除: {} - {}", path.display(), e);
                                 let _ = fs::remove_file(&path);
@@ -192,6 +377,10 @@ impl LyricsCache {
     /// 清空所有缓存
     pub async fn clear(&self) -> LyricsResult<()> {
         debug!("清空所有缓存");
+
+        if let Ok(mut memory) = self.memory.lock() {
+            memory.clear();
+        }
         
         if self.config.cache_dir.exists() {
             fs::remove_dir_all(&self.config.cache_dir)
@@ -209,6 +398,25 @@ impl LyricsCache {
         Ok(())
     }
 
+    /// 从内存层获取
+    fn memory_get(&self, cache_key: &str) -> Option<CacheEntry> {
+        self.memory.lock().ok()?.get(cache_key)
+    }
+
+    /// 写入内存层
+    fn memory_insert(&self, cache_key: String, entry: CacheEntry) {
+        if let Ok(mut memory) = self.memory.lock() {
+            memory.insert(cache_key, entry);
+        }
+    }
+
+    /// 从内存层删除
+    fn memory_remove(&self, cache_key: &str) {
+        if let Ok(mut memory) = self.memory.lock() {
+            memory.remove(cache_key);
+        }
+    }
+
     /// 从磁盘获取
     async fn get_from_disk(&self, cache_key: &str) -> LyricsResult<Option<CacheEntry>> {
         let file_path = self.config.cache_dir.join(format!("{}.json", cache_key));
@@ -220,9 +428,19 @@ impl LyricsCache {
         let content = fs::read_to_string(&file_path)
             .map_err(|e| LyricsError::CacheError(format!("读取缓存文件失败: {}", e)))?;
         
-        let entry: CacheEntry = serde_json::from_str(&content)
+        let entry = CacheEntry::from_json(&content)
             .map_err(|e| LyricsError::CacheError(format!("解析缓存文件失败: {}", e)))?;
         
+        // 旧版本条目升级后写回，下次直接按当前格式读取
+        if entry.version < CACHE_VERSION {
+            let entry = CacheEntry { version: CACHE_VERSION, ..entry };
+            debug!("缓存条目已升级到版本 {}: {}", CACHE_VERSION, entry.song_info);
+            if let Err(e) = self.put_to_disk(cache_key, &entry).await {
+                warn!("写回升级后的缓存条目失败: {}", e);
+            }
+            return Ok(Some(entry));
+        }
+        
         Ok(Some(entry))
     }
 
@@ -385,6 +603,7 @@ impl std::fmt::Display for CacheStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lyrics::LyricsSource;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -416,6 +635,101 @@ mod tests {
         assert!(entry.is_expired());
     }
 
+    #[test]
+    fn test_v1_entry_is_migrated() {
+        // 版本 1：没有版本字段，也没有 yrc、is_synced、来源和获取时间
+        let v1 = r#"{
+            "song_info": { "title": "测试", "artist": "歌手" },
+            "lyrics_data": { "original": "[00:01.00]第一句", "translated": null, "romanized": null },
+            "expires_at": "2999-01-01T00:00:00Z"
+        }"#;
+
+        let entry = CacheEntry::from_json(v1).unwrap();
+        assert_eq!(entry.version, 1);
+        assert!(entry.lyrics_data.has_lyrics);
+        assert!(entry.lyrics_data.is_synced);
+        assert_eq!(entry.lyrics_data.source, LyricsSource::Unknown);
+        assert_eq!(entry.lyrics_data.original.as_deref(), Some("[00:01.00]第一句"));
+        assert!(!entry.is_expired());
+    }
+
+    #[tokio::test]
+    async fn test_newer_version_is_kept_and_old_version_rewritten() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = LyricsCache::new(CacheConfig {
+            cache_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        }).unwrap();
+
+        let song_info = SongInfo::new("测试", "歌手");
+        let mut entry = CacheEntry::new(song_info.clone(), LyricsData::default(), ChronoDuration::days(1));
+        entry.version = 1;
+        cache.put_to_disk(&song_info.cache_key(), &entry).await.unwrap();
+        assert!(cache.get(&song_info).await.is_some());
+        let content = fs::read_to_string(temp_dir.path().join(format!("{}.json", song_info.cache_key()))).unwrap();
+        assert_eq!(CacheEntry::from_json(&content).unwrap().version, CACHE_VERSION);
+
+        let future = SongInfo::new("未来", "歌手");
+        let future_path = temp_dir.path().join(format!("{}.json", future.cache_key()));
+        fs::write(&future_path, r#"{ "version": 99, "song_info": {} }"#).unwrap();
+        assert!(cache.get(&future).await.is_none());
+        cache.cleanup_expired().await.unwrap();
+        assert!(future_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_memory_layer_serves_repeated_gets() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = LyricsCache::new(CacheConfig {
+            cache_dir: temp_dir.path().to_path_buf(),
+            memory_entries: 1,
+            ..Default::default()
+        }).unwrap();
+
+        let song_info = SongInfo::new("测试", "歌手");
+        let file_path = temp_dir.path().join(format!("{}.json", song_info.cache_key()));
+        cache.put(song_info.clone(), LyricsData::default()).await.unwrap();
+        assert!(cache.get(&song_info).await.is_some());
+
+        // 文件已删除，第二次仍从内存命中
+        fs::remove_file(&file_path).unwrap();
+        assert!(cache.get(&song_info).await.is_some());
+
+        // 超出容量时淘汰最久未使用的条目
+        cache.put(SongInfo::new("另一首", "歌手"), LyricsData::default()).await.unwrap();
+        assert!(cache.get(&song_info).await.is_none());
+
+        cache.clear().await.unwrap();
+        assert!(cache.get(&SongInfo::new("另一首", "歌手")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_not_found_marker_is_overwritten_by_lyrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = LyricsCache::new(CacheConfig {
+            cache_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        }).unwrap();
+
+        let song_info = SongInfo::new("纯音乐", "歌手");
+        cache.put_not_found(song_info.clone()).await.unwrap();
+        assert!(cache.is_marked_not_found(&song_info).await);
+        assert!(cache.get(&song_info).await.is_none());
+
+        cache.put(song_info.clone(), LyricsData::default()).await.unwrap();
+        assert!(!cache.is_marked_not_found(&song_info).await);
+        assert!(cache.get(&song_info).await.is_some());
+
+        // 过期的标记不再生效
+        let expired = LyricsCache::new(CacheConfig {
+            cache_dir: temp_dir.path().join("expired"),
+            negative_ttl: ChronoDuration::milliseconds(-1),
+            ..Default::default()
+        }).unwrap();
+        expired.put_not_found(song_info.clone()).await.unwrap();
+        assert!(!expired.is_marked_not_found(&song_info).await);
+    }
+
     #[tokio::test]
     async fn test_cache_cleanup() {
         let temp_dir = TempDir::new().unwrap();
@@ -11,33 +11,222 @@ pub struct LyricsData {
     pub translated: Option<String>,
     /// 罗马音歌词
     pub romanized: Option<String>,
+    /// 逐字歌词（网易云 yrc 格式原文）
+    #[serde(default)]
+    pub yrc: Option<String>,
     /// 是否有歌词
     pub has_lyrics: bool,
+    /// 歌词是否带时间轴（纯文本歌词为 false；旧缓存没有该字段时视为带时间轴）
+    #[serde(default = "default_is_synced")]
+    pub is_synced: bool,
     /// 歌词来源
     pub source: LyricsSource,
+    /// 搜索时实际匹配到的歌曲（用于核对是否匹配错误，如误选了现场版）
+    #[serde(default)]
+    pub matched: Option<SearchResult>,
     /// 获取时间
     pub fetched_at: DateTime<Utc>,
 }
 
+fn default_is_synced() -> bool {
+    true
+}
+
 /// 歌词来源
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LyricsSource {
     NetEase,
     QQMusic,
+    /// 播放器通过媒体会话提供的歌词
+    Embedded,
+    /// 本地离线歌词包
+    Pack,
+    /// 歌曲旁的本地 `.lrc` 文件
+    Local,
     Unknown,
 }
 
+impl LyricsSource {
+    /// 显示用的来源名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            LyricsSource::NetEase => "网易云音乐",
+            LyricsSource::QQMusic => "QQ音乐",
+            LyricsSource::Embedded => "播放器",
+            LyricsSource::Pack => "歌词包",
+            LyricsSource::Local => "本地文件",
+            LyricsSource::Unknown => "未知来源",
+        }
+    }
+}
+
 impl Default for LyricsSource {
     fn default() -> Self {
         LyricsSource::Unknown
     }
 }
 
+/// 作为时间轴依据的歌词轨道
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimelineSource {
+    /// 逐行歌词（LRC），显示最稳定
+    #[default]
+    Lrc,
+    /// 逐字歌词（yrc），与卡拉OK模式一致；缺失时回退到 LRC
+    Yrc,
+}
+
+/// 歌词显示语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LyricsDisplayMode {
+    /// 原文
+    #[default]
+    Original,
+    /// 翻译
+    Translated,
+    /// 罗马音
+    Romanized,
+    /// 原文和翻译并排显示
+    Bilingual,
+}
+
+impl LyricsDisplayMode {
+    /// 切换顺序
+    pub const CYCLE: [LyricsDisplayMode; 4] = [
+        LyricsDisplayMode::Original,
+        LyricsDisplayMode::Translated,
+        LyricsDisplayMode::Romanized,
+        LyricsDisplayMode::Bilingual,
+    ];
+
+    /// 显示用的名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            LyricsDisplayMode::Original => "原文",
+            LyricsDisplayMode::Translated => "翻译",
+            LyricsDisplayMode::Romanized => "罗马音",
+            LyricsDisplayMode::Bilingual => "双语",
+        }
+    }
+
+    /// 当前歌词是否提供该语言（原文始终可用，作为其他语言缺失时的回退）
+    pub fn is_available(&self, lyrics: &LyricsData) -> bool {
+        let has = |text: &Option<String>| text.as_deref().is_some_and(|text| !text.trim().is_empty());
+        match self {
+            LyricsDisplayMode::Original => true,
+            LyricsDisplayMode::Translated => has(&lyrics.translated),
+            LyricsDisplayMode::Romanized => has(&lyrics.romanized),
+            LyricsDisplayMode::Bilingual => has(&lyrics.original) && has(&lyrics.translated),
+        }
+    }
+
+    /// 按顺序切换到下一个当前歌词提供的语言
+    pub fn next_available(self, lyrics: &LyricsData) -> LyricsDisplayMode {
+        let current = Self::CYCLE.iter().position(|&mode| mode == self).unwrap_or(0);
+        (1..=Self::CYCLE.len())
+            .map(|offset| Self::CYCLE[(current + offset) % Self::CYCLE.len()])
+            .find(|mode| mode.is_available(lyrics))
+            .unwrap_or(LyricsDisplayMode::Original)
+    }
+}
+
+/// 逐字歌词中的一个字（或词）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedWord {
+    /// 开始时间（毫秒）
+    pub start_ms: u64,
+    /// 持续时间（毫秒）
+    pub duration_ms: u64,
+    pub text: String,
+}
+
+/// 带逐字时间的一行歌词
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordTimedLine {
+    /// 行开始时间（毫秒）
+    pub start_ms: u64,
+    /// 行持续时间（毫秒）
+    pub duration_ms: u64,
+    pub words: Vec<TimedWord>,
+}
+
+impl WordTimedLine {
+    /// 整行文本
+    pub fn text(&self) -> String {
+        self.words.iter().map(|word| word.text.as_str()).collect()
+    }
+
+    /// 播放位置是否在本行的时间范围内
+    pub fn contains(&self, position_ms: u64) -> bool {
+        position_ms >= self.start_ms && position_ms < self.start_ms + self.duration_ms
+    }
+
+    /// 已唱部分按字符数计算的比例（0.0 ~ 1.0），正在唱的字按时间插值
+    pub fn sung_fraction(&self, position_ms: u64) -> f32 {
+        let total_chars: usize = self.words.iter().map(|word| word.text.chars().count()).sum();
+        if total_chars == 0 {
+            return 0.0;
+        }
+
+        let sung_chars: f32 = self.words.iter().map(|word| {
+            let chars = word.text.chars().count() as f32;
+            if position_ms >= word.start_ms + word.duration_ms {
+                chars
+            } else if position_ms > word.start_ms {
+                chars * (position_ms - word.start_ms) as f32 / word.duration_ms as f32
+            } else {
+                0.0
+            }
+        }).sum();
+
+        (sung_chars / total_chars as f32).clamp(0.0, 1.0)
+    }
+}
+
+/// 默认的歌曲信息显示模板
+pub const DEFAULT_SONG_DISPLAY_TEMPLATE: &str = "{artist} - {title}";
+
+/// 按模板格式化歌曲信息，支持 `{title}` 和 `{artist}` 占位符
+pub fn format_song_display(template: &str, title: &str, artist: &str) -> String {
+    template.replace("{title}", title).replace("{artist}", artist)
+}
+
 /// 歌曲信息
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// 相等性和哈希与 `cache_key` 的规范化一致：忽略首尾空白和大小写
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SongInfo {
     pub title: String,
     pub artist: String,
+    /// 播放器报告的时长，仅用于挑选搜索结果（不参与比较和缓存键）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<Duration>,
+}
+
+/// 规范化字段（去除首尾空白并转小写）的字符序列
+fn normalized_chars(value: &str) -> impl Iterator<Item = char> + '_ {
+    value.trim().chars().flat_map(char::to_lowercase)
+}
+
+impl PartialEq for SongInfo {
+    fn eq(&self, other: &Self) -> bool {
+        normalized_chars(&self.title).eq(normalized_chars(&other.title))
+            && normalized_chars(&self.artist).eq(normalized_chars(&other.artist))
+    }
+}
+
+impl Eq for SongInfo {}
+
+impl std::hash::Hash for SongInfo {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for field in [&self.title, &self.artist] {
+            for c in normalized_chars(field) {
+                c.hash(state);
+            }
+            // 字段分隔符，避免 ("ab", "c") 与 ("a", "bc") 冲突
+            state.write_u8(0xFF);
+        }
+    }
 }
 
 impl SongInfo {
@@ -45,9 +234,15 @@ impl SongInfo {
         Self {
             title: title.into(),
             artist: artist.into(),
+            duration: None,
         }
     }
 
+    /// 设置曲目时长
+    pub fn with_duration(self, duration: Option<Duration>) -> Self {
+        Self { duration, ..self }
+    }
+
     /// 生成缓存键
     pub fn cache_key(&self) -> String {
         use sha2::{Digest, Sha256};
@@ -59,6 +254,11 @@ impl SongInfo {
         format!("{:x}", result)
     }
 
+    /// 交换歌名和艺术家（用于播放器把两者报反的情况）
+    pub fn swapped(&self) -> Self {
+        Self::new(self.artist.clone(), self.title.clone()).with_duration(self.duration)
+    }
+
     /// 检查歌曲信息是否有效
     pub fn is_valid(&self) -> bool {
         !self.title.trim().is_empty() && !self.artist.trim().is_empty()
@@ -81,6 +281,8 @@ pub struct QQSearchResult {
     pub song_mid: String,
     pub title: String,
     pub artist: String,
+    #[serde(default)]
+    pub duration: Option<Duration>,
 }
 
 /// 网易云音乐API响应
@@ -187,7 +389,9 @@ impl LyricsData {
         self.original = None;
         self.translated = None;
         self.romanized = None;
+        self.yrc = None;
         self.has_lyrics = false;
+        self.is_synced = false;
         self.source = LyricsSource::Unknown;
     }
 
@@ -233,9 +437,27 @@ impl LyricsData {
             }
         }
 
+        data.is_synced = data.has_timestamps();
         data
     }
 
+    /// 从播放器提供的歌词文本创建歌词数据（没有时间轴时返回 None）
+    pub fn from_embedded(text: &str) -> Option<Self> {
+        let lyrics = Self::process_lyrics_string(text);
+        if Self::parse_lrc_lines(&lyrics).is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            original: Some(lyrics),
+            has_lyrics: true,
+            is_synced: true,
+            source: LyricsSource::Embedded,
+            fetched_at: Utc::now(),
+            ..Default::default()
+        })
+    }
+
     /// 从网易云API响应创建歌词数据
     pub fn from_netease_response(response: NetEaseLyricsResponse) -> Self {
         let mut data = Self {
@@ -252,10 +474,17 @@ impl LyricsData {
                         data.has_lyrics = true;
                     }
                 }
-                // 网易云的yrc是逐字歌词，我们暂时不处理
+
+                // 保留逐字歌词，供选择 yrc 作为时间轴时使用
+                if let Some(yrc) = lyrics_data.yrc
+                    && !yrc.trim().is_empty()
+                {
+                    data.yrc = Some(Self::process_lyrics_string(&yrc));
+                }
             }
         }
 
+        data.is_synced = data.has_timestamps();
         data
     }
 
@@ -290,27 +519,238 @@ impl LyricsData {
             }
         }
 
+        data.is_synced = data.has_timestamps();
         data
     }
 
+    /// 歌词中是否有可解析的时间轴（LRC 或逐字歌词）
+    pub fn has_timestamps(&self) -> bool {
+        !self.timeline_lines(TimelineSource::Lrc).is_empty()
+            || self.yrc.as_deref().is_some_and(|yrc| !Self::parse_yrc_lines(yrc).is_empty())
+    }
+
+    /// 把歌词合并为一行纯文本（去掉时间标签和空行），用于显示无时间轴的歌词
+    pub fn plain_text(&self) -> Option<String> {
+        let text = self.timeline_text()?
+            .lines()
+            .map(Self::strip_tags)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        Some(text).filter(|text| !text.is_empty())
+    }
+
+    /// 去掉行首的所有方括号标签
+    fn strip_tags(line: &str) -> &str {
+        let mut rest = line.trim();
+        while let Some(after_tag) = rest.strip_prefix('[').and_then(|tagged| tagged.split_once(']')) {
+            rest = after_tag.1.trim_start();
+        }
+        rest.trim_end()
+    }
+
+    /// 获取用于时间轴匹配的歌词文本（优先原文，其次翻译、罗马音）
+    pub fn timeline_text(&self) -> Option<&str> {
+        [&self.original, &self.translated, &self.romanized]
+            .into_iter()
+            .flatten()
+            .map(|text| text.as_str())
+            .find(|text| !text.trim().is_empty())
+    }
+
+    /// 按显示语言换算当前行：`original_line` 为按时间轴选出的原文行，其他语言取同一播放位置的行
+    ///
+    /// 该语言在此位置没有对应行时回退到原文
+    pub fn line_for_mode(&self, mode: LyricsDisplayMode, original_line: &str, position: Duration) -> String {
+        let line_at = |text: &Option<String>| text.as_deref()
+            .and_then(|text| Self::parse_lrc_for_time(text, position))
+            .filter(|line| !line.trim().is_empty());
+
+        match mode {
+            LyricsDisplayMode::Original => None,
+            LyricsDisplayMode::Translated => line_at(&self.translated),
+            LyricsDisplayMode::Romanized => line_at(&self.romanized),
+            LyricsDisplayMode::Bilingual => line_at(&self.translated)
+                .filter(|translated| translated != original_line)
+                .map(|translated| format!("{}  {}", original_line, translated)),
+        }
+        .unwrap_or_else(|| original_line.to_string())
+    }
+
     /// 根据当前播放时间获取对应的歌词行（静态方法）
     pub fn get_current_lyrics_line(lyrics_data: &LyricsData, current_position: Duration) -> Option<String> {
-        // 优先使用原文歌词
-        let lyrics_text = if let Some(original) = &lyrics_data.original {
-            original
-        } else if let Some(translated) = &lyrics_data.translated {
-            translated
-        } else {
-            return None;
-        };
-        
+        let lyrics_text = lyrics_data.timeline_text()?;
         Self::parse_lrc_for_time(lyrics_text, current_position)
     }
 
+    /// 按指定时间轴来源获取当前歌词行（静态方法）
+    pub fn get_current_line_with_source(
+        lyrics_data: &LyricsData,
+        current_position: Duration,
+        source: TimelineSource,
+    ) -> Option<String> {
+        let current_ms = current_position.as_millis() as u64;
+
+        lyrics_data.timeline_lines(source)
+            .into_iter()
+            .take_while(|(timestamp, _)| *timestamp <= current_ms)
+            .last()
+            .map(|(_, text)| text)
+            .filter(|text| !text.is_empty())
+    }
+
+    /// 按指定来源解析时间轴，选择 yrc 但不可用时回退到 LRC
+    pub fn timeline_lines(&self, source: TimelineSource) -> Vec<(u64, String)> {
+        if source == TimelineSource::Yrc {
+            let lines = self.yrc.as_deref().map(Self::parse_yrc_lines).unwrap_or_default();
+            if !lines.is_empty() {
+                return lines;
+            }
+        }
+
+        self.timeline_text().map(Self::parse_lrc_lines).unwrap_or_default()
+    }
+
+    /// 解析网易云 yrc 逐字歌词为按时间排序的 (毫秒, 整行歌词) 列表（静态方法）
+    /// 行格式: `[行开始毫秒,行时长](字开始,字时长,0)字(字开始,字时长,0)字...`
+    pub fn parse_yrc_lines(yrc: &str) -> Vec<(u64, String)> {
+        let mut lines = Vec::new();
+
+        for line in yrc.lines().map(str::trim) {
+            let Some(header_end) = line.strip_prefix('[').and_then(|rest| rest.find(']')) else {
+                continue;
+            };
+            let header = &line[1..header_end + 1];
+            let Some(start_ms) = header.split(',').next().and_then(|start| start.trim().parse::<u64>().ok()) else {
+                continue;
+            };
+
+            let text = Self::strip_yrc_word_timings(&line[header_end + 2..]);
+            lines.push((start_ms, text.trim().to_string()));
+        }
+
+        lines.sort_by_key(|&(time, _)| time);
+        lines
+    }
+
+    /// 解析逐字歌词，没有可用的 yrc 时为 None（此时回退到逐行歌词）
+    pub fn word_timed_lines(&self) -> Option<Vec<WordTimedLine>> {
+        let lines = Self::parse_yrc_words(self.yrc.as_deref()?);
+        Some(lines).filter(|lines| !lines.is_empty())
+    }
+
+    /// 解析网易云 yrc 逐字歌词，保留每个字的开始时间和时长（静态方法）
+    ///
+    /// 无法解析的行被跳过
+    pub fn parse_yrc_words(yrc: &str) -> Vec<WordTimedLine> {
+        let mut lines = Vec::new();
+
+        for line in yrc.lines().map(str::trim) {
+            let Some(header_end) = line.strip_prefix('[').and_then(|rest| rest.find(']')) else {
+                continue;
+            };
+            let mut header = line[1..header_end + 1].split(',').map(|part| part.trim().parse::<u64>());
+            let (Some(Ok(start_ms)), Some(Ok(duration_ms))) = (header.next(), header.next()) else {
+                continue;
+            };
+
+            let words = Self::parse_yrc_line_words(&line[header_end + 2..]);
+            if !words.is_empty() {
+                lines.push(WordTimedLine { start_ms, duration_ms, words });
+            }
+        }
+
+        lines.sort_by_key(|line| line.start_ms);
+        lines
+    }
+
+    /// 解析一行 yrc 内容中的 `(开始,时长,0)字` 序列，时间标记之间的文本属于前一个标记
+    fn parse_yrc_line_words(content: &str) -> Vec<TimedWord> {
+        let mut words: Vec<TimedWord> = Vec::new();
+        let mut rest = content;
+
+        while !rest.is_empty() {
+            let timing = rest.strip_prefix('(')
+                .and_then(|candidate| candidate.find(')').map(|close| (&candidate[..close], &candidate[close + 1..])))
+                .and_then(|(timing, after)| {
+                    let mut parts = timing.split(',').map(|part| part.trim().parse::<u64>());
+                    match (parts.next(), parts.next()) {
+                        (Some(Ok(start_ms)), Some(Ok(duration_ms))) => Some((start_ms, duration_ms, after)),
+                        _ => None,
+                    }
+                });
+
+            match timing {
+                Some((start_ms, duration_ms, after)) => {
+                    words.push(TimedWord { start_ms, duration_ms, text: String::new() });
+                    rest = after;
+                }
+                None => {
+                    // 普通字符（包括不是时间标记的括号）属于当前字
+                    let mut chars = rest.chars();
+                    let ch = chars.next().unwrap_or_default();
+                    if let Some(word) = words.last_mut() {
+                        word.text.push(ch);
+                    }
+                    rest = chars.as_str();
+                }
+            }
+        }
+
+        words.retain(|word| !word.text.is_empty());
+        words
+    }
+
+    /// 去除 yrc 行内的 `(开始,时长,0)` 逐字时间标记
+    fn strip_yrc_word_timings(content: &str) -> String {
+        let mut text = String::with_capacity(content.len());
+        let mut rest = content;
+
+        while let Some(open) = rest.find('(') {
+            let candidate = &rest[open + 1..];
+            let timing_len = candidate.find(')')
+                .filter(|&close| candidate[..close].chars().all(|c| c.is_ascii_digit() || c == ','));
+
+            match timing_len {
+                Some(close) => {
+                    text.push_str(&rest[..open]);
+                    rest = &candidate[close + 1..];
+                }
+                None => {
+                    // 普通括号属于歌词内容
+                    text.push_str(&rest[..open + 1]);
+                    rest = candidate;
+                }
+            }
+        }
+
+        text.push_str(rest);
+        text
+    }
+
     /// 解析LRC歌词，根据时间获取当前应显示的歌词行（静态方法）
     pub fn parse_lrc_for_time(lyrics: &str, current_position: Duration) -> Option<String> {
         let current_ms = current_position.as_millis() as u64;
+        
+        // 找到当前时间对应的歌词
+        let mut current_lyrics = None;
+        for (timestamp, lyrics_text) in Self::parse_lrc_lines(lyrics) {
+            if timestamp <= current_ms {
+                current_lyrics = Some(lyrics_text);
+            } else {
+                break;
+            }
+        }
+        
+        current_lyrics.filter(|s| !s.is_empty())
+    }
+
+    /// 解析LRC歌词为按时间排序的 (毫秒, 歌词) 列表（静态方法）
+    /// 有 `[offset:N]` 标签时所有时间戳加上 N 毫秒（结果不小于 0）
+    pub fn parse_lrc_lines(lyrics: &str) -> Vec<(u64, String)> {
         let mut lyrics_lines = Vec::new();
+        let offset_ms = Self::parse_lrc_offset(lyrics);
         
         // 解析所有歌词行
         for line in lyrics.lines() {
@@ -322,6 +762,8 @@ impl LyricsData {
                     
                     // 解析时间戳 [mm:ss.xx]
                     if let Some(timestamp_ms) = Self::parse_lrc_timestamp(time_part) {
+                        // 应用整体偏移，负偏移超过时间戳时截断为 0
+                        let timestamp_ms = timestamp_ms.saturating_add_signed(offset_ms);
                         lyrics_lines.push((timestamp_ms, lyrics_content.to_string()));
                     }
                 }
@@ -330,43 +772,247 @@ impl LyricsData {
         
         // 按时间排序
         lyrics_lines.sort_by_key(|&(time, _)| time);
-        
-        // 找到当前时间对应的歌词
-        let mut current_lyrics = None;
-        for (timestamp, lyrics_text) in lyrics_lines {
-            if timestamp <= current_ms {
-                current_lyrics = Some(lyrics_text);
-            } else {
-                break;
-            }
-        }
-        
-        current_lyrics.filter(|s| !s.is_empty())
+        lyrics_lines
+    }
+
+    /// 查找 `[offset:N]` 元数据标签，返回整体时间偏移（毫秒，没有时为 0）
+    pub fn parse_lrc_offset(lyrics: &str) -> i64 {
+        lyrics.lines()
+            .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']'))
+            .filter_map(|tag| tag.split_once(':'))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("offset"))
+            .and_then(|(_, value)| value.trim().parse::<i64>().ok())
+            .unwrap_or(0)
     }
 
-    /// 解析LRC时间戳格式 [mm:ss.xx] 返回毫秒（静态方法）
+    /// 解析LRC时间戳格式 [mm:ss.xx] / [hh:mm:ss.xx] 返回毫秒（静态方法）
+    ///
+    /// 小数部分按位数换算（`.5` 为 500ms，`.50` 为 500ms，`.500` 为 500ms），
+    /// 分钟数不限于两位，超长音频（如三小时的有声书）也能正确解析。
     pub fn parse_lrc_timestamp(time_str: &str) -> Option<u64> {
-        // 格式: mm:ss.xx
         let parts: Vec<&str> = time_str.split(':').collect();
-        if parts.len() != 2 {
-            return None;
-        }
-        
-        let minutes: u64 = parts[0].parse().ok()?;
-        let seconds_parts: Vec<&str> = parts[1].split('.').collect();
-        if seconds_parts.len() != 2 {
+        let (hours, minutes, seconds_part) = match parts.as_slice() {
+            [minutes, seconds] => (0, minutes.parse::<u64>().ok()?, *seconds),
+            [hours, minutes, seconds] => (hours.parse::<u64>().ok()?, minutes.parse::<u64>().ok()?, *seconds),
+            _ => return None,
+        };
+
+        let (seconds, fraction) = seconds_part.split_once('.')?;
+        let seconds: u64 = seconds.parse().ok()?;
+        if fraction.is_empty() || fraction.len() > 3 {
             return None;
         }
-        
-        let seconds: u64 = seconds_parts[0].parse().ok()?;
-        let centiseconds: u64 = seconds_parts[1].parse().ok()?;
-        
-        Some(minutes * 60 * 1000 + seconds * 1000 + centiseconds * 10)
+        let fraction_ms = fraction.parse::<u64>().ok()? * 10u64.pow(3 - fraction.len() as u32);
+
+        let total_seconds = hours.checked_mul(3600)?.checked_add(minutes.checked_mul(60)?)?.checked_add(seconds)?;
+        total_seconds.checked_mul(1000)?.checked_add(fraction_ms)
     }
 }
 
 impl std::fmt::Display for SongInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} - {}", self.artist, self.title)
+        f.write_str(&format_song_display(DEFAULT_SONG_DISPLAY_TEMPLATE, &self.title, &self.artist))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash_of(song: &SongInfo) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        song.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_song_info_equality_ignores_case_and_whitespace() {
+        let a = SongInfo::new("Hello World", "Artist");
+        let b = SongInfo::new("  hello world ", "ARTIST");
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+        assert_eq!(a.cache_key(), b.cache_key());
+
+        assert_ne!(a, SongInfo::new("Hello World 2", "Artist"));
+        assert_ne!(SongInfo::new("ab", "c"), SongInfo::new("a", "bc"));
+    }
+
+    #[test]
+    fn test_yrc_timeline_source() {
+        let lyrics = LyricsData {
+            original: Some("[00:01.00]line one\n[00:04.00]line two".to_string()),
+            yrc: Some("[1200,2000](1200,500,0)line (1700,500,0)one (x)\n[4100,1500](4100,800,0)line (4900,700,0)two".to_string()),
+            has_lyrics: true,
+            ..Default::default()
+        };
+
+        assert_eq!(LyricsData::parse_yrc_lines(lyrics.yrc.as_deref().unwrap()), vec![
+            (1200, "line one (x)".to_string()),
+            (4100, "line two".to_string()),
+        ]);
+
+        let position = Duration::from_millis(1100);
+        assert_eq!(
+            LyricsData::get_current_line_with_source(&lyrics, position, TimelineSource::Lrc),
+            Some("line one".to_string())
+        );
+        assert_eq!(LyricsData::get_current_line_with_source(&lyrics, position, TimelineSource::Yrc), None);
+
+        // 没有 yrc 时回退到 LRC
+        let lrc_only = LyricsData { yrc: None, ..lyrics };
+        assert_eq!(lrc_only.timeline_lines(TimelineSource::Yrc), lrc_only.timeline_lines(TimelineSource::Lrc));
+    }
+
+    #[test]
+    fn test_yrc_word_timings() {
+        let yrc = "[1200,2000](1200,500,0)line (1700,500,0)one (x)\nnot a line\n[4100,1500](4100,800,0)line (4900,700,0)two";
+        let lines = LyricsData::parse_yrc_words(yrc);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text(), "line one (x)");
+        assert_eq!(lines[0].words[1], TimedWord { start_ms: 1700, duration_ms: 500, text: "one (x)".to_string() });
+
+        let line = &lines[1];
+        assert!(line.contains(4100) && !line.contains(5600));
+        assert_eq!(line.sung_fraction(4000), 0.0);
+        // "line " 唱完，"two" 唱了一半
+        assert_eq!(line.sung_fraction(4900 + 350), (5.0 + 1.5) / 8.0);
+        assert_eq!(line.sung_fraction(6000), 1.0);
+
+        // 没有或无法解析的 yrc 回退到逐行歌词
+        assert_eq!(LyricsData::default().word_timed_lines(), None);
+        let broken = LyricsData { yrc: Some("[x]garbage".to_string()), ..Default::default() };
+        assert_eq!(broken.word_timed_lines(), None);
+    }
+
+    #[test]
+    fn test_lrc_offset_tag_shifts_timestamps() {
+        let lyrics = "[ti:test]\n[offset:-300]\n[00:00.10]intro\n[00:10.00]line";
+        assert_eq!(LyricsData::parse_lrc_offset(lyrics), -300);
+        assert_eq!(LyricsData::parse_lrc_lines(lyrics), vec![
+            (0, "intro".to_string()),
+            (9700, "line".to_string()),
+        ]);
+        assert_eq!(LyricsData::parse_lrc_for_time(lyrics, Duration::from_millis(9700)), Some("line".to_string()));
+
+        assert_eq!(LyricsData::parse_lrc_offset("[offset:+500]\n[00:01.00]a"), 500);
+        assert_eq!(LyricsData::parse_lrc_offset("[offset:abc]\n[00:01.00]a"), 0);
+    }
+
+    #[test]
+    fn test_format_song_display() {
+        let song = SongInfo::new("晴天", "周杰伦");
+        assert_eq!(song.to_string(), "周杰伦 - 晴天");
+        assert_eq!(format_song_display("{title} / {artist}", &song.title, &song.artist), "晴天 / 周杰伦");
+        assert_eq!(format_song_display("♪ {title}", &song.title, &song.artist), "♪ 晴天");
+    }
+
+    #[test]
+    fn test_duplicate_timestamps_keep_input_order() {
+        let lyrics = "[00:05.00]first\n[00:01.00]intro\n[00:05.00]second";
+
+        for _ in 0..10 {
+            let lines = LyricsData::parse_lrc_lines(lyrics);
+            assert_eq!(lines, vec![
+                (1000, "intro".to_string()),
+                (5000, "first".to_string()),
+                (5000, "second".to_string()),
+            ]);
+            assert_eq!(
+                LyricsData::parse_lrc_for_time(lyrics, Duration::from_secs(6)),
+                Some("second".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_display_mode_cycle_skips_absent_languages() {
+        let lyrics = LyricsData {
+            original: Some("[00:01.00]原文".to_string()),
+            romanized: Some("[00:01.00]romaji".to_string()),
+            ..Default::default()
+        };
+
+        // 没有翻译，跳过翻译和双语
+        let mode = LyricsDisplayMode::Original.next_available(&lyrics);
+        assert_eq!(mode, LyricsDisplayMode::Romanized);
+        assert_eq!(mode.next_available(&lyrics), LyricsDisplayMode::Original);
+
+        let original_only = LyricsData {
+            original: Some("[00:01.00]原文".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(LyricsDisplayMode::Original.next_available(&original_only), LyricsDisplayMode::Original);
+    }
+
+    #[test]
+    fn test_line_for_mode_uses_same_position() {
+        let lyrics = LyricsData {
+            original: Some("[00:01.00]hello\n[00:05.00]world".to_string()),
+            translated: Some("[00:01.00]你好\n[00:05.00]世界".to_string()),
+            ..Default::default()
+        };
+        let position = Duration::from_secs(6);
+
+        assert_eq!(lyrics.line_for_mode(LyricsDisplayMode::Original, "world", position), "world");
+        assert_eq!(lyrics.line_for_mode(LyricsDisplayMode::Translated, "world", position), "世界");
+        assert_eq!(lyrics.line_for_mode(LyricsDisplayMode::Bilingual, "world", position), "world  世界");
+        // 没有罗马音时回退到原文
+        assert_eq!(lyrics.line_for_mode(LyricsDisplayMode::Romanized, "world", position), "world");
+    }
+
+    #[test]
+    fn test_timeline_text_falls_back_to_translated() {
+        let lyrics = LyricsData {
+            original: Some("  ".to_string()),
+            translated: Some("[00:01.00]translated line".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(lyrics.timeline_text(), Some("[00:01.00]translated line"));
+        assert_eq!(
+            LyricsData::get_current_lyrics_line(&lyrics, Duration::from_secs(2)),
+            Some("translated line".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_embedded_requires_timestamps() {
+        let lyrics = LyricsData::from_embedded("[00:01.00]one\\n[00:02.00]two").unwrap();
+        assert_eq!(lyrics.source, LyricsSource::Embedded);
+        assert_eq!(lyrics.timeline_lines(TimelineSource::Lrc).len(), 2);
+
+        assert!(LyricsData::from_embedded("plain text\nwithout timing").is_none());
+    }
+
+    #[test]
+    fn test_plain_text_lyrics_are_unsynced() {
+        let response = LyricsApiResponse {
+            code: None,
+            lrc: Some("[ti:Song]\n第一行歌词\n\n第二行歌词".to_string()),
+            trans: None,
+            roma: None,
+        };
+        let lyrics = LyricsData::from_api_response(response, LyricsSource::QQMusic);
+
+        assert!(lyrics.has_any_content());
+        assert!(!lyrics.is_synced);
+        assert_eq!(lyrics.plain_text().as_deref(), Some("第一行歌词  第二行歌词"));
+
+        let timed = LyricsData::from_embedded("[00:01.00]one").unwrap();
+        assert!(timed.is_synced);
+    }
+
+    #[test]
+    fn test_parse_lrc_timestamp_fraction_and_hours() {
+        assert_eq!(LyricsData::parse_lrc_timestamp("01:02.50"), Some(62_500));
+        assert_eq!(LyricsData::parse_lrc_timestamp("01:02.5"), Some(62_500));
+        assert_eq!(LyricsData::parse_lrc_timestamp("01:02.123"), Some(62_123));
+        assert_eq!(LyricsData::parse_lrc_timestamp("180:00.00"), Some(10_800_000));
+        assert_eq!(LyricsData::parse_lrc_timestamp("3:00:01.25"), Some(10_801_250));
+        assert_eq!(LyricsData::parse_lrc_timestamp("01:02"), None);
+        assert_eq!(LyricsData::parse_lrc_timestamp("01:02.1234"), None);
+        assert_eq!(LyricsData::parse_lrc_timestamp("ti:Song"), None);
     }
 }
@@ -36,6 +36,12 @@ pub enum LyricsError {
     #[error("服务不可用")]
     ServiceUnavailable,
 
+    #[error("未启用任何歌词源，请至少启用网易云音乐或QQ音乐")]
+    NoSourcesEnabled,
+
+    #[error("本次加载的请求次数已用完")]
+    AttemptBudgetExhausted,
+
     #[error("内部错误: {0}")]
     InternalError(String),
 }
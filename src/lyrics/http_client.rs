@@ -1,4 +1,6 @@
-use reqwest::Client;
+use reqwest::{Client, NoProxy, Proxy};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, warn, error};
@@ -16,6 +18,18 @@ pub struct HttpClientConfig {
     pub user_agent: String,
     /// 连接超时
     pub connect_timeout: Duration,
+    /// 首次尝试的请求超时（应短于 `timeout`，网络卡顿时尽快进入重试）
+    pub first_attempt_timeout: Option<Duration>,
+    /// 所有请求使用的代理地址（`http_proxy` / `https_proxy` 优先）
+    pub proxy: Option<String>,
+    /// http:// 请求使用的代理地址
+    pub http_proxy: Option<String>,
+    /// https:// 请求使用的代理地址
+    pub https_proxy: Option<String>,
+    /// 是否使用系统代理设置（如 `HTTPS_PROXY` 环境变量；关闭后不使用任何未显式配置的代理）
+    pub use_system_proxy: bool,
+    /// 不经过上面显式配置的代理的主机（逗号分隔，格式同 `NO_PROXY`；未设置时使用 `NO_PROXY` 环境变量）
+    pub no_proxy: Option<String>,
 }
 
 impl Default for HttpClientConfig {
@@ -25,28 +39,107 @@ impl Default for HttpClientConfig {
             max_retries: 3,
             user_agent: "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36".to_string(),
             connect_timeout: Duration::from_secs(10),
+            first_attempt_timeout: Some(Duration::from_secs(8)),
+            proxy: None,
+            http_proxy: None,
+            https_proxy: None,
+            use_system_proxy: true,
+            no_proxy: None,
         }
     }
 }
 
+/// 一次歌词加载的 HTTP 尝试次数预算（跨歌词源和重试共享）
+///
+/// 在 [`AttemptBudget::scope`] 中运行的请求每次尝试（包括重试）都消耗一次，用完后立即失败，
+/// 避免网络不佳时一首歌的多个歌词源轮流重试长时间占用网络。
+#[derive(Debug)]
+pub struct AttemptBudget {
+    remaining: AtomicU32,
+}
+
+tokio::task_local! {
+    static ATTEMPT_BUDGET: Arc<AttemptBudget>;
+}
+
+impl AttemptBudget {
+    /// 创建预算（0 表示不限制）
+    pub fn new(max_attempts: u32) -> Arc<Self> {
+        let max_attempts = if max_attempts == 0 { u32::MAX } else { max_attempts };
+        Arc::new(Self { remaining: AtomicU32::new(max_attempts) })
+    }
+
+    /// 剩余的尝试次数
+    pub fn remaining(&self) -> u32 {
+        self.remaining.load(Ordering::Relaxed)
+    }
+
+    /// 在预算内运行：期间通过 `HttpClient` 发出的请求都计入预算
+    pub async fn scope<F: std::future::Future>(self: &Arc<Self>, future: F) -> F::Output {
+        ATTEMPT_BUDGET.scope(Arc::clone(self), future).await
+    }
+
+    /// 消耗一次尝试，预算已用完时返回 false
+    fn try_take(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| remaining.checked_sub(1))
+            .is_ok()
+    }
+
+    /// 当前任务的预算中消耗一次尝试（不在预算范围内运行时不限制）
+    fn take_current() -> bool {
+        ATTEMPT_BUDGET.try_with(|budget| budget.try_take()).unwrap_or(true)
+    }
+
+    /// 当前任务的预算是否还有剩余
+    fn current_has_remaining() -> bool {
+        ATTEMPT_BUDGET.try_with(|budget| budget.remaining() > 0).unwrap_or(true)
+    }
+}
+
 /// HTTP客户端，支持指数退避重试
 #[derive(Clone)]
 pub struct HttpClient {
     client: Client,
     config: HttpClientConfig,
+    /// 运行时可调整的最大重试次数（所有克隆共享）
+    max_retries: Arc<AtomicU32>,
 }
 
 impl HttpClient {
     /// 创建新的HTTP客户端
     pub fn new(config: HttpClientConfig) -> LyricsResult<Self> {
-        let client = Client::builder()
+        let mut builder = Client::builder()
             .timeout(config.timeout)
             .connect_timeout(config.connect_timeout)
-            .user_agent(&config.user_agent)
+            .user_agent(&config.user_agent);
+
+        // 代理设置
+        if !config.use_system_proxy {
+            builder = builder.no_proxy();
+        }
+        // 显式配置的代理不会自动读取 NO_PROXY，需要单独设置排除的主机
+        let no_proxy = || match &config.no_proxy {
+            Some(hosts) => NoProxy::from_string(hosts),
+            None if config.use_system_proxy => NoProxy::from_env(),
+            None => None,
+        };
+        if let Some(proxy_url) = &config.http_proxy {
+            builder = builder.proxy(Proxy::http(proxy_url).map_err(LyricsError::NetworkError)?.no_proxy(no_proxy()));
+        }
+        if let Some(proxy_url) = &config.https_proxy {
+            builder = builder.proxy(Proxy::https(proxy_url).map_err(LyricsError::NetworkError)?.no_proxy(no_proxy()));
+        }
+        if let Some(proxy_url) = &config.proxy {
+            builder = builder.proxy(Proxy::all(proxy_url).map_err(LyricsError::NetworkError)?.no_proxy(no_proxy()));
+        }
+
+        let client = builder
             .build()
             .map_err(LyricsError::NetworkError)?;
 
-        Ok(Self { client, config })
+        let max_retries = Arc::new(AtomicU32::new(config.max_retries));
+        Ok(Self { client, config, max_retries })
     }
 
     /// 创建默认HTTP客户端
@@ -54,20 +147,55 @@ impl HttpClient {
         Self::new(HttpClientConfig::default())
     }
 
+    /// 获取当前的最大重试次数
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries.load(Ordering::Relaxed)
+    }
+
+    /// 运行时调整最大重试次数，对之后的请求生效（所有克隆共享）
+    pub fn set_max_retries(&self, max_retries: u32) {
+        self.max_retries.store(max_retries, Ordering::Relaxed);
+    }
+
     /// 发送GET请求
     pub async fn get(&self, url: &str) -> LyricsResult<String> {
         self.request_with_retry(url).await
     }
 
+    /// 预热到指定地址所在主机的连接（单次 HEAD 请求，不重试，任何 HTTP 响应都视为成功）
+    pub async fn warm_up(&self, url: &str) -> LyricsResult<()> {
+        let parsed_url = Url::parse(url)?;
+        let origin = parsed_url.origin().ascii_serialization();
+        debug!("预热连接: {}", origin);
+
+        let mut request = self.client.head(origin.as_str());
+        if let Some(timeout) = self.attempt_timeout(0) {
+            request = request.timeout(timeout);
+        }
+
+        request
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| self.classify_error(e))
+    }
+
     /// 带重试机制的请求
     async fn request_with_retry(&self, url: &str) -> LyricsResult<String> {
         let parsed_url = Url::parse(url)?;
         debug!("发送HTTP请求: {}", url);
 
         let mut last_error = None;
+        let max_retries = self.max_retries();
+
+        for attempt in 0..=max_retries {
+            if !AttemptBudget::take_current() {
+                warn!("本次加载的请求次数已用完，放弃请求: {}", url);
+                return Err(LyricsError::AttemptBudgetExhausted);
+            }
 
-        for attempt in 0..=self.config.max_retries {
-            match self.execute_request(&parsed_url).await {
+            let attempt_timeout = self.attempt_timeout(attempt);
+            match self.execute_request(&parsed_url, attempt_timeout).await {
                 Ok(response_text) => {
                     debug!("请求成功，尝试次数: {}", attempt + 1);
                     return Ok(response_text);
@@ -75,13 +203,16 @@ impl HttpClient {
                 Err(error) => {
                     last_error = Some(error);
                     
-                    if attempt < self.config.max_retries {
+                    if attempt < max_retries && !AttemptBudget::current_has_remaining() {
+                        warn!("本次加载的请求次数已用完，不再重试: {:?}", last_error);
+                        return Err(LyricsError::AttemptBudgetExhausted);
+                    } else if attempt < max_retries {
                         let delay_ms = self.calculate_retry_delay(attempt);
                         warn!(
                             "请求失败，将在{}ms后重试 (尝试 {}/{}): {:?}",
                             delay_ms,
                             attempt + 1,
-                            self.config.max_retries + 1,
+                            max_retries + 1,
                             last_error
                         );
                         
@@ -98,10 +229,24 @@ impl HttpClient {
         }))
     }
 
+    /// 获取指定尝试次数的请求超时（None 表示使用客户端默认超时）
+    fn attempt_timeout(&self, attempt: u32) -> Option<Duration> {
+        if attempt == 0 {
+            self.config.first_attempt_timeout
+                .filter(|timeout| *timeout < self.config.timeout)
+        } else {
+            None
+        }
+    }
+
     /// 执行单次HTTP请求
-    async fn execute_request(&self, url: &Url) -> LyricsResult<String> {
-        let response = self.client
-            .get(url.clone())
+    async fn execute_request(&self, url: &Url, timeout: Option<Duration>) -> LyricsResult<String> {
+        let mut request = self.client.get(url.clone());
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+
+        let response = request
             .send()
             .await
             .map_err(|e| self.classify_error(e))?;
@@ -166,6 +311,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_first_attempt_timeout_only_applies_once() {
+        let config = HttpClientConfig {
+            timeout: Duration::from_secs(30),
+            first_attempt_timeout: Some(Duration::from_secs(5)),
+            ..Default::default()
+        };
+        let client = HttpClient::new(config).unwrap();
+
+        assert_eq!(client.attempt_timeout(0), Some(Duration::from_secs(5)));
+        assert_eq!(client.attempt_timeout(1), None);
+
+        // 不短于总超时时忽略
+        let config = HttpClientConfig {
+            timeout: Duration::from_secs(5),
+            first_attempt_timeout: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let client = HttpClient::new(config).unwrap();
+        assert_eq!(client.attempt_timeout(0), None);
+    }
+
+    #[tokio::test]
+    async fn test_attempt_budget_caps_retries() {
+        // 指向本机未监听的端口，每次请求都会失败
+        let config = HttpClientConfig {
+            http_proxy: Some("http://127.0.0.1:9".to_string()),
+            https_proxy: Some("http://127.0.0.1:9".to_string()),
+            use_system_proxy: false,
+            max_retries: 5,
+            ..Default::default()
+        };
+        let client = HttpClient::new(config).unwrap();
+        let budget = AttemptBudget::new(3);
+
+        let result = budget.scope(client.get("https://example.com/search")).await;
+        assert!(matches!(result, Err(LyricsError::AttemptBudgetExhausted)));
+        assert_eq!(budget.remaining(), 0);
+
+        // 预算用完后后续请求直接失败
+        let result = budget.scope(client.get("https://example.com/lyric")).await;
+        assert!(matches!(result, Err(LyricsError::AttemptBudgetExhausted)));
+        assert_eq!(AttemptBudget::new(0).remaining(), u32::MAX);
+    }
+
+    #[test]
+    fn test_client_with_proxy_config() {
+        let config = HttpClientConfig {
+            http_proxy: Some("http://127.0.0.1:8080".to_string()),
+            https_proxy: Some("http://127.0.0.1:8080".to_string()),
+            use_system_proxy: false,
+            ..Default::default()
+        };
+
+        assert!(HttpClient::new(config).is_ok());
+
+        let config = HttpClientConfig {
+            proxy: Some("http://proxy.example.com:3128".to_string()),
+            ..Default::default()
+        };
+        assert!(HttpClient::new(config).is_ok());
+
+        let config = HttpClientConfig {
+            proxy: Some("http://proxy.example.com:3128".to_string()),
+            no_proxy: Some("localhost, 127.0.0.1, .music.163.com".to_string()),
+            use_system_proxy: false,
+            ..Default::default()
+        };
+        assert!(HttpClient::new(config).is_ok());
+
+        let config = HttpClientConfig {
+            proxy: Some("http://[invalid".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(HttpClient::new(config), Err(LyricsError::NetworkError(_))));
+    }
+
     #[tokio::test]
     async fn test_error_classification() {
         let client = HttpClient::default().unwrap();
@@ -1,10 +1,13 @@
 use std::time::{Duration, Instant};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
 use tokio::sync::{watch, RwLock};
 use tracing::{debug, info, warn};
 
-use crate::lyrics::{LyricsData, LyricsService, SongInfo};
-use crate::system::{PlaybackEvent, MediaEvent};
+use crate::lyrics::{LyricsData, LyricsError, LyricsOverride, LyricsResult, LyricsService, LyricsSource, SongInfo, TimelineSource};
+use crate::lyrics::http_client::AttemptBudget;
+use crate::system::{app_id_matches, MediaEvent, MediaInfo, PlaybackEvent, PlaybackStatus};
 
 /// 歌词事件
 #[derive(Debug, Clone)]
@@ -28,6 +31,8 @@ pub enum LyricsEvent {
         line: Option<String>,
         position: Duration,
     },
+    /// 播放停止但保留歌词（`clear_on_stop` 关闭时）
+    Stopped,
     /// 歌词清空
     Cleared,
 }
@@ -39,12 +44,26 @@ pub struct LyricsState {
     pub current_song: Option<SongInfo>,
     /// 当前歌词数据
     pub current_lyrics: Option<LyricsData>,
+    /// 当前歌词解析后的时间轴 (毫秒, 歌词)，没有歌词时为空
+    pub timeline: Arc<Vec<(u64, String)>>,
+    /// 当前歌词是否带时间轴（没有歌词时为 false）
+    pub is_synced: bool,
     /// 是否正在加载
     pub is_loading: bool,
+    /// 本次加载的开始时间（未在加载时为 None）
+    pub load_started_at: Option<Instant>,
     /// 当前歌词行
     pub current_line: Option<String>,
+    /// 下一句歌词（用于预览，由后台引擎按播放位置刷新）
+    pub next_line: Option<String>,
+    /// 当前行在时间轴中的索引（仅在重复行重新触发时记录，用于区分相同文本的每一次出现）
+    pub current_line_index: Option<usize>,
     /// 当前播放位置
     pub current_position: Duration,
+    /// 播放位置是否还在第一句歌词之前（前奏）
+    pub before_first_line: bool,
+    /// 播放已停止但保留了最后的歌词状态
+    pub is_stopped: bool,
     /// 最后更新时间
     pub last_updated: Instant,
 }
@@ -54,40 +73,331 @@ impl Default for LyricsState {
         Self {
             current_song: None,
             current_lyrics: None,
+            timeline: Arc::default(),
+            is_synced: false,
             is_loading: false,
+            load_started_at: None,
             current_line: None,
+            next_line: None,
+            current_line_index: None,
             current_position: Duration::ZERO,
+            before_first_line: false,
+            is_stopped: false,
             last_updated: Instant::now(),
         }
     }
 }
 
+/// 搜索歌词时使用的艺术家字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArtistField {
+    /// 使用艺术家字段
+    #[default]
+    Artist,
+    /// 使用专辑艺术家字段（为空时回退到艺术家）
+    AlbumArtist,
+    /// 先用艺术家搜索，失败后再用专辑艺术家搜索
+    Both,
+}
+
+/// 相同歌词行重复出现（如副歌）时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RepeatedLines {
+    /// 按时间轴来源决定：逐字歌词（卡拉OK）重新触发，逐行歌词合并
+    #[default]
+    Auto,
+    /// 每次出现都重新开始高亮和滚动
+    Retrigger,
+    /// 文本相同视为同一行，不重新开始滚动
+    Merge,
+}
+
+impl RepeatedLines {
+    /// 在指定时间轴来源下是否重新触发重复行
+    pub fn retriggers(self, timeline_source: TimelineSource) -> bool {
+        match self {
+            RepeatedLines::Auto => timeline_source == TimelineSource::Yrc,
+            RepeatedLines::Retrigger => true,
+            RepeatedLines::Merge => false,
+        }
+    }
+}
+
+/// 判断媒体会话是否为音乐的规则（视频、播客等不搜索歌词，只显示标题）
+#[derive(Debug, Clone)]
+pub struct MediaFilter {
+    /// 视为非音乐的应用（按应用 ID 子串匹配，不区分大小写）
+    pub blocked_apps: Vec<String>,
+    /// 只为这些应用搜索歌词，媒体监控也只读取这些应用的会话（为空时不限制，按应用 ID 子串匹配）
+    pub allowed_apps: Vec<String>,
+    /// 根据媒体类型、流派和标题识别视频与播客
+    pub skip_video: bool,
+}
+
+impl Default for MediaFilter {
+    fn default() -> Self {
+        Self {
+            blocked_apps: vec!["ZuneVideo".to_string(), "Netflix".to_string()],
+            allowed_apps: Vec::new(),
+            skip_video: true,
+        }
+    }
+}
+
+impl MediaFilter {
+    /// 不做任何过滤的规则
+    pub fn permissive() -> Self {
+        Self {
+            blocked_apps: Vec::new(),
+            allowed_apps: Vec::new(),
+            skip_video: false,
+        }
+    }
+
+    /// 是否对所有媒体都搜索歌词
+    pub fn is_permissive(&self) -> bool {
+        self.blocked_apps.is_empty() && self.allowed_apps.is_empty() && !self.skip_video
+    }
+
+    /// 判断媒体是否为音乐
+    pub fn is_music(&self, media_info: &MediaInfo) -> bool {
+        if app_id_matches(&self.blocked_apps, &media_info.app_name) {
+            return false;
+        }
+        if !self.allowed_apps.is_empty() && !app_id_matches(&self.allowed_apps, &media_info.app_name) {
+            return false;
+        }
+        if self.skip_video && (media_info.is_video || Self::looks_like_video(media_info)) {
+            return false;
+        }
+        true
+    }
+
+    /// 根据流派和标题粗略判断是否为视频或播客
+    fn looks_like_video(media_info: &MediaInfo) -> bool {
+        let is_non_music_genre = media_info.genres.iter().any(|genre| {
+            let genre = genre.to_lowercase();
+            ["podcast", "audiobook", "video", "播客", "有声书"].iter().any(|keyword| genre.contains(keyword))
+        });
+
+        // 剧集标题（第N集/期、Episode N、EP N）
+        let title = media_info.title.to_lowercase();
+        let is_episode_title = (title.contains('第') && (title.contains('集') || title.contains('期')))
+            || title.contains("episode ")
+            || title.split(|c: char| !c.is_alphanumeric())
+                .any(|word| word.strip_prefix("ep").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())));
+
+        is_non_music_genre || is_episode_title
+    }
+}
+
+/// 歌词管理器配置
+#[derive(Debug, Clone)]
+pub struct LyricsManagerConfig {
+    /// 播放停止或媒体信息为空时是否清空歌词（关闭后保留最后一行直到下一首歌开始）
+    pub clear_on_stop: bool,
+    /// 内存中最多缓存的歌曲时间轴数量，超出时淘汰最久未加载的歌曲
+    pub max_cached_timelines: usize,
+    /// 新歌曲需要连续出现的媒体更新次数才确认切歌（1 表示不防抖）
+    pub song_change_debounce: u32,
+    /// 歌词行匹配使用的时间轴来源（默认 LRC）
+    pub timeline_source: TimelineSource,
+    /// 搜索歌词时使用的艺术家字段
+    pub artist_field: ArtistField,
+    /// 一行歌词至少显示多久才切换到下一行，避免密集段落频繁换行（0 表示严格按时间轴）
+    pub min_line_interval: Duration,
+    /// 播放器提供带时间轴的歌词时优先使用，不再联网搜索
+    pub prefer_embedded_lyrics: bool,
+    /// 非音乐媒体的识别规则
+    pub media_filter: MediaFilter,
+    /// 相同歌词行重复出现时是否重新触发高亮和滚动
+    pub repeated_lines: RepeatedLines,
+    /// 加载一首歌的歌词时所有歌词源和重试共用的 HTTP 尝试次数上限（0 表示不限制）
+    pub max_attempts_per_load: u32,
+    /// 播放状态为 Unknown（播放器启动中）时推迟加载歌词的最长时间，超过后照常加载（0 表示不推迟）
+    pub unknown_status_grace: Duration,
+    /// 所有歌词时间轴整体加上的偏移（毫秒，正数推迟、负数提前，与 `[offset:N]` 标签方向一致）
+    pub lyrics_offset_ms: i64,
+}
+
+impl Default for LyricsManagerConfig {
+    fn default() -> Self {
+        Self {
+            clear_on_stop: true,
+            max_cached_timelines: 100,
+            song_change_debounce: 1,
+            timeline_source: TimelineSource::Lrc,
+            artist_field: ArtistField::Artist,
+            min_line_interval: Duration::ZERO,
+            prefer_embedded_lyrics: false,
+            media_filter: MediaFilter::default(),
+            repeated_lines: RepeatedLines::default(),
+            max_attempts_per_load: 10,
+            unknown_status_grace: Duration::from_secs(2),
+            lyrics_offset_ms: 0,
+        }
+    }
+}
+
+/// 当前显示行的停留记录（用于最短换行间隔）
+#[derive(Debug, Clone, Copy)]
+struct LineDwell {
+    /// 开始显示的时间
+    shown_at: Instant,
+    /// 该行在时间轴上的开始时间（毫秒）
+    start_ms: u64,
+}
+
+/// 播放位置在时间轴中的定位结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineLocation {
+    /// 当前应显示的歌词行
+    pub line: Option<String>,
+    /// 当前行在时间轴中的索引（仅在重复行重新触发时记录）
+    pub index: Option<usize>,
+    /// 下一句非空歌词
+    pub next_line: Option<String>,
+    /// 播放位置是否还在第一句非空歌词之前
+    pub before_first_line: bool,
+}
+
+/// 对时间轴的一次遍历结果
+struct TimelineScan {
+    /// 开始时间不晚于播放位置的最后一行（包括空行）
+    latest: Option<usize>,
+    /// 播放位置之后的第一句非空歌词
+    next_line: Option<String>,
+    /// 播放位置是否还在第一句非空歌词之前
+    before_first_line: bool,
+}
+
+impl TimelineScan {
+    fn new(timeline: &[(u64, String)], position: Duration) -> Self {
+        let current_ms = position.as_millis() as u64;
+        let mut latest = None;
+        let mut first_line_ms = None;
+        let mut next_line = None;
+
+        for (index, (timestamp, text)) in timeline.iter().enumerate() {
+            if first_line_ms.is_none() && !text.is_empty() {
+                first_line_ms = Some(*timestamp);
+            }
+            if *timestamp <= current_ms {
+                latest = Some(index);
+            } else if !text.is_empty() {
+                next_line = Some(text.clone());
+                break;
+            }
+        }
+
+        Self {
+            latest,
+            next_line,
+            before_first_line: first_line_ms.is_some_and(|timestamp| current_ms < timestamp),
+        }
+    }
+}
+
+/// 有容量上限的时间轴缓存，按最近加载顺序淘汰
+struct TimelineCache {
+    entries: HashMap<SongInfo, Arc<Vec<(u64, String)>>>,
+    /// 加载顺序，队首为最久未加载的歌曲
+    order: VecDeque<SongInfo>,
+    capacity: usize,
+}
+
+impl TimelineCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn get(&self, song_info: &SongInfo) -> Option<&Arc<Vec<(u64, String)>>> {
+        self.entries.get(song_info)
+    }
+
+    fn insert(&mut self, song_info: SongInfo, timeline: Arc<Vec<(u64, String)>>) {
+        self.order.retain(|song| song != &song_info);
+        self.order.push_back(song_info.clone());
+        self.entries.insert(song_info, timeline);
+
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.entries.remove(&oldest);
+                    debug!("淘汰时间轴缓存: {}", oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn remove(&mut self, song_info: &SongInfo) {
+        self.order.retain(|song| song != song_info);
+        self.entries.remove(song_info);
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
 /// 歌词管理器
 /// 负责歌词获取、缓存和实时匹配
 pub struct LyricsManager {
     /// 歌词服务
     lyrics_service: LyricsService,
+    /// 管理器配置
+    config: LyricsManagerConfig,
     /// 当前状态
     state: RwLock<LyricsState>,
     /// 事件发送器
     event_sender: watch::Sender<LyricsEvent>,
     /// 解析后的歌词缓存 (歌曲信息 -> 时间戳歌词列表)
-    parsed_lyrics_cache: RwLock<HashMap<SongInfo, Vec<(u64, String)>>>,
+    parsed_lyrics_cache: RwLock<TimelineCache>,
     /// 缓存最后清理时间，用于定期清理
     cache_last_cleanup: RwLock<Instant>,
+    /// 待确认的切歌候选及其连续出现次数（用于切歌防抖）
+    pending_song: RwLock<Option<(SongInfo, u32)>>,
+    /// 当前显示行的停留记录
+    line_dwell: RwLock<Option<LineDwell>>,
+    /// 播放状态开始连续上报 Unknown 的时间
+    unknown_status_since: RwLock<Option<Instant>>,
+    /// 时间轴整体偏移（毫秒），可在运行时修改
+    lyrics_offset_ms: AtomicI64,
 }
 
 impl LyricsManager {
     /// 创建新的歌词管理器
     pub fn new(lyrics_service: LyricsService) -> (Self, watch::Receiver<LyricsEvent>) {
+        Self::with_config(lyrics_service, LyricsManagerConfig::default())
+    }
+
+    /// 使用指定配置创建歌词管理器
+    pub fn with_config(lyrics_service: LyricsService, config: LyricsManagerConfig) -> (Self, watch::Receiver<LyricsEvent>) {
         let (event_sender, event_receiver) = watch::channel(LyricsEvent::Cleared);
         
+        let max_cached_timelines = config.max_cached_timelines;
+        let lyrics_offset_ms = AtomicI64::new(config.lyrics_offset_ms);
         let manager = Self {
             lyrics_service,
+            config,
             state: RwLock::new(LyricsState::default()),
             event_sender,
-            parsed_lyrics_cache: RwLock::new(HashMap::new()),
+            parsed_lyrics_cache: RwLock::new(TimelineCache::new(max_cached_timelines)),
             cache_last_cleanup: RwLock::new(Instant::now()),
+            pending_song: RwLock::new(None),
+            line_dwell: RwLock::new(None),
+            unknown_status_since: RwLock::new(None),
+            lyrics_offset_ms,
         };
         
         (manager, event_receiver)
@@ -105,7 +415,10 @@ impl LyricsManager {
                 let is_different_song = state.current_song.as_ref() != Some(&song_info);
                 drop(state);
                 
-                if is_different_song {
+                if is_different_song && !self.playback_events_load_lyrics() {
+                    // 启用防抖或使用专辑艺术家时由媒体事件确认切歌
+                    debug!("播放事件：等待媒体事件确认切歌");
+                } else if is_different_song {
                     self.load_lyrics_for_song(song_info).await;
                 } else {
                     debug!("播放事件：歌曲无变化，跳过歌词加载");
@@ -140,7 +453,13 @@ impl LyricsManager {
         match event {
             MediaEvent::InfoUpdated(media_info) => {
                 if !media_info.title.is_empty() && !media_info.artist.is_empty() {
-                    let song_info = SongInfo::new(&media_info.title, &media_info.artist);
+                    // 视频、播客等不搜索歌词，由界面直接显示标题
+                    if !self.config.media_filter.is_music(&media_info) {
+                        self.skip_non_music(&media_info).await;
+                        return;
+                    }
+                    
+                    let (song_info, fallback_song) = self.song_info_for_media(&media_info);
                     
                     // 检查是否是新歌曲，使用更严格的检测逻辑
                     let (is_new_song, old_song, needs_cache_cleanup) = {
@@ -162,6 +481,21 @@ impl LyricsManager {
                         (should_reload, current_song, needs_cleanup)
                     };
                     
+                    // 播放器启动时可能短暂上报 Unknown，等状态明确后再加载，避免过早加载
+                    if is_new_song && self.defer_for_unknown_status(&media_info).await {
+                        debug!("播放状态未知，暂不加载: {}", song_info);
+                        return;
+                    }
+                    
+                    self.set_stopped(false).await;
+                    
+                    // 切歌防抖：新歌曲需连续出现足够次数，避免播放器短暂上报下一首标题
+                    let current_song_changed = old_song.as_ref() != Some(&song_info);
+                    if !self.confirm_song_change(&song_info, current_song_changed).await {
+                        debug!("切歌待确认，暂不加载: {}", song_info);
+                        return;
+                    }
+                    
                     if is_new_song {
                         info!("检测到歌曲切换或状态异常: {:?} -> {:?}", old_song, song_info);
                         
@@ -170,8 +504,11 @@ impl LyricsManager {
                             let mut state = self.state.write().await;
                             state.current_song = Some(song_info.clone());
                             state.is_loading = true;
+                            state.load_started_at = Some(Instant::now());
                             state.current_line = None;
                             state.current_lyrics = None;
+                            state.timeline = Arc::default();
+                            state.is_synced = false;
                             state.current_position = Duration::ZERO;
                             state.last_updated = Instant::now();
                         }
@@ -193,8 +530,14 @@ impl LyricsManager {
                             song_info: song_info.clone(),
                         });
                         
-                        // 加载新歌词
-                        self.load_lyrics_for_song(song_info).await;
+                        // 加载新歌词（播放器自带带时间轴的歌词时直接使用）
+                        match self.embedded_lyrics_for(&media_info) {
+                            Some(lyrics_data) => {
+                                info!("使用播放器提供的歌词: {}", song_info);
+                                self.complete_loading(song_info, lyrics_data).await;
+                            }
+                            None => self.load_lyrics_with_fallback(song_info, fallback_song).await,
+                        }
                     }
                     
                     // 更新播放位置
@@ -202,29 +545,89 @@ impl LyricsManager {
                         self.update_current_position(position).await;
                     }
                 } else {
-                    // 媒体信息为空时，清空歌词
-                    self.clear_lyrics().await;
+                    // 媒体信息为空时，按配置清空或保留歌词
+                    self.handle_stop().await;
                 }
             }
-            MediaEvent::Error(_) | MediaEvent::Stopped => {
+            MediaEvent::Stopped => {
+                self.handle_stop().await;
+            }
+            MediaEvent::Error(_) => {
                 self.clear_lyrics().await;
             }
         }
     }
 
+    /// 播放事件的切歌通知是否可以直接触发加载（否则由媒体事件确认）
+    fn playback_events_load_lyrics(&self) -> bool {
+        // 播放事件不含应用和媒体类型信息，过滤非音乐媒体时必须由媒体事件确认
+        self.config.song_change_debounce <= 1
+            && self.config.artist_field == ArtistField::Artist
+            && self.config.media_filter.is_permissive()
+    }
+
+    /// 非音乐媒体：清空之前的歌词，不搜索
+    async fn skip_non_music(&self, media_info: &MediaInfo) {
+        let has_song = self.state.read().await.current_song.is_some();
+        *self.pending_song.write().await = None;
+        if has_song {
+            info!("非音乐媒体，不搜索歌词: {} ({})", media_info.title, media_info.app_name);
+            self.clear_lyrics().await;
+        }
+    }
+
+    /// 按配置的艺术家字段生成歌曲信息，返回 (主要歌曲信息, 备用搜索信息)
+    fn song_info_for_media(&self, media_info: &MediaInfo) -> (SongInfo, Option<SongInfo>) {
+        let by_artist = SongInfo::new(&media_info.title, &media_info.artist).with_duration(media_info.duration);
+        let by_album_artist = Some(&media_info.album_artist)
+            .filter(|album_artist| !album_artist.trim().is_empty())
+            .map(|album_artist| SongInfo::new(&media_info.title, album_artist).with_duration(media_info.duration))
+            .filter(|song| *song != by_artist);
+
+        match self.config.artist_field {
+            ArtistField::Artist => (by_artist, None),
+            ArtistField::AlbumArtist => (by_album_artist.unwrap_or(by_artist), None),
+            ArtistField::Both => (by_artist, by_album_artist),
+        }
+    }
+
+    /// 按配置取出播放器提供的带时间轴歌词（未启用、没有或不带时间轴时返回 None）
+    fn embedded_lyrics_for(&self, media_info: &MediaInfo) -> Option<LyricsData> {
+        if !self.config.prefer_embedded_lyrics {
+            return None;
+        }
+        media_info.embedded_lyrics.as_deref().and_then(LyricsData::from_embedded)
+    }
+
     /// 为指定歌曲加载歌词
     async fn load_lyrics_for_song(&self, song_info: SongInfo) {
+        self.load_lyrics_with_fallback(song_info, None).await;
+    }
+
+    /// 为指定歌曲加载歌词，失败时使用备用歌曲信息再搜索一次
+    async fn load_lyrics_with_fallback(&self, song_info: SongInfo, fallback_song: Option<SongInfo>) {
+        self.load_lyrics_with(song_info, fallback_song, None).await;
+    }
+
+    /// 加载歌词的完整流程；`forced` 为本次手动指定的匹配（不持久化，也不读写缓存）
+    async fn load_lyrics_with(&self, song_info: SongInfo, fallback_song: Option<SongInfo>, forced: Option<LyricsOverride>) {
         info!("开始加载歌词: {}", song_info);
         
         // 检查是否已经设置为加载状态，如果没有则设置
         {
             let mut state = self.state.write().await;
             if state.current_song != Some(song_info.clone()) || !state.is_loading {
+                // 同一首歌重新加载（手动更正匹配、刷新）时保留播放位置，加载完成后立即定位到当前行
+                if state.current_song.as_ref() != Some(&song_info) {
+                    state.current_position = Duration::ZERO;
+                }
                 state.current_song = Some(song_info.clone());
                 state.is_loading = true;
+                state.load_started_at = Some(Instant::now());
                 state.current_lyrics = None;
+                state.timeline = Arc::default();
+                state.is_synced = false;
                 state.current_line = None;
-                state.current_position = Duration::ZERO;
                 state.last_updated = Instant::now();
                 
                 // 发送加载开始事件
@@ -234,34 +637,29 @@ impl LyricsManager {
             }
         }
         
-        // 异步加载歌词
-        match self.lyrics_service.search_and_get_lyrics(&song_info).await {
+        // 异步加载歌词（手动指定过匹配的歌曲直接按ID获取，不再搜索）
+        let lyrics_override = forced.clone().or_else(|| self.lyrics_service.get_override(&song_info));
+        // 本次加载的所有请求（包括备用歌曲信息的搜索）共用一份尝试次数预算
+        let budget = AttemptBudget::new(self.config.max_attempts_per_load);
+        let result = budget.scope(async {
+            let result = match (&forced, &lyrics_override) {
+                (Some(forced), _) => self.lyrics_service.fetch_lyrics_by_id(forced).await,
+                (None, Some(lyrics_override)) => self.lyrics_service.get_lyrics_by_override(&song_info, lyrics_override).await,
+                (None, None) => self.lyrics_service.search_and_get_lyrics(&song_info).await,
+            };
+            match (result, &fallback_song, &lyrics_override) {
+                (Err(e), Some(fallback_song), None) if !matches!(e, LyricsError::AttemptBudgetExhausted) => {
+                    info!("使用专辑艺术家重新搜索: {} ({})", fallback_song, e);
+                    self.lyrics_service.search_and_get_lyrics(fallback_song).await
+                }
+                (result, _, _) => result,
+            }
+        }).await;
+        
+        match result {
             Ok(lyrics_data) => {
                 info!("成功加载歌词: {}", song_info);
-                
-                // 解析歌词并缓存
-                if let Some(original_lyrics) = &lyrics_data.original {
-                    let parsed_lyrics = self.parse_lyrics_to_timestamps(original_lyrics);
-                    self.parsed_lyrics_cache.write().await.insert(song_info.clone(), parsed_lyrics);
-                }
-                
-                // 更新状态
-                {
-                    let mut state = self.state.write().await;
-                    state.current_lyrics = Some(lyrics_data.clone());
-                    state.is_loading = false;
-                    state.last_updated = Instant::now();
-                }
-                
-                // 发送加载完成事件
-                let _ = self.event_sender.send(LyricsEvent::LoadingCompleted {
-                    song_info,
-                    lyrics: lyrics_data,
-                });
-                
-                // 立即更新当前歌词行
-                let current_position = self.state.read().await.current_position;
-                self.update_current_lyrics_line(current_position).await;
+                self.complete_loading(song_info, lyrics_data).await;
             }
             Err(e) => {
                 warn!("加载歌词失败: {} - {}", song_info, e);
@@ -270,6 +668,7 @@ impl LyricsManager {
                 {
                     let mut state = self.state.write().await;
                     state.is_loading = false;
+                    state.load_started_at = None;
                     state.last_updated = Instant::now();
                 }
                 
@@ -282,6 +681,55 @@ impl LyricsManager {
         }
     }
 
+    /// 歌词获取成功：解析时间轴、更新状态、发送加载完成事件并立即定位当前行
+    async fn complete_loading(&self, song_info: SongInfo, lyrics_data: LyricsData) {
+        // 新歌词从第一行开始计算停留时间
+        *self.line_dwell.write().await = None;
+
+        // 按配置的时间轴来源解析歌词并缓存（原文缺失时使用翻译或罗马音）
+        let parsed_lyrics = Arc::new(self.parse_timeline(&lyrics_data));
+        if !parsed_lyrics.is_empty() {
+            let last_timestamp = parsed_lyrics.last().map(|(time, _)| *time).unwrap_or(0);
+            debug!("歌词时间轴: {} 行, 覆盖至 {:?}", parsed_lyrics.len(), Duration::from_millis(last_timestamp));
+            self.parsed_lyrics_cache.write().await.insert(song_info.clone(), parsed_lyrics.clone());
+        }
+        
+        // 更新状态
+        {
+            let mut state = self.state.write().await;
+            state.is_synced = lyrics_data.is_synced;
+            state.current_lyrics = Some(lyrics_data.clone());
+            state.timeline = parsed_lyrics;
+            state.is_loading = false;
+            state.load_started_at = None;
+            state.last_updated = Instant::now();
+        }
+        
+        // 发送加载完成事件
+        let _ = self.event_sender.send(LyricsEvent::LoadingCompleted {
+            song_info,
+            lyrics: lyrics_data,
+        });
+        
+        // 立即更新当前歌词行
+        let current_position = self.state.read().await.current_position;
+        self.update_current_lyrics_line(current_position).await;
+    }
+
+    /// 跳转到指定播放位置并立即重新匹配歌词行（供拖动进度等外部位置控制使用）
+    pub async fn seek_to(&self, position: Duration) {
+        debug!("跳转到播放位置: {:?}", position);
+        {
+            let mut state = self.state.write().await;
+            state.current_position = position;
+            state.last_updated = Instant::now();
+        }
+        
+        // 跳转不受最短换行间隔限制
+        *self.line_dwell.write().await = None;
+        self.update_current_lyrics_line(position).await;
+    }
+
     /// 更新当前播放位置（仅在播放时更新歌词行）
     async fn update_current_position(&self, position: Duration) {
         {  
@@ -319,11 +767,9 @@ impl LyricsManager {
             cache.get(&song_info).cloned()
         };
         
-        let current_line = if let Some(lyrics_list) = parsed_lyrics {
-            self.find_current_lyrics_line(&lyrics_list, position)
-        } else {
-            None
-        };
+        let candidate = parsed_lyrics.and_then(|lyrics_list| self.find_current_entry(&lyrics_list, position));
+        let displayed = self.state.read().await.current_line.clone();
+        let current_line = self.hold_line_for_interval(&displayed, candidate, position).await;
         
         // 更新状态中的当前歌词行
         {
@@ -343,39 +789,15 @@ impl LyricsManager {
         }
     }
 
-    /// 解析歌词为时间戳列表
-    fn parse_lyrics_to_timestamps(&self, lyrics: &str) -> Vec<(u64, String)> {
-        let mut lyrics_lines = Vec::new();
-        
-        for line in lyrics.lines() {
-            let line = line.trim();
-            if !line.is_empty() && line.starts_with('[') {
-                if let Some(close_bracket) = line.find(']') {
-                    let time_part = &line[1..close_bracket];
-                    let lyrics_content = &line[close_bracket + 1..].trim();
-                    
-                    // 解析时间戳
-                    if let Some(timestamp_ms) = LyricsData::parse_lrc_timestamp(time_part) {
-                        lyrics_lines.push((timestamp_ms, lyrics_content.to_string()));
-                    }
-                }
-            }
-        }
-        
-        // 按时间排序
-        lyrics_lines.sort_by_key(|&(time, _)| time);
-        lyrics_lines
-    }
-
-    /// 根据当前播放时间查找对应的歌词行
-    fn find_current_lyrics_line(&self, lyrics_list: &[(u64, String)], position: Duration) -> Option<String> {
+    /// 根据当前播放时间查找对应的歌词行及其开始时间（跳过空行）
+    fn find_current_entry(&self, lyrics_list: &[(u64, String)], position: Duration) -> Option<(u64, Option<String>)> {
         let current_ms = position.as_millis() as u64;
         
         let mut current_lyrics = None;
         for (timestamp, lyrics_text) in lyrics_list {
             if *timestamp <= current_ms {
                 if !lyrics_text.is_empty() {
-                    current_lyrics = Some(lyrics_text.clone());
+                    current_lyrics = Some((*timestamp, Some(lyrics_text.clone())));
                 }
             } else {
                 break;
@@ -385,6 +807,128 @@ impl LyricsManager {
         current_lyrics
     }
 
+    /// 按配置的时间轴来源解析歌词，并加上整体偏移
+    fn parse_timeline(&self, lyrics: &LyricsData) -> Vec<(u64, String)> {
+        let mut timeline = lyrics.timeline_lines(self.config.timeline_source);
+        let offset_ms = self.lyrics_offset_ms.load(Ordering::Relaxed);
+        if offset_ms != 0 {
+            for (timestamp, _) in &mut timeline {
+                *timestamp = timestamp.saturating_add_signed(offset_ms);
+            }
+        }
+        timeline
+    }
+
+    /// 按指定歌词数据计算当前应显示的歌词行（遵循时间轴来源和最短换行间隔）
+    pub async fn current_line_for(&self, lyrics: &LyricsData, position: Duration, displayed: &Option<String>) -> Option<String> {
+        let timeline = self.parse_timeline(lyrics);
+        self.locate_line(&timeline, position, displayed, None).await.line
+    }
+
+    /// 播放位置是否还在第一句非空歌词之前（前奏期间当前行为 None）
+    pub fn is_before_first_line(&self, lyrics: &LyricsData, position: Duration) -> bool {
+        let timeline = self.parse_timeline(lyrics);
+        TimelineScan::new(&timeline, position).before_first_line
+    }
+
+    /// 当前显示行在时间轴中的索引，用于重复行每次出现时重新触发
+    ///
+    /// 显示行因最短换行间隔被保持时沿用 `previous`；重复行合并模式下始终为 None
+    pub fn current_line_index_for(
+        &self,
+        lyrics: &LyricsData,
+        position: Duration,
+        displayed: &Option<String>,
+        previous: Option<usize>,
+    ) -> Option<usize> {
+        let timeline = self.parse_timeline(lyrics);
+        self.locate_displayed(&timeline, position, displayed.clone(), previous).index
+    }
+
+    /// 在已解析的时间轴中定位播放位置：一次遍历得到当前行（应用最短换行间隔）、索引、下一句和前奏标记
+    ///
+    /// 后台引擎每次定时更新调用，时间轴使用 [`LyricsState::timeline`] 共享的解析结果，不会重新解析歌词
+    pub async fn locate_line(
+        &self,
+        timeline: &[(u64, String)],
+        position: Duration,
+        displayed: &Option<String>,
+        previous: Option<usize>,
+    ) -> LineLocation {
+        let scan = TimelineScan::new(timeline, position);
+        let candidate = scan.latest.map(|index| {
+            let (timestamp, text) = &timeline[index];
+            (*timestamp, Some(text.clone()).filter(|text| !text.is_empty()))
+        });
+        let line = self.hold_line_for_interval(displayed, candidate, position).await;
+        self.location_from_scan(timeline, scan, line, previous)
+    }
+
+    /// 按已确定的显示行计算索引、下一句和前奏标记（不应用最短换行间隔）
+    pub fn locate_displayed(
+        &self,
+        timeline: &[(u64, String)],
+        position: Duration,
+        line: Option<String>,
+        previous: Option<usize>,
+    ) -> LineLocation {
+        let scan = TimelineScan::new(timeline, position);
+        self.location_from_scan(timeline, scan, line, previous)
+    }
+
+    /// 由遍历结果和显示行组装定位结果
+    fn location_from_scan(
+        &self,
+        timeline: &[(u64, String)],
+        scan: TimelineScan,
+        line: Option<String>,
+        previous: Option<usize>,
+    ) -> LineLocation {
+        let index = if self.config.repeated_lines.retriggers(self.config.timeline_source) {
+            line.as_deref().and_then(|line| {
+                let latest = scan.latest?;
+                if timeline[latest].1 == line { Some(latest) } else { previous }
+            })
+        } else {
+            None
+        };
+
+        LineLocation {
+            line,
+            index,
+            next_line: scan.next_line,
+            before_first_line: scan.before_first_line,
+        }
+    }
+
+    /// 应用最短换行间隔：当前行显示不足间隔时继续显示，除非播放位置已越过新行开始时间一个间隔（如拖动进度）
+    async fn hold_line_for_interval(
+        &self,
+        displayed: &Option<String>,
+        candidate: Option<(u64, Option<String>)>,
+        position: Duration,
+    ) -> Option<String> {
+        let (start_ms, line) = candidate.unwrap_or((0, None));
+        let min_interval = self.config.min_line_interval;
+        if min_interval.is_zero() || line == *displayed {
+            return line;
+        }
+
+        let mut dwell = self.line_dwell.write().await;
+        let now = Instant::now();
+        let should_hold = dwell.is_some_and(|dwell| {
+            start_ms > dwell.start_ms
+                && now.duration_since(dwell.shown_at) < min_interval
+                && (position.as_millis() as u64) < start_ms + min_interval.as_millis() as u64
+        });
+        if should_hold {
+            return displayed.clone();
+        }
+
+        *dwell = Some(LineDwell { shown_at: now, start_ms });
+        line
+    }
+
     /// 获取下一句歌词的开始时间（用于计算滚动速度）
     pub async fn get_next_lyrics_time(&self, current_position: Duration) -> Option<Duration> {
         let state = self.state.read().await;
@@ -399,17 +943,99 @@ impl LyricsManager {
         let current_ms = current_position.as_millis() as u64;
         
         // 找到下一句歌词的时间戳
-        for (timestamp, lyrics_text) in lyrics_list {
-            if timestamp > current_ms && !lyrics_text.is_empty() {
-                return Some(Duration::from_millis(timestamp));
+        for (timestamp, lyrics_text) in lyrics_list.iter() {
+            if *timestamp > current_ms && !lyrics_text.is_empty() {
+                return Some(Duration::from_millis(*timestamp));
             }
         }
         
         None
     }
 
+    /// 获取下一句歌词的文本（跳过空行）
+    pub async fn get_next_line_text(&self, current_position: Duration) -> Option<String> {
+        let song_info = self.state.read().await.current_song.clone()?;
+        let current_ms = current_position.as_millis() as u64;
+
+        let cache = self.parsed_lyrics_cache.read().await;
+        cache.get(&song_info)?
+            .iter()
+            .find(|(timestamp, lyrics_text)| *timestamp > current_ms && !lyrics_text.is_empty())
+            .map(|(_, lyrics_text)| lyrics_text.clone())
+    }
+
+    /// 播放状态连续为 Unknown 且未超过宽限期时返回 true（状态明确后重置计时）
+    async fn defer_for_unknown_status(&self, media_info: &MediaInfo) -> bool {
+        let mut unknown_since = self.unknown_status_since.write().await;
+        if media_info.playback_status != PlaybackStatus::Unknown {
+            *unknown_since = None;
+            return false;
+        }
+
+        let since = *unknown_since.get_or_insert_with(Instant::now);
+        since.elapsed() < self.config.unknown_status_grace
+    }
+
+    /// 记录切歌候选，返回该歌曲是否已确认（连续出现次数达到防抖要求）
+    async fn confirm_song_change(&self, song_info: &SongInfo, is_different: bool) -> bool {
+        let mut pending = self.pending_song.write().await;
+
+        if !is_different || self.config.song_change_debounce <= 1 {
+            *pending = None;
+            return true;
+        }
+
+        let count = match pending.as_mut() {
+            Some((candidate, count)) if candidate == song_info => {
+                *count += 1;
+                *count
+            }
+            _ => {
+                *pending = Some((song_info.clone(), 1));
+                1
+            }
+        };
+
+        if count >= self.config.song_change_debounce {
+            *pending = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 处理播放停止：按配置清空歌词，或保留状态并标记为已停止
+    async fn handle_stop(&self) {
+        if self.config.clear_on_stop {
+            self.clear_lyrics().await;
+        } else if self.set_stopped(true).await {
+            info!("播放停止，保留当前歌词");
+            let _ = self.event_sender.send(LyricsEvent::Stopped);
+        }
+    }
+
+    /// 更新停止标记，返回是否发生变化
+    async fn set_stopped(&self, stopped: bool) -> bool {
+        let mut state = self.state.write().await;
+        if state.is_stopped == stopped {
+            return false;
+        }
+
+        state.is_stopped = stopped;
+        state.last_updated = Instant::now();
+        true
+    }
+
+    /// 完全重置：清空歌词状态、解析缓存和待确认的切歌
+    pub async fn reset(&self) {
+        self.parsed_lyrics_cache.write().await.clear();
+        *self.pending_song.write().await = None;
+        *self.line_dwell.write().await = None;
+        self.clear_lyrics().await;
+    }
+
     /// 清空歌词
-    async fn clear_lyrics(&self) {
+    pub async fn clear_lyrics(&self) {
         info!("清空歌词状态");
         
         {
@@ -418,7 +1044,10 @@ impl LyricsManager {
             
             state.current_song = None;
             state.current_lyrics = None;
+            state.timeline = Arc::default();
+            state.is_synced = false;
             state.is_loading = false;
+            state.load_started_at = None;
             state.current_line = None;
             state.current_position = Duration::ZERO;
             state.last_updated = Instant::now();
@@ -445,6 +1074,56 @@ impl LyricsManager {
         self.state.read().await.current_line.clone()
     }
 
+    /// 获取当前歌曲的完整歌词数据
+    pub async fn get_current_lyrics(&self) -> Option<LyricsData> {
+        self.state.read().await.current_lyrics.clone()
+    }
+
+    /// 获取当前歌曲的完整歌词数据（同步版本，非阻塞）
+    pub fn try_get_current_lyrics(&self) -> Option<LyricsData> {
+        self.state.try_read().ok()?.current_lyrics.clone()
+    }
+
+    /// 当前歌词解析后的时间轴（与时间轴缓存共享，不复制歌词）
+    pub async fn current_timeline(&self) -> Arc<Vec<(u64, String)>> {
+        self.state.read().await.timeline.clone()
+    }
+
+    /// 获取当前歌曲解析后的时间轴 (毫秒, 歌词)
+    pub async fn get_current_timeline(&self) -> Vec<(u64, String)> {
+        let song_info = match self.state.read().await.current_song.clone() {
+            Some(song) => song,
+            None => return Vec::new(),
+        };
+
+        self.parsed_lyrics_cache.read().await
+            .get(&song_info)
+            .map(|timeline| timeline.to_vec())
+            .unwrap_or_default()
+    }
+
+    /// 获取当前歌曲解析后的时间轴（同步版本，非阻塞，锁被占用时返回空列表）
+    pub fn try_get_current_timeline(&self) -> Vec<(u64, String)> {
+        let song_info = match self.state.try_read().ok().and_then(|state| state.current_song.clone()) {
+            Some(song) => song,
+            None => return Vec::new(),
+        };
+
+        self.parsed_lyrics_cache.try_read().ok()
+            .and_then(|cache| cache.get(&song_info).map(|timeline| timeline.to_vec()))
+            .unwrap_or_default()
+    }
+
+    /// 获取指定歌曲已解析时间轴的诊断信息 (歌词行数, 最后一行时间戳)
+    ///
+    /// 用于排查"歌词只显示到一半"之类的问题：可以看出歌词文件的时间戳覆盖到哪里
+    pub async fn timeline_info(&self, song_info: &SongInfo) -> Option<(usize, Duration)> {
+        let cache = self.parsed_lyrics_cache.read().await;
+        let timeline = cache.get(song_info)?;
+        let span = timeline.last().map(|(time, _)| Duration::from_millis(*time)).unwrap_or(Duration::ZERO);
+        Some((timeline.len(), span))
+    }
+
     /// 检查是否正在加载
     pub async fn is_loading(&self) -> bool {
         self.state.read().await.is_loading
@@ -463,20 +1142,108 @@ impl LyricsManager {
         };
         
         if let Some(song_info) = song_info {
-            // 清除缓存
+            // 清除缓存（包括"没有歌词"的标记）
             self.parsed_lyrics_cache.write().await.remove(&song_info);
+            if let Err(e) = self.lyrics_service.forget_not_found(&song_info).await {
+                warn!("删除没有歌词的标记失败: {}", e);
+            }
             // 重新加载
             self.load_lyrics_for_song(song_info).await;
         }
     }
 
-    /// 预加载歌词
-    pub async fn preload_lyrics(&self, songs: Vec<SongInfo>) {
-        for song_info in songs {
-            if let Err(e) = self.lyrics_service.search_and_get_lyrics(&song_info).await {
-                debug!("预加载歌词失败: {} - {}", song_info, e);
+    /// 启用或停用网络歌词源，有变化时重新加载当前歌曲的歌词
+    pub async fn set_network_sources(&self, netease: bool, qqmusic: bool) {
+        let mut changed = false;
+        for (source, enabled) in [(LyricsSource::NetEase, netease), (LyricsSource::QQMusic, qqmusic)] {
+            if self.lyrics_service.is_source_enabled(&source) != enabled {
+                self.lyrics_service.set_source_enabled(source, enabled);
+                changed = true;
             }
         }
+        if changed {
+            self.refresh_lyrics().await;
+        }
+    }
+
+    /// 修改时间轴整体偏移，有变化时丢弃按旧偏移解析的时间轴并重新加载当前歌曲的歌词
+    pub async fn set_lyrics_offset(&self, offset_ms: i64) {
+        if self.lyrics_offset_ms.swap(offset_ms, Ordering::Relaxed) != offset_ms {
+            info!("歌词偏移: {} 毫秒", offset_ms);
+            self.parsed_lyrics_cache.write().await.clear();
+            self.refresh_lyrics().await;
+        }
+    }
+
+    /// 切换到下一个网络歌词源并重新加载当前歌曲的歌词
+    pub async fn switch_source(&self) -> Option<LyricsSource> {
+        let source = self.lyrics_service.switch_to_next_source()?;
+        info!("切换歌词源: {}", source.display_name());
+        self.refresh_lyrics().await;
+        Some(source)
+    }
+
+    /// 按指定的歌词源和歌曲ID为当前歌曲加载一次歌词（不记住该匹配）
+    ///
+    /// 与自动加载一样发送 `LoadingStarted` / `LoadingCompleted` 事件，并按当前播放位置立即更新歌词行
+    pub async fn load_lyrics_by_id(&self, lyrics_override: LyricsOverride) {
+        let current_song = self.state.read().await.current_song.clone();
+        let Some(song_info) = current_song else {
+            debug!("当前没有歌曲，忽略手动加载歌词");
+            return;
+        };
+
+        self.parsed_lyrics_cache.write().await.remove(&song_info);
+        self.load_lyrics_with(song_info, None, Some(lyrics_override)).await;
+    }
+
+    /// 记住歌曲的手动匹配；若正是当前歌曲则立即按新匹配重新加载
+    pub async fn set_lyrics_override(&self, song_info: &SongInfo, lyrics_override: LyricsOverride) -> LyricsResult<()> {
+        self.lyrics_service.set_override(song_info, lyrics_override).await?;
+        self.reload_if_current(song_info).await;
+        Ok(())
+    }
+
+    /// 移除歌曲的手动匹配；若正是当前歌曲则立即重新搜索
+    pub async fn remove_lyrics_override(&self, song_info: &SongInfo) -> LyricsResult<bool> {
+        let removed = self.lyrics_service.remove_override(song_info).await?;
+        if removed {
+            self.reload_if_current(song_info).await;
+        }
+        Ok(removed)
+    }
+
+    /// 清空所有手动匹配（已加载的歌词保持不变）
+    pub fn clear_lyrics_overrides(&self) -> LyricsResult<()> {
+        self.lyrics_service.clear_overrides()
+    }
+
+    /// 指定歌曲正在显示时强制重新加载
+    async fn reload_if_current(&self, song_info: &SongInfo) {
+        let is_current = self.state.read().await.current_song.as_ref() == Some(song_info);
+        if is_current {
+            self.refresh_lyrics().await;
+        }
+    }
+
+    /// 预加载歌词（有限并发，由歌词服务配置控制）
+    pub async fn preload_lyrics(&self, songs: Vec<SongInfo>) {
+        self.lyrics_service.preload_batch(songs).await;
+    }
+    
+    /// 获取歌词服务（用于运行时调整超时、重试等设置）
+    pub fn lyrics_service(&self) -> &LyricsService {
+        &self.lyrics_service
+    }
+
+    /// 获取歌词行匹配使用的时间轴来源
+    pub fn timeline_source(&self) -> TimelineSource {
+        self.config.timeline_source
+    }
+
+    /// 预热歌词源连接（建议启动后在后台调用一次）
+    pub async fn warm_connection(&self) {
+        self.lyrics_service.warm_connection().await;
     }
     
     /// 清理过期的内存缓存
@@ -491,8 +1258,8 @@ impl LyricsManager {
             let mut cache = self.parsed_lyrics_cache.write().await;
             let cache_size_before = cache.len();
             
-            // 如果缓存太大，保留最近的100首歌的缓存
-            if cache_size_before > 100 {
+            // 如果缓存超出上限（正常情况下插入时已淘汰），清空重新开始
+            if cache_size_before > self.config.max_cached_timelines {
                 // 清空所有缓存，重新开始
                 cache.clear();
                 info!("清理内存歌词缓存，清理前: {} 项", cache_size_before);
@@ -510,3 +1277,541 @@ impl LyricsManager {
         (cache_size, last_cleanup)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::{CacheConfig, LyricsServiceBuilder, LyricsSource};
+    use tempfile::TempDir;
+
+    fn create_test_manager() -> (LyricsManager, TempDir) {
+        create_test_manager_with_config(LyricsManagerConfig::default())
+    }
+
+    fn create_test_manager_with_config(config: LyricsManagerConfig) -> (LyricsManager, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let service = LyricsServiceBuilder::new()
+            .with_cache_config(CacheConfig {
+                cache_dir: temp_dir.path().to_path_buf(),
+                ..Default::default()
+            })
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .build()
+            .unwrap();
+        let (manager, _event_receiver) = LyricsManager::with_config(service, config);
+        (manager, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_translated_only_lyrics_are_matched() {
+        let (manager, _temp_dir) = create_test_manager();
+        let lyrics = LyricsData {
+            translated: Some("[00:01.00]first line\n[00:05.00]second line".to_string()),
+            has_lyrics: true,
+            ..Default::default()
+        };
+
+        let parsed = lyrics.timeline_lines(TimelineSource::Lrc);
+        assert_eq!(
+            manager.find_current_entry(&parsed, Duration::from_secs(6)),
+            Some((5000, Some("second line".to_string())))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lyrics_offset_shifts_timeline() {
+        let config = LyricsManagerConfig { lyrics_offset_ms: -1500, ..Default::default() };
+        let (manager, _temp_dir) = create_test_manager_with_config(config);
+        let lyrics = LyricsData {
+            original: Some("[00:01.00]first\n[00:05.00]second".to_string()),
+            has_lyrics: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            manager.parse_timeline(&lyrics),
+            vec![(0, "first".to_string()), (3500, "second".to_string())]
+        );
+
+        manager.set_lyrics_offset(500).await;
+        assert_eq!(
+            manager.parse_timeline(&lyrics),
+            vec![(1500, "first".to_string()), (5500, "second".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeline_info_reports_count_and_span() {
+        let (manager, _temp_dir) = create_test_manager();
+        let song_info = SongInfo::new("title", "artist");
+        assert_eq!(manager.timeline_info(&song_info).await, None);
+
+        let parsed = LyricsData::parse_lrc_lines("[00:01.00]first\n[01:30.50]last\n[00:45.00]middle");
+        manager.parsed_lyrics_cache.write().await.insert(song_info.clone(), parsed.into());
+
+        assert_eq!(
+            manager.timeline_info(&song_info).await,
+            Some((3, Duration::from_millis(90_500)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_keeps_lyrics_when_clear_on_stop_disabled() {
+        let config = LyricsManagerConfig { clear_on_stop: false, ..Default::default() };
+        let (manager, _temp_dir) = create_test_manager_with_config(config);
+
+        {
+            let mut state = manager.state.write().await;
+            state.current_song = Some(SongInfo::new("title", "artist"));
+            state.current_line = Some("last line".to_string());
+        }
+
+        manager.handle_media_event(MediaEvent::Stopped).await;
+
+        let state = manager.get_current_state().await;
+        assert!(state.is_stopped);
+        assert_eq!(state.current_line, Some("last line".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_stop_clears_lyrics_by_default() {
+        let (manager, _temp_dir) = create_test_manager();
+        manager.state.write().await.current_line = Some("last line".to_string());
+
+        manager.handle_media_event(MediaEvent::Stopped).await;
+
+        let state = manager.get_current_state().await;
+        assert!(!state.is_stopped);
+        assert_eq!(state.current_line, None);
+    }
+
+    #[test]
+    fn test_timeline_cache_evicts_oldest_loaded() {
+        let mut cache = TimelineCache::new(2);
+        let first = SongInfo::new("first", "artist");
+        let second = SongInfo::new("second", "artist");
+        let third = SongInfo::new("third", "artist");
+
+        cache.insert(first.clone(), vec![(0, "a".to_string())].into());
+        cache.insert(second.clone(), vec![(0, "b".to_string())].into());
+        // 重新加载会刷新顺序
+        cache.insert(first.clone(), vec![(0, "a".to_string())].into());
+        cache.insert(third.clone(), vec![(0, "c".to_string())].into());
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&first).is_some());
+        assert!(cache.get(&second).is_none());
+        assert!(cache.get(&third).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_song_change_debounce_ignores_one_frame_flip() {
+        let config = LyricsManagerConfig { song_change_debounce: 2, ..Default::default() };
+        let (manager, _temp_dir) = create_test_manager_with_config(config);
+
+        let current = SongInfo::new("current", "artist");
+        let next = SongInfo::new("next", "artist");
+
+        // 当前歌曲保持不变
+        assert!(manager.confirm_song_change(&current, false).await);
+        // 下一首标题只闪现一帧，随后恢复
+        assert!(!manager.confirm_song_change(&next, true).await);
+        assert!(manager.confirm_song_change(&current, false).await);
+        // 计数已被重置，再次闪现仍不确认
+        assert!(!manager.confirm_song_change(&next, true).await);
+        // 连续两次出现才确认切歌
+        assert!(manager.confirm_song_change(&next, true).await);
+    }
+
+    #[test]
+    fn test_song_info_for_media_artist_field() {
+        let media_info = MediaInfo {
+            title: "Symphony No. 5".to_string(),
+            artist: "Berliner Philharmoniker".to_string(),
+            album_artist: "Beethoven".to_string(),
+            ..Default::default()
+        };
+        let by_artist = SongInfo::new("Symphony No. 5", "Berliner Philharmoniker");
+        let by_album_artist = SongInfo::new("Symphony No. 5", "Beethoven");
+
+        for (artist_field, expected) in [
+            (ArtistField::Artist, (by_artist.clone(), None)),
+            (ArtistField::AlbumArtist, (by_album_artist.clone(), None)),
+            (ArtistField::Both, (by_artist.clone(), Some(by_album_artist.clone()))),
+        ] {
+            let config = LyricsManagerConfig { artist_field, ..Default::default() };
+            let (manager, _temp_dir) = create_test_manager_with_config(config);
+            assert_eq!(manager.song_info_for_media(&media_info), expected);
+        }
+
+        // 专辑艺术家为空时回退到艺术家
+        let config = LyricsManagerConfig { artist_field: ArtistField::AlbumArtist, ..Default::default() };
+        let (manager, _temp_dir) = create_test_manager_with_config(config);
+        let media_info = MediaInfo { album_artist: String::new(), ..media_info };
+        assert_eq!(manager.song_info_for_media(&media_info), (by_artist, None));
+    }
+
+    #[tokio::test]
+    async fn test_reset_clears_state_and_timeline_cache() {
+        let (manager, _temp_dir) = create_test_manager();
+        let song_info = SongInfo::new("title", "artist");
+        manager.parsed_lyrics_cache.write().await.insert(song_info.clone(), vec![(0, "line".to_string())].into());
+        manager.state.write().await.current_song = Some(song_info.clone());
+
+        manager.reset().await;
+
+        assert_eq!(manager.timeline_info(&song_info).await, None);
+        assert_eq!(manager.get_current_state().await.current_song, None);
+    }
+
+    #[tokio::test]
+    async fn test_manual_load_keeps_position_of_current_song() {
+        let (manager, _temp_dir) = create_test_manager();
+        let mut events = manager.subscribe();
+        {
+            let mut state = manager.state.write().await;
+            state.current_song = Some(SongInfo::new("title", "artist"));
+            state.current_position = Duration::from_secs(42);
+        }
+
+        // 未知来源无法加载，但不应把播放位置重置为零
+        manager.load_lyrics_by_id(LyricsOverride {
+            source: LyricsSource::Unknown,
+            song_id: "1".to_string(),
+            song_mid: String::new(),
+        }).await;
+
+        let state = manager.get_current_state().await;
+        assert_eq!(state.current_position, Duration::from_secs(42));
+        assert!(!state.is_loading);
+        assert!(matches!(*events.borrow_and_update(), LyricsEvent::LoadingFailed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_min_line_interval_holds_fast_lines() {
+        let (manager, _temp_dir) = create_test_manager_with_config(LyricsManagerConfig {
+            min_line_interval: Duration::from_secs(2),
+            ..Default::default()
+        });
+        let lyrics = LyricsData {
+            original: Some("[00:01.00]one\n[00:01.50]two\n[00:10.00]three".to_string()),
+            has_lyrics: true,
+            ..Default::default()
+        };
+
+        let first = manager.current_line_for(&lyrics, Duration::from_millis(1200), &None).await;
+        assert_eq!(first.as_deref(), Some("one"));
+
+        // 下一行只隔半秒，尚未达到最短间隔，继续显示上一行
+        let held = manager.current_line_for(&lyrics, Duration::from_millis(1600), &first).await;
+        assert_eq!(held.as_deref(), Some("one"));
+
+        // 播放位置远超新行开始时间（例如拖动进度）时立即切换
+        let jumped = manager.current_line_for(&lyrics, Duration::from_secs(13), &held).await;
+        assert_eq!(jumped.as_deref(), Some("three"));
+    }
+
+    #[tokio::test]
+    async fn test_embedded_lyrics_skip_online_search() {
+        let (manager, _temp_dir) = create_test_manager_with_config(LyricsManagerConfig {
+            prefer_embedded_lyrics: true,
+            ..Default::default()
+        });
+        let media_info = MediaInfo {
+            title: "title".to_string(),
+            artist: "artist".to_string(),
+            embedded_lyrics: Some("[00:01.00]one\n[00:03.00]two".to_string()),
+            position: Some(Duration::from_secs(2)),
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        };
+
+        manager.handle_media_event(MediaEvent::InfoUpdated(media_info)).await;
+
+        let state = manager.get_current_state().await;
+        assert!(!state.is_loading);
+        assert_eq!(state.current_lyrics.map(|lyrics| lyrics.source), Some(LyricsSource::Embedded));
+        assert_eq!(state.current_line.as_deref(), Some("one"));
+        assert_eq!(*state.timeline, vec![(1000, "one".to_string()), (3000, "two".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_seek_to_updates_current_line() {
+        let (manager, _temp_dir) = create_test_manager();
+        let mut events = manager.subscribe();
+        let song_info = SongInfo::new("title", "artist");
+        manager.parsed_lyrics_cache.write().await.insert(
+            song_info.clone(),
+            vec![(1000, "first line".to_string()), (5000, "second line".to_string())].into(),
+        );
+        manager.state.write().await.current_song = Some(song_info);
+
+        manager.seek_to(Duration::from_secs(6)).await;
+        assert_eq!(manager.get_current_line().await.as_deref(), Some("second line"));
+        assert!(matches!(
+            &*events.borrow_and_update(),
+            LyricsEvent::CurrentLineUpdated { line: Some(line), position } if line == "second line" && *position == Duration::from_secs(6)
+        ));
+
+        manager.seek_to(Duration::from_secs(2)).await;
+        assert_eq!(manager.get_current_line().await.as_deref(), Some("first line"));
+        assert_eq!(manager.get_current_state().await.current_position, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_media_filter_classifies_non_music() {
+        let media = |app_name: &str, title: &str| MediaInfo {
+            app_name: app_name.to_string(),
+            title: title.to_string(),
+            artist: "artist".to_string(),
+            ..Default::default()
+        };
+        let filter = MediaFilter::default();
+
+        assert!(filter.is_music(&media("Spotify.exe", "晴天")));
+        assert!(!filter.is_music(&media("Microsoft.ZuneVideo_8wekyb3d8bbwe!Microsoft.ZuneVideo", "Movie")));
+        assert!(!filter.is_music(&media("chrome", "某节目 第12期")));
+        assert!(!filter.is_music(&media("chrome", "Tech Talk EP42")));
+        assert!(!filter.is_music(&MediaInfo { is_video: true, ..media("chrome", "Clip") }));
+
+        let allow_only_spotify = MediaFilter {
+            allowed_apps: vec!["spotify".to_string()],
+            ..MediaFilter::permissive()
+        };
+        assert!(allow_only_spotify.is_music(&media("Spotify.exe", "Song")));
+        assert!(!allow_only_spotify.is_music(&media("chrome", "Song")));
+        assert!(MediaFilter::permissive().is_music(&MediaInfo { is_video: true, ..media("chrome", "EP1") }));
+    }
+
+    #[tokio::test]
+    async fn test_next_line_text_skips_empty_lines() {
+        let (manager, _temp_dir) = create_test_manager();
+        let song_info = SongInfo::new("title", "artist");
+        let parsed = LyricsData::parse_lrc_lines("[00:01.00]first\n[00:03.00]\n[00:05.00]third");
+        manager.parsed_lyrics_cache.write().await.insert(song_info.clone(), parsed.into());
+        assert_eq!(manager.get_next_line_text(Duration::from_secs(2)).await, None);
+
+        manager.state.write().await.current_song = Some(song_info);
+        assert_eq!(manager.get_next_line_text(Duration::from_secs(2)).await.as_deref(), Some("third"));
+        assert_eq!(manager.get_next_line_text(Duration::from_secs(6)).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_zero_min_line_interval_follows_timeline() {
+        let (manager, _temp_dir) = create_test_manager();
+        let lyrics = LyricsData {
+            original: Some("[00:01.00]one\n[00:01.50]two".to_string()),
+            has_lyrics: true,
+            ..Default::default()
+        };
+
+        let first = manager.current_line_for(&lyrics, Duration::from_millis(1200), &None).await;
+        let next = manager.current_line_for(&lyrics, Duration::from_millis(1600), &first).await;
+        assert_eq!(next.as_deref(), Some("two"));
+    }
+
+    #[tokio::test]
+    async fn test_line_matching_at_three_hour_position() {
+        let (manager, _temp_dir) = create_test_manager();
+        let lyrics = LyricsData {
+            original: Some("[179:59.99]before\n[3:00:00.00]three hours\n[3:00:00.01]next".to_string()),
+            has_lyrics: true,
+            ..Default::default()
+        };
+        let three_hours = Duration::from_secs(3 * 3600);
+
+        let line = manager.current_line_for(&lyrics, three_hours - Duration::from_millis(1), &None).await;
+        assert_eq!(line.as_deref(), Some("before"));
+        let line = manager.current_line_for(&lyrics, three_hours, &line).await;
+        assert_eq!(line.as_deref(), Some("three hours"));
+        let line = manager.current_line_for(&lyrics, three_hours + Duration::from_millis(10), &line).await;
+        assert_eq!(line.as_deref(), Some("next"));
+    }
+
+    #[tokio::test]
+    async fn test_before_first_line_skips_leading_empty_lines() {
+        let (manager, _temp_dir) = create_test_manager();
+        let lyrics = LyricsData {
+            original: Some("[00:00.00]\n[00:12.00]first\n[00:15.00]second".to_string()),
+            has_lyrics: true,
+            ..Default::default()
+        };
+
+        assert!(manager.is_before_first_line(&lyrics, Duration::from_secs(5)));
+        assert!(!manager.is_before_first_line(&lyrics, Duration::from_secs(12)));
+        assert!(!manager.is_before_first_line(&LyricsData::default(), Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_locate_line_in_one_pass() {
+        let (manager, _temp_dir) = create_test_manager();
+        let timeline = LyricsData::parse_lrc_lines("[00:00.00]\n[00:02.00]first\n[00:04.00]\n[00:06.00]third");
+
+        let intro = manager.locate_line(&timeline, Duration::from_secs(1), &None, None).await;
+        assert_eq!(intro, LineLocation {
+            line: None,
+            index: None,
+            next_line: Some("first".to_string()),
+            before_first_line: true,
+        });
+
+        let location = manager.locate_line(&timeline, Duration::from_secs(3), &intro.line, None).await;
+        assert_eq!(location.line.as_deref(), Some("first"));
+        assert_eq!(location.next_line.as_deref(), Some("third"));
+        assert!(!location.before_first_line);
+
+        let last = manager.locate_displayed(&timeline, Duration::from_secs(7), Some("third".to_string()), None);
+        assert_eq!(last.line.as_deref(), Some("third"));
+        assert_eq!(last.next_line, None);
+        assert_eq!(manager.locate_line(&[], Duration::ZERO, &None, None).await, LineLocation::default());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_line_retrigger_follows_mode() {
+        let lyrics = LyricsData {
+            original: Some("[00:01.00]chorus\n[00:03.00]chorus\n[00:05.00]verse".to_string()),
+            has_lyrics: true,
+            ..Default::default()
+        };
+        let chorus = Some("chorus".to_string());
+
+        let (merging, _temp_dir) = create_test_manager();
+        assert_eq!(merging.current_line_index_for(&lyrics, Duration::from_secs(4), &chorus, Some(0)), None);
+
+        let (retriggering, _temp_dir) = create_test_manager_with_config(LyricsManagerConfig {
+            repeated_lines: RepeatedLines::Retrigger,
+            ..Default::default()
+        });
+        assert_eq!(retriggering.current_line_index_for(&lyrics, Duration::from_secs(2), &chorus, None), Some(0));
+        assert_eq!(retriggering.current_line_index_for(&lyrics, Duration::from_secs(4), &chorus, Some(0)), Some(1));
+        // 显示行被保持时索引不变
+        assert_eq!(retriggering.current_line_index_for(&lyrics, Duration::from_secs(6), &chorus, Some(1)), Some(1));
+
+        assert!(RepeatedLines::Auto.retriggers(TimelineSource::Yrc));
+        assert!(!RepeatedLines::Auto.retriggers(TimelineSource::Lrc));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_status_defers_load_until_playing() {
+        let (manager, _temp_dir) = create_test_manager_with_config(LyricsManagerConfig {
+            prefer_embedded_lyrics: true,
+            unknown_status_grace: Duration::from_secs(60),
+            ..Default::default()
+        });
+        let media_info = MediaInfo {
+            title: "title".to_string(),
+            artist: "artist".to_string(),
+            embedded_lyrics: Some("[00:01.00]one".to_string()),
+            playback_status: PlaybackStatus::Unknown,
+            ..Default::default()
+        };
+
+        // 播放器启动时上报 Unknown：暂不加载
+        manager.handle_media_event(MediaEvent::InfoUpdated(media_info.clone())).await;
+        assert_eq!(manager.get_current_state().await.current_song, None);
+
+        // 状态变为 Playing 后立即加载
+        let playing = MediaInfo { playback_status: PlaybackStatus::Playing, ..media_info.clone() };
+        manager.handle_media_event(MediaEvent::InfoUpdated(playing)).await;
+        assert!(manager.get_current_lyrics().await.is_some());
+
+        // 宽限期为 0 时不推迟
+        let (eager, _temp_dir) = create_test_manager_with_config(LyricsManagerConfig {
+            prefer_embedded_lyrics: true,
+            unknown_status_grace: Duration::ZERO,
+            ..Default::default()
+        });
+        eager.handle_media_event(MediaEvent::InfoUpdated(media_info)).await;
+        assert!(eager.get_current_lyrics().await.is_some());
+    }
+
+    /// 回放输入：媒体信息（标题, 播放位置毫秒）、播放位置更新、停止
+    enum Replay {
+        Media(&'static str, u64),
+        Position(u64),
+        Stopped,
+    }
+
+    /// 每一步之后期望的最新歌词事件
+    enum Expect {
+        Loaded(&'static str),
+        Line(&'static str),
+        NoEvent,
+        Cleared,
+    }
+
+    /// 端到端回放：媒体/播放事件 → 歌词管理器 → 歌词事件和状态（歌词来自离线歌词包，不访问网络）
+    #[tokio::test]
+    async fn test_replay_pipeline_fixture() {
+        use crate::lyrics::PackProvider;
+        use Expect::*;
+        use Replay::*;
+
+        let pack: &[(&str, &str)] = &[
+            ("Artist - First.lrc", "[00:01.00]first one\n[00:05.00]first two"),
+            ("Artist - Second.lrc", "[00:02.00]second one"),
+        ];
+        let fixture: &[(Replay, Expect, Option<&str>)] = &[
+            (Media("First", 500), Loaded("First"), None),
+            (Position(1500), Line("first one"), Some("first one")),
+            (Position(6000), Line("first two"), Some("first two")),
+            // 同一行不重复发送事件
+            (Position(6500), NoEvent, Some("first two")),
+            // 切歌后从新歌词的当前位置开始
+            (Media("Second", 2500), Line("second one"), Some("second one")),
+            (Stopped, Cleared, None),
+        ];
+
+        let temp_dir = TempDir::new().unwrap();
+        let pack_dir = temp_dir.path().join("pack");
+        std::fs::create_dir_all(&pack_dir).unwrap();
+        for (file, content) in pack {
+            std::fs::write(pack_dir.join(file), content).unwrap();
+        }
+        PackProvider::build_index(&pack_dir).unwrap();
+
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .with_lyrics_pack(&pack_dir)
+            .build()
+            .unwrap();
+        service.set_source_enabled(LyricsSource::NetEase, false);
+        service.set_source_enabled(LyricsSource::QQMusic, false);
+        let (manager, mut events) = LyricsManager::new(service);
+
+        for (step, (input, expect, line)) in fixture.iter().enumerate() {
+            match input {
+                Media(title, position_ms) => manager.handle_media_event(MediaEvent::InfoUpdated(MediaInfo {
+                    title: title.to_string(),
+                    artist: "Artist".to_string(),
+                    position: Some(Duration::from_millis(*position_ms)),
+                    playback_status: PlaybackStatus::Playing,
+                    ..Default::default()
+                })).await,
+                Position(position_ms) => manager.handle_playback_event(PlaybackEvent::PositionUpdate {
+                    position: Duration::from_millis(*position_ms),
+                }).await,
+                Stopped => manager.handle_media_event(MediaEvent::Stopped).await,
+            }
+
+            let changed = events.has_changed().unwrap();
+            let event = events.borrow_and_update().clone();
+            match (expect, event) {
+                (NoEvent, _) => assert!(!changed, "step {}: unexpected event", step),
+                (Loaded(title), LyricsEvent::LoadingCompleted { song_info, lyrics }) => {
+                    assert_eq!(song_info.title, *title, "step {}", step);
+                    assert_eq!(lyrics.source, LyricsSource::Pack, "step {}", step);
+                }
+                (Line(expected), LyricsEvent::CurrentLineUpdated { line, .. }) => {
+                    assert_eq!(line.as_deref(), Some(*expected), "step {}", step);
+                }
+                (Cleared, LyricsEvent::Cleared) => {}
+                (_, event) => panic!("step {}: unexpected event {:?}", step, event),
+            }
+
+            let state = manager.get_current_state().await;
+            assert_eq!(state.current_line.as_deref(), *line, "step {}", step);
+            assert!(!state.is_loading, "step {}", step);
+        }
+    }
+}
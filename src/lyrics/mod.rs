@@ -5,9 +5,13 @@ pub mod cache;
 pub mod service;
 pub mod errors;
 pub mod manager;
+pub mod overrides;
+pub mod pack;
 
 pub use data::*;
 pub use service::{LyricsService, LyricsServiceConfig, LyricsServiceBuilder};
 pub use cache::{CacheConfig, CacheStats};
+pub use overrides::{LyricsOverride, OverrideStore};
+pub use pack::PackProvider;
 pub use errors::*;
-pub use manager::{LyricsManager, LyricsEvent, LyricsState};
+pub use manager::{ArtistField, LyricsManager, LyricsManagerConfig, LyricsEvent, LyricsState, LineLocation, MediaFilter, RepeatedLines};
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::lyrics::{LyricsResult, LyricsError, LyricsSource, SongInfo};
+
+/// 手动指定的歌词匹配（歌词源 + 歌曲ID）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LyricsOverride {
+    /// 歌词来源
+    pub source: LyricsSource,
+    /// 歌曲ID（网易云为音乐ID，QQ音乐为 songid）
+    pub song_id: String,
+    /// QQ音乐的 songmid（网易云留空）
+    #[serde(default)]
+    pub song_mid: String,
+}
+
+impl LyricsOverride {
+    /// 指定网易云音乐的歌曲
+    pub fn netease(music_id: impl Into<String>) -> Self {
+        Self {
+            source: LyricsSource::NetEase,
+            song_id: music_id.into(),
+            song_mid: String::new(),
+        }
+    }
+
+    /// 指定QQ音乐的歌曲
+    pub fn qqmusic(song_id: impl Into<String>, song_mid: impl Into<String>) -> Self {
+        Self {
+            source: LyricsSource::QQMusic,
+            song_id: song_id.into(),
+            song_mid: song_mid.into(),
+        }
+    }
+}
+
+/// 持久化条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OverrideEntry {
+    song_info: SongInfo,
+    lyrics_override: LyricsOverride,
+    updated_at: DateTime<Utc>,
+}
+
+/// 歌词匹配覆盖存储（配置目录下的 JSON 文件，按歌曲记住手动选择的匹配）
+pub struct OverrideStore {
+    path: PathBuf,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, OverrideEntry>>,
+}
+
+impl OverrideStore {
+    /// 默认最多记住的歌曲数量，超出时淘汰最早设置的
+    pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+    /// 从指定文件加载（文件不存在或损坏时从空表开始）
+    pub fn load(path: impl Into<PathBuf>, max_entries: usize) -> Self {
+        let path = path.into();
+        let entries = Self::read_entries(&path).unwrap_or_else(|e| {
+            warn!("读取歌词匹配覆盖失败，将从空表开始: {}", e);
+            HashMap::new()
+        });
+        debug!("歌词匹配覆盖: {} 项 ({:?})", entries.len(), path);
+
+        Self {
+            path,
+            max_entries: max_entries.max(1),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// 默认存储路径
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .map(|mut path| {
+                path.push("taskbar_lrc");
+                path.push("lyrics_overrides.json");
+                path
+            })
+            .unwrap_or_else(|| PathBuf::from("config/lyrics_overrides.json"))
+    }
+
+    /// 获取歌曲的匹配覆盖
+    pub fn get(&self, song_info: &SongInfo) -> Option<LyricsOverride> {
+        let entries = self.entries.lock().ok()?;
+        entries.get(&song_info.cache_key()).map(|entry| entry.lyrics_override.clone())
+    }
+
+    /// 记住歌曲的匹配覆盖并立即写入磁盘
+    pub fn set(&self, song_info: &SongInfo, lyrics_override: LyricsOverride) -> LyricsResult<()> {
+        let mut entries = self.lock_entries()?;
+        entries.insert(song_info.cache_key(), OverrideEntry {
+            song_info: song_info.clone(),
+            lyrics_override,
+            updated_at: Utc::now(),
+        });
+
+        // 超出上限时淘汰最早设置的条目
+        while entries.len() > self.max_entries {
+            let Some(oldest_key) = entries.iter()
+                .min_by_key(|(_, entry)| entry.updated_at)
+                .map(|(key, _)| key.clone()) else {
+                break;
+            };
+            entries.remove(&oldest_key);
+        }
+
+        self.write_entries(&entries)
+    }
+
+    /// 移除歌曲的匹配覆盖，返回是否存在
+    pub fn remove(&self, song_info: &SongInfo) -> LyricsResult<bool> {
+        let mut entries = self.lock_entries()?;
+        if entries.remove(&song_info.cache_key()).is_none() {
+            return Ok(false);
+        }
+        self.write_entries(&entries)?;
+        Ok(true)
+    }
+
+    /// 清空所有匹配覆盖
+    pub fn clear(&self) -> LyricsResult<()> {
+        let mut entries = self.lock_entries()?;
+        entries.clear();
+        self.write_entries(&entries)
+    }
+
+    /// 已记住的歌曲数量
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|entries| entries.len()).unwrap_or(0)
+    }
+
+    /// 是否没有任何匹配覆盖
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock_entries(&self) -> LyricsResult<std::sync::MutexGuard<'_, HashMap<String, OverrideEntry>>> {
+        self.entries.lock()
+            .map_err(|_| LyricsError::CacheError("歌词匹配覆盖锁已损坏".to_string()))
+    }
+
+    fn read_entries(path: &Path) -> LyricsResult<HashMap<String, OverrideEntry>> {
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| LyricsError::CacheError(format!("读取歌词匹配覆盖文件失败: {}", e)))?;
+        let list: Vec<OverrideEntry> = serde_json::from_str(&content)?;
+        Ok(list.into_iter()
+            .map(|entry| (entry.song_info.cache_key(), entry))
+            .collect())
+    }
+
+    fn write_entries(&self, entries: &HashMap<String, OverrideEntry>) -> LyricsResult<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| LyricsError::CacheError(format!("创建配置目录失败: {}", e)))?;
+        }
+
+        // 按设置时间排序写出，便于手动查看
+        let mut list: Vec<&OverrideEntry> = entries.values().collect();
+        list.sort_by_key(|entry| entry.updated_at);
+        let content = serde_json::to_string_pretty(&list)?;
+
+        fs::write(&self.path, content)
+            .map_err(|e| LyricsError::CacheError(format!("写入歌词匹配覆盖文件失败: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_override_persists_across_loads() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("overrides.json");
+        let song = SongInfo::new("晴天", "周杰伦");
+
+        let store = OverrideStore::load(&path, OverrideStore::DEFAULT_MAX_ENTRIES);
+        assert!(store.get(&song).is_none());
+        store.set(&song, LyricsOverride::netease("186016")).unwrap();
+
+        // 标题大小写/空白不同也视为同一首歌
+        let reloaded = OverrideStore::load(&path, OverrideStore::DEFAULT_MAX_ENTRIES);
+        let same_song = SongInfo::new(" 晴天 ", "周杰伦");
+        assert_eq!(reloaded.get(&same_song), Some(LyricsOverride::netease("186016")));
+
+        assert!(reloaded.remove(&song).unwrap());
+        assert!(!reloaded.remove(&song).unwrap());
+        assert!(OverrideStore::load(&path, OverrideStore::DEFAULT_MAX_ENTRIES).is_empty());
+    }
+
+    #[test]
+    fn test_override_store_evicts_oldest_and_clears() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = OverrideStore::load(temp_dir.path().join("overrides.json"), 2);
+        let songs: Vec<SongInfo> = (0..3)
+            .map(|i| SongInfo::new(format!("Song {}", i), "Artist"))
+            .collect();
+
+        for (i, song) in songs.iter().enumerate() {
+            store.set(song, LyricsOverride::qqmusic(i.to_string(), "")).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&songs[0]).is_none());
+        assert!(store.get(&songs[2]).is_some());
+
+        store.clear().unwrap();
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::lyrics::{LyricsResult, LyricsError, LyricsData, LyricsSource, SongInfo};
+
+/// 歌词包索引条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PackEntry {
+    song_info: SongInfo,
+    /// 相对歌词包目录的 `.lrc` 文件路径
+    file: PathBuf,
+}
+
+/// 离线歌词包（只读）
+///
+/// 歌词包是一个包含 `.lrc` 文件的目录，`index.json` 按 `SongInfo::cache_key` 索引到具体文件。
+/// 歌曲信息取自 `[ti:]` / `[ar:]` 标签，缺失时按 `艺术家 - 标题.lrc` 文件名解析。
+pub struct PackProvider {
+    root: PathBuf,
+    entries: HashMap<String, PackEntry>,
+}
+
+impl PackProvider {
+    /// 索引文件名
+    pub const INDEX_FILE: &'static str = "index.json";
+
+    /// 打开歌词包：有索引时直接加载，否则扫描目录建立索引（不写入磁盘）
+    pub fn open(root: impl Into<PathBuf>) -> LyricsResult<Self> {
+        let root = root.into();
+        let index_path = root.join(Self::INDEX_FILE);
+
+        let entries = if index_path.exists() {
+            let content = fs::read_to_string(&index_path)
+                .map_err(|e| LyricsError::CacheError(format!("读取歌词包索引失败: {}", e)))?;
+            let list: Vec<PackEntry> = serde_json::from_str(&content)?;
+            Self::index_entries(list)
+        } else {
+            warn!("歌词包缺少索引，扫描目录: {:?}", root);
+            Self::index_entries(Self::scan(&root)?)
+        };
+
+        info!("加载歌词包: {} 首 ({:?})", entries.len(), root);
+        Ok(Self { root, entries })
+    }
+
+    /// 扫描歌词包目录并写入 `index.json`，返回收录的歌曲数量
+    pub fn build_index(root: impl AsRef<Path>) -> LyricsResult<usize> {
+        let root = root.as_ref();
+        let list = Self::scan(root)?;
+        let content = serde_json::to_string_pretty(&list)?;
+
+        fs::write(root.join(Self::INDEX_FILE), content)
+            .map_err(|e| LyricsError::CacheError(format!("写入歌词包索引失败: {}", e)))?;
+
+        info!("歌词包索引已生成: {} 首", list.len());
+        Ok(list.len())
+    }
+
+    /// 获取歌曲的歌词（不在歌词包中或文件无法读取时为 None）
+    pub fn get(&self, song_info: &SongInfo) -> Option<LyricsData> {
+        let entry = self.entries.get(&song_info.cache_key())?;
+        let content = match fs::read_to_string(self.root.join(&entry.file)) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("读取歌词包文件失败: {:?} - {}", entry.file, e);
+                return None;
+            }
+        };
+
+        let mut lyrics = LyricsData {
+            original: Some(content),
+            has_lyrics: true,
+            source: LyricsSource::Pack,
+            fetched_at: Utc::now(),
+            ..Default::default()
+        };
+        lyrics.is_synced = lyrics.has_timestamps();
+        Some(lyrics).filter(LyricsData::has_any_content)
+    }
+
+    /// 歌词包收录的歌曲数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 歌词包是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn index_entries(list: Vec<PackEntry>) -> HashMap<String, PackEntry> {
+        list.into_iter()
+            .map(|entry| (entry.song_info.cache_key(), entry))
+            .collect()
+    }
+
+    /// 递归扫描目录下的所有 `.lrc` 文件
+    fn scan(root: &Path) -> LyricsResult<Vec<PackEntry>> {
+        let mut entries = Vec::new();
+        let mut pending = vec![root.to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            let read_dir = fs::read_dir(&dir)
+                .map_err(|e| LyricsError::CacheError(format!("读取歌词包目录失败: {}", e)))?;
+
+            for path in read_dir.flatten().map(|entry| entry.path()) {
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("lrc")) {
+                    continue;
+                }
+
+                let Some(song_info) = Self::song_info_for_file(&path) else {
+                    debug!("无法识别歌词文件对应的歌曲: {:?}", path);
+                    continue;
+                };
+                let Ok(file) = path.strip_prefix(root).map(Path::to_path_buf) else {
+                    continue;
+                };
+                entries.push(PackEntry { song_info, file });
+            }
+        }
+
+        entries.sort_by(|a, b| a.file.cmp(&b.file));
+        Ok(entries)
+    }
+
+    /// 从标签或文件名识别歌曲信息
+    fn song_info_for_file(path: &Path) -> Option<SongInfo> {
+        let content = fs::read_to_string(path).ok()?;
+        let tag = |name: &str| content.lines()
+            .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']')?.split_once(':'))
+            .find(|(key, value)| key.trim().eq_ignore_ascii_case(name) && !value.trim().is_empty())
+            .map(|(_, value)| value.trim().to_string());
+
+        let stem = path.file_stem()?.to_string_lossy();
+        let (file_artist, file_title) = stem.split_once(" - ")
+            .map(|(artist, title)| (Some(artist.trim().to_string()), Some(title.trim().to_string())))
+            .unwrap_or((None, None));
+
+        let song_info = SongInfo::new(tag("ti").or(file_title)?, tag("ar").or(file_artist)?);
+        Some(song_info).filter(SongInfo::is_valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pack_index_build_and_lookup() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("jay")).unwrap();
+        fs::write(root.join("jay/周杰伦 - 晴天.lrc"), "[00:01.00]故事的小黄花").unwrap();
+        fs::write(root.join("tagged.lrc"), "[ti:Song]\n[ar:Artist]\n[00:02.00]line").unwrap();
+        fs::write(root.join("unknown.lrc"), "[00:03.00]no tags").unwrap();
+        fs::write(root.join("notes.txt"), "not lyrics").unwrap();
+
+        assert_eq!(PackProvider::build_index(root).unwrap(), 2);
+
+        let pack = PackProvider::open(root).unwrap();
+        assert_eq!(pack.len(), 2);
+
+        let lyrics = pack.get(&SongInfo::new("晴天", "周杰伦")).unwrap();
+        assert_eq!(lyrics.source, LyricsSource::Pack);
+        assert!(lyrics.is_synced);
+        assert!(pack.get(&SongInfo::new("SONG", "artist")).is_some());
+        assert!(pack.get(&SongInfo::new("Missing", "Artist")).is_none());
+    }
+}
@@ -1,10 +1,19 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use chrono::Duration as ChronoDuration;
+use tokio::sync::Semaphore;
 use tracing::{debug, info, warn, error};
 
 use crate::lyrics::{
     LyricsResult, LyricsError, LyricsData, LyricsSource, SongInfo,
     http_client::{HttpClient, HttpClientConfig},
-    api::{NetEaseApi, QQMusicApi},
+    api::{NetEaseApi, QQMusicApi, LocalLrcSource, DEFAULT_SEARCH_LIMIT},
     cache::{LyricsCache, CacheConfig, CacheStats},
+    overrides::{LyricsOverride, OverrideStore},
+    pack::PackProvider,
 };
 
 /// 歌词服务配置
@@ -20,6 +29,25 @@ pub struct LyricsServiceConfig {
     pub enable_qqmusic: bool,
     /// 搜索超时时间（秒）
     pub search_timeout_secs: u64,
+    /// 批量预加载的最大并发数
+    pub max_concurrent_preloads: usize,
+    /// 启动时是否预热到各歌词源的连接
+    pub warm_up_connections: bool,
+    /// 手动匹配覆盖的存储文件
+    pub overrides_path: PathBuf,
+    /// 离线歌词包目录（在缓存和网络之前查找）
+    pub pack_dir: Option<PathBuf>,
+    /// 本地 `.lrc` 文件目录（在网络歌词源之前查找）
+    pub local_dir: Option<PathBuf>,
+    /// 网络歌词源的查询顺序（为空时使用默认顺序）
+    pub source_priority: Vec<LyricsSource>,
+    /// 每次搜索获取的候选数量（从中挑选与歌名、时长最接近的结果）
+    pub search_limit: usize,
+}
+
+impl LyricsServiceConfig {
+    /// 默认的歌词源查询顺序
+    pub const DEFAULT_SOURCE_PRIORITY: [LyricsSource; 2] = [LyricsSource::NetEase, LyricsSource::QQMusic];
 }
 
 impl Default for LyricsServiceConfig {
@@ -30,6 +58,13 @@ impl Default for LyricsServiceConfig {
             enable_netease: true,
             enable_qqmusic: true,
             search_timeout_secs: 30,
+            max_concurrent_preloads: 2,
+            warm_up_connections: true,
+            overrides_path: OverrideStore::default_path(),
+            pack_dir: None,
+            local_dir: None,
+            source_priority: Vec::new(),
+            search_limit: DEFAULT_SEARCH_LIMIT,
         }
     }
 }
@@ -37,41 +72,69 @@ impl Default for LyricsServiceConfig {
 /// 歌词服务
 pub struct LyricsService {
     config: LyricsServiceConfig,
-    netease_api: Option<NetEaseApi>,
-    qqmusic_api: Option<QQMusicApi>,
+    netease_api: NetEaseApi,
+    qqmusic_api: QQMusicApi,
+    /// 运行时可切换的歌词源开关（初始值来自配置）
+    netease_enabled: AtomicBool,
+    qqmusic_enabled: AtomicBool,
     cache: LyricsCache,
+    /// 正在预加载的歌曲（缓存键），防止重复请求
+    preloading: Mutex<HashSet<String>>,
+    /// 共享的HTTP客户端（用于运行时调整重试次数）
+    http_client: HttpClient,
+    /// 运行时可调整的搜索超时时间（秒）
+    search_timeout_secs: AtomicU64,
+    /// 按歌曲记住的手动匹配
+    overrides: OverrideStore,
+    /// 离线歌词包
+    pack: Option<PackProvider>,
+    /// 本地歌词文件
+    local: Option<LocalLrcSource>,
 }
 
 impl LyricsService {
     /// 创建新的歌词服务
     pub fn new(config: LyricsServiceConfig) -> LyricsResult<Self> {
+        // 没有任何歌词源时服务永远无法获取歌词，直接报错而不是一直显示加载中（本地歌词目录和离线歌词包也算歌词源）
+        let has_offline_source = config.local_dir.is_some() || config.pack_dir.is_some();
+        if !config.enable_netease && !config.enable_qqmusic && !has_offline_source {
+            return Err(LyricsError::NoSourcesEnabled);
+        }
+        
         // 创建HTTP客户端
         let http_client = HttpClient::new(config.http_config.clone())?;
         
-        // 创建API实例
-        let netease_api = if config.enable_netease {
-            Some(NetEaseApi::new(http_client.clone()))
-        } else {
-            None
-        };
-        
-        let qqmusic_api = if config.enable_qqmusic {
-            Some(QQMusicApi::new(http_client))
-        } else {
-            None
-        };
+        // 创建API实例（未启用的歌词源也创建，便于运行时重新启用）
+        let netease_api = NetEaseApi::new(http_client.clone()).with_search_limit(config.search_limit);
+        let qqmusic_api = QQMusicApi::new(http_client.clone()).with_search_limit(config.search_limit);
         
         // 创建缓存
         let cache = LyricsCache::new(config.cache_config.clone())?;
+        let overrides = OverrideStore::load(&config.overrides_path, OverrideStore::DEFAULT_MAX_ENTRIES);
+        // 歌词包不可用时只记录警告，仍可从网络获取歌词
+        let pack = config.pack_dir.as_ref().and_then(|dir| PackProvider::open(dir)
+            .inspect_err(|e| warn!("加载离线歌词包失败: {:?} - {}", dir, e))
+            .ok());
         
-        info!("歌词服务初始化完成 - 网易云: {}, QQ音乐: {}", 
-              config.enable_netease, config.enable_qqmusic);
+        let local = config.local_dir.clone().map(LocalLrcSource::new);
         
+        info!("歌词服务初始化完成 - 网易云: {}, QQ音乐: {}, 本地歌词: {:?}", 
+              config.enable_netease, config.enable_qqmusic, config.local_dir);
+        
+        let search_timeout_secs = config.search_timeout_secs;
         Ok(Self {
+            netease_enabled: AtomicBool::new(config.enable_netease),
+            qqmusic_enabled: AtomicBool::new(config.enable_qqmusic),
             config,
             netease_api,
             qqmusic_api,
             cache,
+            preloading: Mutex::new(HashSet::new()),
+            http_client,
+            search_timeout_secs: AtomicU64::new(search_timeout_secs),
+            overrides,
+            pack,
+            local,
         })
     }
 
@@ -88,16 +151,46 @@ impl LyricsService {
 
         info!("开始搜索歌词: {}", song_info);
 
-        // 1. 首先检查缓存
-        if let Some(cached_lyrics) = self.cache.get(song_info).await {
+        // 0. 离线歌词包（只读，不写入缓存）
+        if let Some(pack_lyrics) = self.pack.as_ref().and_then(|pack| pack.get(song_info)) {
+            info!("从离线歌词包获取歌词: {}", song_info);
+            return Ok(pack_lyrics);
+        }
+
+        // 1. 本地歌词文件（本地播放的歌曲在网络上往往搜不到）。随时可能被修改，每次重新读取、不写入缓存；
+        // 在缓存和"没有歌词"的标记之前检查，联网获取过的歌曲放入本地文件后也能立即生效
+        if let Some(local_lyrics) = self.local_lyrics(song_info) {
+            return Ok(local_lyrics);
+        }
+
+        // 2. 检查缓存（来自已停用歌词源的缓存视为未命中，切换歌词源后会重新搜索）
+        if let Some(cached_lyrics) = self.cache.get(song_info).await
+            .filter(|lyrics| !self.is_network_source_disabled(&lyrics.source))
+        {
             info!("从缓存获取歌词: {}", song_info);
             return Ok(cached_lyrics);
         }
 
-        // 2. 从API获取歌词
-        let lyrics_data = self.fetch_lyrics_from_apis(song_info).await?;
+        // 最近确认过网络上没有歌词，不再重复搜索
+        if self.cache.is_marked_not_found(song_info).await {
+            info!("最近已确认没有歌词，跳过搜索: {}", song_info);
+            return Err(LyricsError::LyricsNotFound);
+        }
+
+        // 3. 从API获取歌词（确实没找到时记录标记）
+        let lyrics_data = match self.fetch_lyrics_from_apis(song_info).await {
+            Ok(lyrics_data) => lyrics_data,
+            Err(e) => {
+                if is_not_found(&e)
+                    && let Err(cache_error) = self.cache.put_not_found(song_info.clone()).await
+                {
+                    warn!("记录没有歌词失败: {}", cache_error);
+                }
+                return Err(e);
+            }
+        };
 
-        // 3. 存储到缓存
+        // 4. 存储到缓存
         if let Err(e) = self.cache.put(song_info.clone(), lyrics_data.clone()).await {
             warn!("缓存歌词失败: {}", e);
         }
@@ -106,56 +199,227 @@ impl LyricsService {
         Ok(lyrics_data)
     }
 
-    /// 从API获取歌词
+    /// 按手动指定的匹配获取歌词（不再搜索）
+    pub async fn get_lyrics_by_override(&self, song_info: &SongInfo, lyrics_override: &LyricsOverride) -> LyricsResult<LyricsData> {
+        // 设置覆盖时已删除旧缓存，来源一致的缓存即为按覆盖获取的结果
+        if let Some(cached_lyrics) = self.cache.get(song_info).await.filter(|lyrics| lyrics.source == lyrics_override.source) {
+            info!("从缓存获取手动匹配的歌词: {}", song_info);
+            return Ok(cached_lyrics);
+        }
+
+        let lyrics_data = self.fetch_lyrics_by_id(lyrics_override).await?;
+        if let Err(e) = self.cache.put(song_info.clone(), lyrics_data.clone()).await {
+            warn!("缓存歌词失败: {}", e);
+        }
+
+        info!("按手动匹配获取歌词: {} ({:?} {})", song_info, lyrics_override.source, lyrics_override.song_id);
+        Ok(lyrics_data)
+    }
+
+    /// 按歌词源和歌曲ID直接获取歌词（不读写缓存）
+    pub async fn fetch_lyrics_by_id(&self, lyrics_override: &LyricsOverride) -> LyricsResult<LyricsData> {
+        let lyrics_data = match tokio::time::timeout(self.search_timeout(), self.request_lyrics_by_id(lyrics_override)).await {
+            Ok(result) => result?,
+            Err(_) => return Err(LyricsError::Timeout),
+        };
+        if !lyrics_data.has_any_content() {
+            return Err(LyricsError::LyricsNotFound);
+        }
+        Ok(lyrics_data)
+    }
+
+    /// 向对应歌词源请求指定歌曲的歌词
+    async fn request_lyrics_by_id(&self, lyrics_override: &LyricsOverride) -> LyricsResult<LyricsData> {
+        match lyrics_override.source {
+            LyricsSource::NetEase => match self.netease() {
+                Some(netease_api) => netease_api.get_lyrics(&lyrics_override.song_id).await,
+                None => Err(LyricsError::ServiceUnavailable),
+            },
+            LyricsSource::QQMusic => match self.qqmusic() {
+                Some(qqmusic_api) => qqmusic_api.get_lyrics(&lyrics_override.song_id, &lyrics_override.song_mid).await,
+                None => Err(LyricsError::ServiceUnavailable),
+            },
+            LyricsSource::Embedded | LyricsSource::Pack | LyricsSource::Local | LyricsSource::Unknown => Err(LyricsError::SongNotFound),
+        }
+    }
+
+    /// 获取歌曲的手动匹配覆盖
+    pub fn get_override(&self, song_info: &SongInfo) -> Option<LyricsOverride> {
+        self.overrides.get(song_info)
+    }
+
+    /// 记住歌曲的手动匹配，并删除按搜索结果缓存的旧歌词
+    pub async fn set_override(&self, song_info: &SongInfo, lyrics_override: LyricsOverride) -> LyricsResult<()> {
+        self.overrides.set(song_info, lyrics_override)?;
+        self.cache.remove(song_info).await
+    }
+
+    /// 移除歌曲的手动匹配，返回是否存在（缓存的歌词同时删除，下次重新搜索）
+    pub async fn remove_override(&self, song_info: &SongInfo) -> LyricsResult<bool> {
+        let removed = self.overrides.remove(song_info)?;
+        if removed {
+            self.cache.remove(song_info).await?;
+        }
+        Ok(removed)
+    }
+
+    /// 删除歌曲的"没有歌词"标记，下次获取时重新搜索（如手动刷新）
+    pub async fn forget_not_found(&self, song_info: &SongInfo) -> LyricsResult<()> {
+        if self.cache.is_marked_not_found(song_info).await {
+            self.cache.remove(song_info).await?;
+        }
+        Ok(())
+    }
+
+    /// 清空所有手动匹配
+    pub fn clear_overrides(&self) -> LyricsResult<()> {
+        self.overrides.clear()
+    }
+
+    /// 运行时启用或停用歌词源，对之后的请求生效（仅网易云和QQ音乐可切换）
+    pub fn set_source_enabled(&self, source: LyricsSource, enabled: bool) {
+        match source {
+            LyricsSource::NetEase => self.netease_enabled.store(enabled, Ordering::Relaxed),
+            LyricsSource::QQMusic => self.qqmusic_enabled.store(enabled, Ordering::Relaxed),
+            LyricsSource::Embedded | LyricsSource::Pack | LyricsSource::Local | LyricsSource::Unknown => {
+                warn!("歌词源 {:?} 不支持运行时切换", source);
+                return;
+            }
+        }
+        info!("歌词源 {:?} 已{}", source, if enabled { "启用" } else { "停用" });
+    }
+
+    /// 歌词源当前是否启用
+    pub fn is_source_enabled(&self, source: &LyricsSource) -> bool {
+        match source {
+            LyricsSource::NetEase => self.netease_enabled.load(Ordering::Relaxed),
+            LyricsSource::QQMusic => self.qqmusic_enabled.load(Ordering::Relaxed),
+            LyricsSource::Embedded | LyricsSource::Pack | LyricsSource::Local | LyricsSource::Unknown => false,
+        }
+    }
+
+    /// 是否为已停用的网络歌词源
+    fn is_network_source_disabled(&self, source: &LyricsSource) -> bool {
+        matches!(source, LyricsSource::NetEase | LyricsSource::QQMusic) && !self.is_source_enabled(source)
+    }
+
+    /// 切换到查询顺序中的下一个网络歌词源（只启用该歌词源），返回切换后的歌词源
+    pub fn switch_to_next_source(&self) -> Option<LyricsSource> {
+        let order = self.source_order();
+        let next = match order.iter().position(|source| self.is_source_enabled(source)) {
+            Some(current) => order.get((current + 1) % order.len())?,
+            None => order.first()?,
+        }.clone();
+        for source in &order {
+            self.set_source_enabled(source.clone(), *source == next);
+        }
+        Some(next)
+    }
+
+    /// 当前启用的网易云音乐API
+    fn netease(&self) -> Option<&NetEaseApi> {
+        Some(&self.netease_api).filter(|_| self.is_source_enabled(&LyricsSource::NetEase))
+    }
+
+    /// 当前启用的QQ音乐API
+    fn qqmusic(&self) -> Option<&QQMusicApi> {
+        Some(&self.qqmusic_api).filter(|_| self.is_source_enabled(&LyricsSource::QQMusic))
+    }
+
+    /// 获取当前的单个歌词源搜索超时时间
+    pub fn search_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.search_timeout_secs.load(Ordering::Relaxed))
+    }
+
+    /// 运行时调整搜索超时时间（秒），对之后的请求生效
+    pub fn set_search_timeout(&self, timeout_secs: u64) {
+        self.search_timeout_secs.store(timeout_secs, Ordering::Relaxed);
+    }
+
+    /// 获取当前的HTTP最大重试次数
+    pub fn max_retries(&self) -> u32 {
+        self.http_client.max_retries()
+    }
+
+    /// 运行时调整HTTP最大重试次数，对之后的请求生效
+    pub fn set_max_retries(&self, max_retries: u32) {
+        self.http_client.set_max_retries(max_retries);
+    }
+
+    /// 从本地歌词目录读取歌词（未配置或没有对应文件时为 None）
+    fn local_lyrics(&self, song_info: &SongInfo) -> Option<LyricsData> {
+        let local = self.local.as_ref()?;
+        match local.get_lyrics(song_info) {
+            Ok(lyrics_data) => {
+                info!("从本地歌词文件获取歌词: {}", song_info);
+                Some(lyrics_data)
+            }
+            Err(e) => {
+                debug!("本地歌词目录中没有歌词: {:?} - {}", local.dir(), e);
+                None
+            }
+        }
+    }
+
+    /// 从API获取歌词，找不到时交换歌名和艺术家再试一次（部分播放器会把两者报反）
     async fn fetch_lyrics_from_apis(&self, song_info: &SongInfo) -> LyricsResult<LyricsData> {
-        let mut last_error = None;
+        // 所有歌词源都已在运行时停用
+        if self.get_supported_sources().is_empty() {
+            return Err(LyricsError::NoSourcesEnabled);
+        }
 
-        // 尝试网易云音乐
-        if let Some(netease_api) = &self.netease_api {
-            debug!("尝试从网易云音乐获取歌词");
-            
-            match tokio::time::timeout(
-                std::time::Duration::from_secs(self.config.search_timeout_secs),
-                netease_api.search_and_get_lyrics(song_info)
-            ).await {
-                Ok(Ok(lyrics_data)) => {
-                    if lyrics_data.has_any_content() {
-                        info!("从网易云音乐成功获取歌词");
-                        return Ok(lyrics_data);
-                    }
-                }
-                Ok(Err(e)) => {
-                    warn!("网易云音乐获取歌词失败: {}", e);
-                    last_error = Some(e);
+        match self.fetch_from_sources(song_info).await {
+            Err(e) => match swapped_retry(song_info, &e) {
+                Some(swapped) => {
+                    info!("交换歌名和艺术家后重试: {}", swapped);
+                    // 重试也没找到时报告原始错误，重试时超时等错误仍需保留（不能记为没有歌词）
+                    self.fetch_from_sources(&swapped).await
+                        .map_err(|retry_error| if is_not_found(&retry_error) { e } else { retry_error })
                 }
-                Err(_) => {
-                    warn!("网易云音乐请求超时");
-                    last_error = Some(LyricsError::Timeout);
-                }
-            }
+                None => Err(e),
+            },
+            result => result,
         }
+    }
+
+    /// 按优先顺序依次从各个启用的歌词源获取歌词
+    async fn fetch_from_sources(&self, song_info: &SongInfo) -> LyricsResult<LyricsData> {
+        let mut last_error = None;
+
+        for source in self.source_order() {
+            let name = source.display_name();
+            debug!("尝试从{}获取歌词", name);
+
+            let request = async {
+                match source {
+                    LyricsSource::NetEase => match self.netease() {
+                        Some(netease_api) => Some(netease_api.search_and_get_lyrics(song_info).await),
+                        None => None,
+                    },
+                    LyricsSource::QQMusic => match self.qqmusic() {
+                        Some(qqmusic_api) => Some(qqmusic_api.search_and_get_lyrics(song_info).await),
+                        None => None,
+                    },
+                    _ => None,
+                }
+            };
 
-        // 尝试QQ音乐
-        if let Some(qqmusic_api) = &self.qqmusic_api {
-            debug!("尝试从QQ音乐获取歌词");
-            
-            match tokio::time::timeout(
-                std::time::Duration::from_secs(self.config.search_timeout_secs),
-                qqmusic_api.search_and_get_lyrics(song_info)
-            ).await {
-                Ok(Ok(lyrics_data)) => {
+            match tokio::time::timeout(self.search_timeout(), request).await {
+                // 歌词源已停用
+                Ok(None) => {}
+                Ok(Some(Ok(lyrics_data))) => {
                     if lyrics_data.has_any_content() {
-                        info!("从QQ音乐成功获取歌词");
+                        info!("从{}成功获取歌词", name);
                         return Ok(lyrics_data);
                     }
                 }
-                Ok(Err(e)) => {
-                    warn!("QQ音乐获取歌词失败: {}", e);
-                    last_error = Some(e);
+                Ok(Some(Err(e))) => {
+                    warn!("{}获取歌词失败: {}", name, e);
+                    last_error = Some(merge_source_error(last_error, e));
                 }
                 Err(_) => {
-                    warn!("QQ音乐请求超时");
-                    last_error = Some(LyricsError::Timeout);
+                    warn!("{}请求超时", name);
+                    last_error = Some(merge_source_error(last_error, LyricsError::Timeout));
                 }
             }
         }
@@ -165,6 +429,23 @@ impl LyricsService {
         Err(last_error.unwrap_or(LyricsError::LyricsNotFound))
     }
 
+    /// 网络歌词源的查询顺序（未配置时为网易云、QQ音乐），重复项只保留第一次出现
+    pub fn source_order(&self) -> Vec<LyricsSource> {
+        let configured = if self.config.source_priority.is_empty() {
+            &LyricsServiceConfig::DEFAULT_SOURCE_PRIORITY[..]
+        } else {
+            &self.config.source_priority[..]
+        };
+
+        let mut order: Vec<LyricsSource> = Vec::new();
+        for source in configured {
+            if !order.contains(source) {
+                order.push(source.clone());
+            }
+        }
+        order
+    }
+
     /// 预加载歌词（异步）
     pub async fn preload_lyrics(&self, song_info: &SongInfo) {
         if !song_info.is_valid() {
@@ -176,26 +457,35 @@ impl LyricsService {
             return;
         }
 
+        // 同一首歌正在预加载时直接跳过
+        let cache_key = song_info.cache_key();
+        let is_new = self.preloading.lock()
+            .map(|mut preloading| preloading.insert(cache_key.clone()))
+            .unwrap_or(false);
+        if !is_new {
+            return;
+        }
+
         debug!("预加载歌词: {}", song_info);
 
         // 直接获取歌词，不启动新任务
-        let song_info_clone = song_info.clone();
-        if let Err(e) = self.search_and_get_lyrics(&song_info_clone).await {
-            debug!("预加载歌词失败: {} - {}", song_info_clone, e);
+        if let Err(e) = self.search_and_get_lyrics(song_info).await {
+            debug!("预加载歌词失败: {} - {}", song_info, e);
+        }
+
+        if let Ok(mut preloading) = self.preloading.lock() {
+            preloading.remove(&cache_key);
         }
     }
 
-    /// 批量预加载歌词
+    /// 批量预加载歌词（并发数受 `max_concurrent_preloads` 限制）
     pub async fn preload_batch(&self, songs: Vec<SongInfo>) {
         info!("开始批量预加载 {} 首歌曲的歌词", songs.len());
 
-        for song in songs.into_iter().filter(|song| song.is_valid()) {
-            if self.cache.get(&song).await.is_none() {
-                if let Err(e) = self.search_and_get_lyrics(&song).await {
-                    debug!("批量预加载歌词失败: {} - {}", song, e);
-                }
-            }
-        }
+        let songs: Vec<SongInfo> = songs.into_iter().filter(|song| song.is_valid()).collect();
+        run_bounded(songs, self.config.max_concurrent_preloads, |song| async move {
+            self.preload_lyrics(&song).await;
+        }).await;
 
         info!("批量预加载完成");
     }
@@ -226,19 +516,40 @@ impl LyricsService {
         self.cache.get(song_info).await.is_some()
     }
 
-    /// 获取支持的歌词源
+    /// 获取当前启用的歌词源（按查询顺序）
     pub fn get_supported_sources(&self) -> Vec<LyricsSource> {
-        let mut sources = Vec::new();
-        
-        if self.netease_api.is_some() {
-            sources.push(LyricsSource::NetEase);
-        }
-        
-        if self.qqmusic_api.is_some() {
-            sources.push(LyricsSource::QQMusic);
+        self.source_order()
+            .into_iter()
+            .filter(|source| self.is_source_enabled(source))
+            .collect()
+    }
+
+    /// 预热到已启用歌词源的连接（提前完成 TLS 握手），失败不影响后续请求
+    pub async fn warm_connection(&self) {
+        if !self.config.warm_up_connections {
+            return;
         }
-        
-        sources
+
+        let netease = async {
+            let Some(netease_api) = self.netease() else {
+                return;
+            };
+            if let Err(e) = netease_api.warm_connection().await {
+                debug!("网易云连接预热失败: {}", e);
+            }
+        };
+
+        let qqmusic = async {
+            let Some(qqmusic_api) = self.qqmusic() else {
+                return;
+            };
+            if let Err(e) = qqmusic_api.warm_connection().await {
+                debug!("QQ音乐连接预热失败: {}", e);
+            }
+        };
+
+        futures::future::join(netease, qqmusic).await;
+        debug!("歌词源连接预热完成");
     }
 
     /// 测试服务连通性
@@ -249,13 +560,13 @@ impl LyricsService {
         let test_song = SongInfo::new("测试", "测试");
         
         // 测试网易云
-        if let Some(netease_api) = &self.netease_api {
+        if let Some(netease_api) = self.netease() {
             let result = netease_api.search_song(&test_song).await.is_ok();
             results.push((LyricsSource::NetEase, result));
         }
         
         // 测试QQ音乐
-        if let Some(qqmusic_api) = &self.qqmusic_api {
+        if let Some(qqmusic_api) = self.qqmusic() {
             let result = qqmusic_api.search_song(&test_song).await.is_ok();
             results.push((LyricsSource::QQMusic, result));
         }
@@ -286,6 +597,18 @@ impl LyricsServiceBuilder {
         self
     }
 
+    /// 设置磁盘缓存目录（其余缓存配置保持不变）
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.config.cache_config.cache_dir = cache_dir.into();
+        self
+    }
+
+    /// 设置缓存生存时间（其余缓存配置保持不变）
+    pub fn with_cache_ttl(mut self, ttl: ChronoDuration) -> Self {
+        self.config.cache_config.ttl = ttl;
+        self
+    }
+
     pub fn enable_netease(mut self, enable: bool) -> Self {
         self.config.enable_netease = enable;
         self
@@ -301,6 +624,46 @@ impl LyricsServiceBuilder {
         self
     }
 
+    pub fn with_max_concurrent_preloads(mut self, max_concurrent: usize) -> Self {
+        self.config.max_concurrent_preloads = max_concurrent;
+        self
+    }
+
+    pub fn with_connection_warmup(mut self, enable: bool) -> Self {
+        self.config.warm_up_connections = enable;
+        self
+    }
+
+    /// 设置手动匹配覆盖的存储文件
+    pub fn with_overrides_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.overrides_path = path.into();
+        self
+    }
+
+    /// 设置离线歌词包目录
+    pub fn with_lyrics_pack(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.pack_dir = Some(dir.into());
+        self
+    }
+
+    /// 设置本地 `.lrc` 文件目录（查找 `艺术家 - 标题.lrc` 或 `标题.lrc`）
+    pub fn with_local_dir(mut self, dir: PathBuf) -> Self {
+        self.config.local_dir = Some(dir);
+        self
+    }
+
+    /// 设置网络歌词源的查询顺序（如优先使用QQ音乐的翻译）
+    pub fn with_source_priority(mut self, priority: Vec<LyricsSource>) -> Self {
+        self.config.source_priority = priority;
+        self
+    }
+
+    /// 设置每次搜索获取的候选数量
+    pub fn with_search_limit(mut self, limit: usize) -> Self {
+        self.config.search_limit = limit;
+        self
+    }
+
     pub fn build(self) -> LyricsResult<LyricsService> {
         LyricsService::new(self.config)
     }
@@ -312,9 +675,51 @@ impl Default for LyricsServiceBuilder {
     }
 }
 
+/// 正常搜索失败后是否值得交换歌名和艺术家重试，返回交换后的歌曲信息
+///
+/// 只在确实没搜到时重试；网络错误、超时等交换后同样会失败
+fn swapped_retry(song_info: &SongInfo, error: &LyricsError) -> Option<SongInfo> {
+    let swapped = song_info.swapped();
+    (is_not_found(error) && swapped.is_valid() && swapped != *song_info).then_some(swapped)
+}
+
+/// 歌词源是否确定地答复了没有这首歌或歌词
+fn is_not_found(error: &LyricsError) -> bool {
+    matches!(error, LyricsError::SongNotFound | LyricsError::LyricsNotFound)
+}
+
+/// 合并各歌词源的错误：超时、网络等错误优先于没有找到，
+/// 只有所有歌词源都确定没有找到时才记录没有歌词的标记
+fn merge_source_error(previous: Option<LyricsError>, error: LyricsError) -> LyricsError {
+    match previous {
+        Some(previous) if !is_not_found(&previous) => previous,
+        _ => error,
+    }
+}
+
+/// 以有限并发执行一组任务，最多同时运行 `max_concurrent` 个
+async fn run_bounded<T, F, Fut>(items: Vec<T>, max_concurrent: usize, task: F)
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let semaphore = Semaphore::new(max_concurrent.max(1));
+    let tasks = items.into_iter().map(|item| {
+        let semaphore = &semaphore;
+        let fut = task(item);
+        async move {
+            let _permit = semaphore.acquire().await;
+            fut.await;
+        }
+    });
+
+    futures::future::join_all(tasks).await;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[tokio::test]
     async fn test_lyrics_service_creation() {
@@ -347,4 +752,305 @@ mod tests {
         let invalid_song = SongInfo::new("", "");
         assert!(!invalid_song.is_valid());
     }
+
+    #[test]
+    fn test_swapped_retry_only_after_not_found() {
+        let song = SongInfo::new("周杰伦", "晴天");
+
+        let swapped = swapped_retry(&song, &LyricsError::LyricsNotFound).unwrap();
+        assert_eq!(swapped.title, "晴天");
+        assert_eq!(swapped.artist, "周杰伦");
+        assert!(swapped_retry(&song, &LyricsError::SongNotFound).is_some());
+
+        // 网络问题交换后同样会失败，不重试
+        assert!(swapped_retry(&song, &LyricsError::Timeout).is_none());
+        assert!(swapped_retry(&song, &LyricsError::AttemptBudgetExhausted).is_none());
+
+        // 歌名和艺术家相同，交换没有意义
+        assert!(swapped_retry(&SongInfo::new("Song", "song"), &LyricsError::LyricsNotFound).is_none());
+    }
+
+    #[test]
+    fn test_failure_outranks_not_found() {
+        // 网易云超时、QQ音乐没有找到：不能记为没有歌词
+        let error = merge_source_error(Some(LyricsError::Timeout), LyricsError::SongNotFound);
+        assert!(matches!(error, LyricsError::Timeout));
+        assert!(!is_not_found(&error));
+
+        let error = merge_source_error(Some(LyricsError::SongNotFound), LyricsError::Timeout);
+        assert!(matches!(error, LyricsError::Timeout));
+
+        // 都没有找到时才报告没有找到
+        let error = merge_source_error(Some(LyricsError::SongNotFound), LyricsError::LyricsNotFound);
+        assert!(is_not_found(&error));
+        assert!(is_not_found(&merge_source_error(None, LyricsError::SongNotFound)));
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_respects_limit() {
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let (active, peak, completed) = (&active, &peak, &completed);
+
+        run_bounded((0..10).collect(), 2, move |_: i32| async move {
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            active.fetch_sub(1, Ordering::SeqCst);
+            completed.fetch_add(1, Ordering::SeqCst);
+        }).await;
+
+        assert_eq!(completed.load(Ordering::SeqCst), 10);
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn test_builder_cache_shortcuts() {
+        let builder = LyricsServiceBuilder::new()
+            .with_cache_dir("portable/cache")
+            .with_cache_ttl(ChronoDuration::hours(2));
+
+        assert_eq!(builder.config.cache_config.cache_dir, PathBuf::from("portable/cache"));
+        assert_eq!(builder.config.cache_config.ttl, ChronoDuration::hours(2));
+        assert_eq!(builder.config.cache_config.max_files, CacheConfig::default().max_files);
+    }
+
+    #[test]
+    fn test_runtime_tunable_timeout_and_retries() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .with_search_timeout(30)
+            .build()
+            .unwrap();
+        assert_eq!(service.search_timeout(), std::time::Duration::from_secs(30));
+        assert_eq!(service.max_retries(), HttpClientConfig::default().max_retries);
+
+        service.set_search_timeout(60);
+        service.set_max_retries(5);
+        assert_eq!(service.search_timeout(), std::time::Duration::from_secs(60));
+        assert_eq!(service.max_retries(), 5);
+    }
+
+    #[test]
+    fn test_build_fails_without_sources() {
+        let result = LyricsServiceBuilder::new()
+            .enable_netease(false)
+            .enable_qqmusic(false)
+            .build();
+
+        assert!(matches!(result, Err(LyricsError::NoSourcesEnabled)));
+    }
+
+    #[tokio::test]
+    async fn test_build_with_only_local_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let local_dir = temp_dir.path().join("music");
+        std::fs::create_dir_all(&local_dir).unwrap();
+        std::fs::write(local_dir.join("Artist - Song.lrc"), "[00:01.00]local line").unwrap();
+
+        let service = LyricsServiceBuilder::new()
+            .enable_netease(false)
+            .enable_qqmusic(false)
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .with_local_dir(local_dir)
+            .build()
+            .unwrap();
+        assert!(service.get_supported_sources().is_empty());
+
+        let lyrics = service.search_and_get_lyrics(&SongInfo::new("Song", "Artist")).await.unwrap();
+        assert_eq!(lyrics.source, LyricsSource::Local);
+    }
+
+    #[tokio::test]
+    async fn test_toggle_source_at_runtime() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .build()
+            .unwrap();
+
+        service.set_source_enabled(LyricsSource::NetEase, false);
+        assert_eq!(service.get_supported_sources(), vec![LyricsSource::QQMusic]);
+        let result = service.fetch_lyrics_by_id(&LyricsOverride::netease("1")).await;
+        assert!(matches!(result, Err(LyricsError::ServiceUnavailable)));
+
+        // 全部停用后直接报错，不发起网络请求
+        service.set_source_enabled(LyricsSource::QQMusic, false);
+        let result = service.search_and_get_lyrics(&SongInfo::new("Song", "Artist")).await;
+        assert!(matches!(result, Err(LyricsError::NoSourcesEnabled)));
+
+        service.set_source_enabled(LyricsSource::NetEase, true);
+        assert!(service.is_source_enabled(&LyricsSource::NetEase));
+        assert_eq!(service.get_supported_sources(), vec![LyricsSource::NetEase]);
+    }
+
+    #[tokio::test]
+    async fn test_switch_to_next_source() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .build()
+            .unwrap();
+        let song = SongInfo::new("Song", "Artist");
+        let cached = LyricsData {
+            original: Some("[00:01.00]netease line".to_string()),
+            source: LyricsSource::NetEase,
+            ..Default::default()
+        };
+        service.cache.put(song.clone(), cached).await.unwrap();
+
+        // 两个都启用时切到第二个，之后在两者之间轮换
+        assert_eq!(service.switch_to_next_source(), Some(LyricsSource::QQMusic));
+        assert_eq!(service.get_supported_sources(), vec![LyricsSource::QQMusic]);
+        assert_eq!(service.switch_to_next_source(), Some(LyricsSource::NetEase));
+        assert_eq!(service.switch_to_next_source(), Some(LyricsSource::QQMusic));
+
+        // 已停用歌词源的缓存不再返回；全部停用后从第一个开始
+        service.set_source_enabled(LyricsSource::QQMusic, false);
+        let result = service.search_and_get_lyrics(&song).await;
+        assert!(matches!(result, Err(LyricsError::NoSourcesEnabled)));
+        assert_eq!(service.switch_to_next_source(), Some(LyricsSource::NetEase));
+        let lyrics = service.search_and_get_lyrics(&song).await.unwrap();
+        assert_eq!(lyrics.source, LyricsSource::NetEase);
+    }
+
+    #[tokio::test]
+    async fn test_lyrics_pack_checked_before_network() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let pack_dir = temp_dir.path().join("pack");
+        std::fs::create_dir_all(&pack_dir).unwrap();
+        std::fs::write(pack_dir.join("Artist - Song.lrc"), "[00:01.00]packed line").unwrap();
+        PackProvider::build_index(&pack_dir).unwrap();
+
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .with_lyrics_pack(&pack_dir)
+            .build()
+            .unwrap();
+        service.set_source_enabled(LyricsSource::NetEase, false);
+        service.set_source_enabled(LyricsSource::QQMusic, false);
+
+        let lyrics = service.search_and_get_lyrics(&SongInfo::new("Song", "Artist")).await.unwrap();
+        assert_eq!(lyrics.source, LyricsSource::Pack);
+        assert!(!service.is_lyrics_cached(&SongInfo::new("Song", "Artist")).await);
+    }
+
+    #[tokio::test]
+    async fn test_local_lrc_checked_before_network() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let local_dir = temp_dir.path().join("music");
+        std::fs::create_dir_all(&local_dir).unwrap();
+        std::fs::write(local_dir.join("Artist - Song.lrc"), "[00:01.00]local line").unwrap();
+
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .with_local_dir(local_dir)
+            .build()
+            .unwrap();
+        service.set_source_enabled(LyricsSource::NetEase, false);
+        service.set_source_enabled(LyricsSource::QQMusic, false);
+
+        let lyrics = service.search_and_get_lyrics(&SongInfo::new("Song", "Artist")).await.unwrap();
+        assert_eq!(lyrics.source, LyricsSource::Local);
+        assert!(!service.is_lyrics_cached(&SongInfo::new("Song", "Artist")).await);
+    }
+
+    #[tokio::test]
+    async fn test_local_lrc_checked_before_not_found_marker() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let local_dir = temp_dir.path().join("music");
+        std::fs::create_dir_all(&local_dir).unwrap();
+
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .with_local_dir(local_dir.clone())
+            .build()
+            .unwrap();
+        let song = SongInfo::new("Song", "Artist");
+        service.cache.put_not_found(song.clone()).await.unwrap();
+        let result = service.search_and_get_lyrics(&song).await;
+        assert!(matches!(result, Err(LyricsError::LyricsNotFound)));
+
+        // 标记之后才放入的本地歌词文件立即生效
+        std::fs::write(local_dir.join("Artist - Song.lrc"), "[00:01.00]local line").unwrap();
+        let lyrics = service.search_and_get_lyrics(&song).await.unwrap();
+        assert_eq!(lyrics.source, LyricsSource::Local);
+    }
+
+    #[tokio::test]
+    async fn test_local_lrc_checked_before_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let local_dir = temp_dir.path().join("music");
+        std::fs::create_dir_all(&local_dir).unwrap();
+
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .with_local_dir(local_dir.clone())
+            .build()
+            .unwrap();
+        let song = SongInfo::new("Song", "Artist");
+        let online = LyricsData {
+            original: Some("[00:01.00]online line".to_string()),
+            source: LyricsSource::NetEase,
+            has_lyrics: true,
+            ..Default::default()
+        };
+        service.cache.put(song.clone(), online).await.unwrap();
+        assert_eq!(service.search_and_get_lyrics(&song).await.unwrap().source, LyricsSource::NetEase);
+
+        // 联网获取过的歌曲放入本地歌词文件后，不必等缓存过期
+        std::fs::write(local_dir.join("Artist - Song.lrc"), "[00:01.00]local line").unwrap();
+        assert_eq!(service.search_and_get_lyrics(&song).await.unwrap().source, LyricsSource::Local);
+    }
+
+    #[test]
+    fn test_source_priority_order() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let builder = || LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"));
+
+        let service = builder().build().unwrap();
+        assert_eq!(service.source_order(), vec![LyricsSource::NetEase, LyricsSource::QQMusic]);
+
+        let service = builder()
+            .with_source_priority(vec![LyricsSource::QQMusic, LyricsSource::NetEase, LyricsSource::QQMusic])
+            .build()
+            .unwrap();
+        assert_eq!(service.source_order(), vec![LyricsSource::QQMusic, LyricsSource::NetEase]);
+    }
+
+    #[tokio::test]
+    async fn test_set_override_drops_searched_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let service = LyricsServiceBuilder::new()
+            .with_cache_dir(temp_dir.path().join("cache"))
+            .with_overrides_path(temp_dir.path().join("overrides.json"))
+            .build()
+            .unwrap();
+        let song = SongInfo::new("Song", "Artist");
+        let searched = LyricsData {
+            original: Some("[00:01.00]wrong match".to_string()),
+            source: LyricsSource::QQMusic,
+            ..Default::default()
+        };
+        service.cache.put(song.clone(), searched).await.unwrap();
+
+        service.set_override(&song, LyricsOverride::netease("123")).await.unwrap();
+        assert!(!service.is_lyrics_cached(&song).await);
+        assert_eq!(service.get_override(&song), Some(LyricsOverride::netease("123")));
+
+        assert!(service.remove_override(&song).await.unwrap());
+        assert!(service.get_override(&song).is_none());
+    }
 }
@@ -1,11 +1,12 @@
 #![windows_subsystem = "windows"]
 
-use taskbar_lrc::{App, EventLoop};
+use taskbar_lrc::{App, AppSettings, EngineConfig, EventLoop, LyricsEngine};
 use windows::Win32::Foundation::{CloseHandle, GetLastError, HANDLE, ERROR_ALREADY_EXISTS};
 use windows::Win32::System::Threading::CreateMutexW;
 use windows::core::PCWSTR;
 use std::ffi::OsStr;
 use std::os::windows::ffi::OsStrExt;
+use std::path::PathBuf;
 
 /// 程序入口点
 fn main() -> std::result::Result<(), String> {
@@ -13,8 +14,24 @@ fn main() -> std::result::Result<(), String> {
     let _mutex_handle = ensure_single_instance()
         .map_err(|e| format!("单实例检查失败: {}", e))?;
     
+    // 读取用户设置（由设置窗口保存）
+    let settings = AppSettings::load(AppSettings::default_path());
+    let mut config = EngineConfig::default();
+    settings.apply_to_engine(&mut config);
+    
+    // 无窗口模式：只运行歌词引擎
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--headless") {
+        let output_path = args.iter()
+            .position(|arg| arg == "--output")
+            .and_then(|index| args.get(index + 1))
+            .map(PathBuf::from);
+        return run_headless(output_path, config);
+    }
+    
     // 创建应用实例
-    let mut app = App::new();
+    let mut app = App::with_config(config);
+    app.apply_settings(settings);
     
     // 创建事件循环
     let event_loop = EventLoop::new()
@@ -27,6 +44,37 @@ fn main() -> std::result::Result<(), String> {
     Ok(())
 }
 
+/// 无窗口模式：不创建窗口和渲染器，仅运行歌词引擎
+/// 指定输出文件时，当前歌词行变化后写入该文件
+fn run_headless(output_path: Option<PathBuf>, config: EngineConfig) -> Result<(), String> {
+    let engine = LyricsEngine::start(config);
+    let mut state_receiver = engine.subscribe();
+    
+    let rt = taskbar_lrc::tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("创建运行时失败: {}", e))?;
+    
+    rt.block_on(async move {
+        let mut last_line: Option<String> = None;
+        
+        // 引擎线程退出时通道关闭，循环随之结束
+        while state_receiver.changed().await.is_ok() {
+            let current_line = state_receiver.borrow_and_update().lyrics_state.current_line.clone();
+            if current_line == last_line {
+                continue;
+            }
+            
+            if let Some(path) = &output_path {
+                let _ = std::fs::write(path, current_line.as_deref().unwrap_or(""));
+            }
+            last_line = current_line;
+        }
+    });
+    
+    Ok(())
+}
+
 /// 确保只有单一实例运行
 fn ensure_single_instance() -> Result<MutexHandle, String> {
     // 创建一个唯一的互斥锁名称
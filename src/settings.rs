@@ -0,0 +1,189 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::engine::EngineConfig;
+use crate::graphics::RenderStyle;
+use crate::lyrics::LyricsDisplayMode;
+use crate::widget::TaskbarWidget;
+
+/// 用户设置，保存在配置目录的 JSON 文件中，可通过设置窗口修改
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// 样式预设名称（见 `RenderStyle::preset_names`）
+    pub style_preset: String,
+    /// 歌词显示语言
+    pub display_mode: LyricsDisplayMode,
+    /// 贴靠任务栏左侧
+    pub show_on_left: bool,
+    /// 窗口足够高时预览下一句
+    pub show_next_line: bool,
+    /// 暂停时隐藏歌词
+    pub hide_when_paused: bool,
+    /// 保持窗口置顶
+    pub keep_topmost: bool,
+    /// 启用网易云音乐歌词源
+    pub enable_netease: bool,
+    /// 启用QQ音乐歌词源
+    pub enable_qqmusic: bool,
+    /// 歌词时间轴整体偏移（毫秒，正数推迟、负数提前）
+    pub lyrics_offset_ms: i64,
+    /// 字号占窗口高度的比例，覆盖样式预设中的字号
+    pub font_size_ratio: f32,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            style_preset: "light".to_string(),
+            display_mode: LyricsDisplayMode::default(),
+            show_on_left: false,
+            show_next_line: false,
+            hide_when_paused: true,
+            keep_topmost: true,
+            enable_netease: true,
+            enable_qqmusic: true,
+            lyrics_offset_ms: 0,
+            font_size_ratio: RenderStyle::DEFAULT_FONT_SIZE_RATIO,
+        }
+    }
+}
+
+impl AppSettings {
+    /// 默认存储路径
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .map(|mut path| {
+                path.push("taskbar_lrc");
+                path.push("settings.json");
+                path
+            })
+            .unwrap_or_else(|| PathBuf::from("config/settings.json"))
+    }
+
+    /// 读取设置，文件不存在或无法解析时使用默认值
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Self::default();
+        }
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                warn!("读取设置文件失败，将使用默认设置: {}", e);
+                Self::default()
+            })
+    }
+
+    /// 写入设置文件
+    pub fn save(&self, path: impl AsRef<Path>) -> std::result::Result<(), String> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("序列化设置失败: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("写入设置文件失败: {}", e))
+    }
+
+    /// 把显示相关的设置应用到小组件（未知的样式预设保持原样式）
+    pub fn apply_to_widget(&self, widget: &mut TaskbarWidget) {
+        if let Err(e) = widget.set_style_preset(&self.style_preset) {
+            warn!("{}", e);
+        }
+        let style = widget.style;
+        widget.set_style(style.with_ratios(self.font_size_ratio, style.margin_ratio));
+        widget.display_mode = self.display_mode;
+        widget.show_on_left = self.show_on_left;
+        widget.show_next_line = self.show_next_line;
+        widget.hide_when_paused = self.hide_when_paused;
+        widget.keep_topmost = self.keep_topmost;
+    }
+
+    /// 把歌词源和偏移设置应用到引擎配置（启动前调用）
+    pub fn apply_to_engine(&self, config: &mut EngineConfig) {
+        config.service.enable_netease = self.enable_netease;
+        config.service.enable_qqmusic = self.enable_qqmusic;
+        config.manager.lyrics_offset_ms = self.lyrics_offset_ms;
+    }
+
+    /// 当前样式预设在 `RenderStyle::preset_names` 中的位置（未知时为 0）
+    pub fn style_preset_index(&self) -> usize {
+        RenderStyle::preset_names().iter()
+            .position(|name| RenderStyle::preset(name) == RenderStyle::preset(&self.style_preset))
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("settings.json");
+        assert_eq!(AppSettings::load(&path), AppSettings::default());
+
+        let settings = AppSettings {
+            style_preset: "dark".to_string(),
+            display_mode: LyricsDisplayMode::Bilingual,
+            show_on_left: true,
+            enable_qqmusic: false,
+            ..Default::default()
+        };
+        settings.save(&path).unwrap();
+        assert_eq!(AppSettings::load(&path), settings);
+    }
+
+    #[test]
+    fn test_invalid_or_partial_file_uses_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("settings.json");
+
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(AppSettings::load(&path), AppSettings::default());
+
+        // 缺少的字段使用默认值，便于以后新增设置项
+        fs::write(&path, r#"{"show_on_left": true}"#).unwrap();
+        let settings = AppSettings::load(&path);
+        assert!(settings.show_on_left);
+        assert!(settings.hide_when_paused);
+        assert_eq!(settings.style_preset, "light");
+    }
+
+    #[test]
+    fn test_apply_settings() {
+        let settings = AppSettings {
+            style_preset: "high-contrast".to_string(),
+            display_mode: LyricsDisplayMode::Translated,
+            show_next_line: true,
+            keep_topmost: false,
+            enable_netease: false,
+            lyrics_offset_ms: -300,
+            font_size_ratio: 0.5,
+            ..Default::default()
+        };
+
+        let mut widget = TaskbarWidget::new();
+        settings.apply_to_widget(&mut widget);
+        assert_eq!(widget.display_mode, LyricsDisplayMode::Translated);
+        assert!(widget.show_next_line);
+        assert!(!widget.keep_topmost);
+        assert_eq!(widget.style.font_size_ratio, 0.5);
+        assert_eq!(widget.style.margin_ratio, RenderStyle::DEFAULT_MARGIN_RATIO);
+
+        let mut config = EngineConfig::default();
+        settings.apply_to_engine(&mut config);
+        assert!(!config.service.enable_netease);
+        assert!(config.service.enable_qqmusic);
+        assert_eq!(config.manager.lyrics_offset_ms, -300);
+
+        assert_eq!(settings.style_preset_index(), 2);
+        assert_eq!(AppSettings { style_preset: "unknown".to_string(), ..Default::default() }.style_preset_index(), 0);
+    }
+}
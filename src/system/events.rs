@@ -13,7 +13,7 @@ pub fn set_widget_pointer(widget: &TaskbarWidget) {
 }
 
 /// 设置系统事件钩子
-pub fn setup_system_event_hook() -> std::result::Result<HWINEVENTHOOK, String> {
+pub fn setup_system_event_hook() -> InitResult<HWINEVENTHOOK> {
     unsafe {
         let hook = SetWinEventHook(
             EVENT_OBJECT_CREATE,         // 最小事件类型 - 监听窗口创建
@@ -28,7 +28,7 @@ pub fn setup_system_event_hook() -> std::result::Result<HWINEVENTHOOK, String> {
         if !hook.0.is_null() {
             Ok(hook)
         } else {
-            Err("设置事件钩子失败".to_string())
+            Err(InitError::EventHook("SetWinEventHook 返回空句柄".to_string()))
         }
     }
 }
@@ -68,7 +68,7 @@ unsafe extern "system" fn win_event_proc(
             // 当其他窗口获得焦点时，立即确保我们的窗口保持在最上层
             if let Some(our_hwnd) = widget.get_window_hwnd() {
                 if hwnd != our_hwnd {
-                    widget.ensure_topmost();
+                    widget.request_topmost();
                 }
             }
         }
@@ -76,8 +76,8 @@ unsafe extern "system" fn win_event_proc(
             // 窗口创建或销毁时，检查是否是任务栏相关窗口
             if is_taskbar_related {
                 widget.position_update_pending = true;
-                // 窗口创建/销毁可能影响层级，立即确保最上层
-                widget.ensure_topmost();
+                // 窗口创建/销毁可能影响层级，确保最上层
+                widget.request_topmost();
             }
         }
         EVENT_OBJECT_LOCATIONCHANGE => {
@@ -97,8 +97,8 @@ unsafe extern "system" fn win_event_proc(
             if is_taskbar_related {
                 widget.position_update_pending = true;
             }
-            // 任何窗口重新排序都可能影响我们的层级，立即确保最上层
-            widget.ensure_topmost();
+            // 任何窗口重新排序都可能影响我们的层级，确保最上层
+            widget.request_topmost();
         }
         EVENT_OBJECT_STATECHANGE => {
             // 窗口状态变化（最小化、最大化等）
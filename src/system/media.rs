@@ -1,10 +1,14 @@
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tokio::sync::watch;
+use tokio::sync::{mpsc, watch, Notify};
+use tracing::warn;
 use serde::{Deserialize, Serialize};
 
 use windows::{
     core::*,
-    Media::Control::*,
+    Foundation::TypedEventHandler,
+    Media::{Control::*, MediaPlaybackType},
+    Storage::Streams::DataReader,
 };
 
 /// 媒体播放状态
@@ -39,13 +43,62 @@ pub struct MediaInfo {
     pub app_name: String,
     pub title: String,
     pub artist: String,
+    /// 专辑艺术家（播放器未提供时为空）
+    #[serde(default)]
+    pub album_artist: String,
+    /// 媒体会话标记为视频内容
+    #[serde(default)]
+    pub is_video: bool,
+    /// 流派（播放器未提供时为空）
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// 播放器在媒体会话中附带的多行文本（可能是歌词，是否带时间轴由歌词管理器判断）
+    #[serde(default)]
+    pub embedded_lyrics: Option<String>,
     pub duration: Option<Duration>,
     pub position: Option<Duration>,
     pub playback_status: PlaybackStatus,
+    /// 专辑封面的原始图片数据（PNG / JPEG 等，播放器未提供或未启用 `MediaMonitorConfig::read_thumbnails` 时为 None）
+    ///
+    /// 只在切歌时读取一次，多份媒体信息共享同一份数据
+    #[serde(skip)]
+    pub thumbnail: Option<Arc<Vec<u8>>>,
     #[serde(skip)]
     pub last_updated: Option<Instant>,
 }
 
+impl MediaInfo {
+    /// 除播放位置和更新时间外是否完全相同（用于区分有意义的变化和单纯的进度推进）
+    pub fn content_eq(&self, other: &MediaInfo) -> bool {
+        // 解构全部字段，新增字段时必须在这里决定是否参与比较
+        let Self {
+            app_name,
+            title,
+            artist,
+            album_artist,
+            is_video,
+            genres,
+            embedded_lyrics,
+            duration,
+            playback_status,
+            position: _,
+            // 封面随歌曲变化，歌曲信息相同时不需要比较
+            thumbnail: _,
+            last_updated: _,
+        } = self;
+
+        *app_name == other.app_name
+            && *title == other.title
+            && *artist == other.artist
+            && *album_artist == other.album_artist
+            && *is_video == other.is_video
+            && *genres == other.genres
+            && *embedded_lyrics == other.embedded_lyrics
+            && *duration == other.duration
+            && *playback_status == other.playback_status
+    }
+}
+
 /// 媒体事件
 #[derive(Debug, Clone)]
 pub enum MediaEvent {
@@ -57,28 +110,195 @@ pub enum MediaEvent {
     Stopped,
 }
 
+/// 播放控制命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaControl {
+    /// 播放/暂停
+    TogglePlayPause,
+    /// 下一首
+    Next,
+    /// 上一首
+    Previous,
+}
+
+/// 媒体监测配置
+#[derive(Debug, Clone, Default)]
+pub struct MediaMonitorConfig {
+    /// 切换播放器的宽限期（0 表示立即切换）
+    pub session_switch_grace: Duration,
+    /// 切歌时读取专辑封面（默认关闭，小组件本身不显示封面，供需要封面的嵌入方开启）
+    pub read_thumbnails: bool,
+}
+
+/// 播放器会话选择：系统当前会话切换到其他应用后，在宽限期内继续使用原应用的会话
+#[derive(Debug, Default)]
+struct SessionSelector {
+    /// 宽限期（0 表示立即切换）
+    grace: Duration,
+    /// 当前使用的应用
+    committed: Option<String>,
+    /// 等待确认的新应用及首次出现的时间
+    candidate: Option<(Option<String>, Instant)>,
+}
+
+impl SessionSelector {
+    fn new(grace: Duration) -> Self {
+        Self { grace, ..Default::default() }
+    }
+
+    /// 根据系统当前会话的应用（None 表示没有会话）和原应用的会话是否仍然存在，决定使用哪个应用
+    fn select(&mut self, current_app: Option<&str>, previous_available: bool, now: Instant) -> Option<String> {
+        let current_app = current_app.map(str::to_string);
+        if self.committed.is_none() || self.committed == current_app || self.grace.is_zero() || !previous_available {
+            return self.commit(current_app);
+        }
+
+        match &self.candidate {
+            Some((candidate, since)) if *candidate == current_app => {
+                if now.duration_since(*since) >= self.grace {
+                    return self.commit(current_app);
+                }
+            }
+            _ => self.candidate = Some((current_app, now)),
+        }
+        self.committed.clone()
+    }
+
+    fn commit(&mut self, app: Option<String>) -> Option<String> {
+        self.candidate = None;
+        self.committed = app.clone();
+        app
+    }
+}
+
+/// 最近读取的封面
+struct CachedThumbnail {
+    /// 对应的歌曲（应用、标题、艺术家）
+    song_key: String,
+    thumbnail: Option<Arc<Vec<u8>>>,
+}
+
+/// 已注册变化回调的会话，释放时注销回调
+struct SessionWatch {
+    session: GlobalSystemMediaTransportControlsSession,
+    media_properties_token: i64,
+    playback_info_token: i64,
+    timeline_properties_token: i64,
+}
+
+impl SessionWatch {
+    /// 在会话的媒体属性、播放状态和时间轴变化时唤醒监控循环
+    fn register(session: GlobalSystemMediaTransportControlsSession, changed: &Arc<Notify>) -> Result<Self> {
+        let notify = changed.clone();
+        let media_properties_token = session.MediaPropertiesChanged(&TypedEventHandler::new(move |_, _| {
+            notify.notify_one();
+            Ok(())
+        }))?;
+        let notify = changed.clone();
+        let playback_info_token = session.PlaybackInfoChanged(&TypedEventHandler::new(move |_, _| {
+            notify.notify_one();
+            Ok(())
+        }))?;
+        let notify = changed.clone();
+        let timeline_properties_token = session.TimelinePropertiesChanged(&TypedEventHandler::new(move |_, _| {
+            notify.notify_one();
+            Ok(())
+        }))?;
+
+        Ok(Self {
+            session,
+            media_properties_token,
+            playback_info_token,
+            timeline_properties_token,
+        })
+    }
+}
+
+impl Drop for SessionWatch {
+    fn drop(&mut self) {
+        let _ = self.session.RemoveMediaPropertiesChanged(self.media_properties_token);
+        let _ = self.session.RemovePlaybackInfoChanged(self.playback_info_token);
+        let _ = self.session.RemoveTimelinePropertiesChanged(self.timeline_properties_token);
+    }
+}
+
 /// 优化的媒体监测器
 /// 使用事件驱动架构，提供实时媒体信息更新
+///
+/// 会话的变化事件到达时立即读取并推送媒体信息，定时轮询只作为事件丢失时的兜底
 pub struct MediaMonitor {
     session_manager: Option<GlobalSystemMediaTransportControlsSessionManager>,
     event_sender: watch::Sender<MediaEvent>,
     is_running: bool,
+    session_selector: Mutex<SessionSelector>,
+    /// 系统媒体事件触发时唤醒监控循环
+    changed: Arc<Notify>,
+    /// 当前读取的会话上注册的回调
+    session_watch: Mutex<Option<SessionWatch>>,
+    /// 会话管理器上 `CurrentSessionChanged` 回调的注册令牌
+    current_session_token: Option<i64>,
+    /// 是否读取专辑封面
+    read_thumbnails: bool,
+    /// 最近读取的封面，同一首歌不重复读取
+    thumbnail_cache: Mutex<Option<CachedThumbnail>>,
+    /// 允许读取的应用（为空表示不限制）
+    allowed_apps: Vec<String>,
+    /// 播放控制命令通道，命令在监控循环所在的异步运行时上执行
+    control_sender: mpsc::UnboundedSender<MediaControl>,
+    control_receiver: mpsc::UnboundedReceiver<MediaControl>,
 }
 
 impl MediaMonitor {
     /// 创建新的媒体监测器
     pub fn new() -> (Self, watch::Receiver<MediaEvent>) {
         let (event_sender, event_receiver) = watch::channel(MediaEvent::Stopped);
+        let (control_sender, control_receiver) = mpsc::unbounded_channel();
         
         let monitor = Self {
             session_manager: None,
             event_sender,
             is_running: false,
+            session_selector: Mutex::new(SessionSelector::default()),
+            changed: Arc::new(Notify::new()),
+            session_watch: Mutex::new(None),
+            current_session_token: None,
+            read_thumbnails: false,
+            thumbnail_cache: Mutex::new(None),
+            allowed_apps: Vec::new(),
+            control_sender,
+            control_receiver,
         };
         
         (monitor, event_receiver)
     }
 
+    /// 设置切换播放器的宽限期：当前会话变为其他应用后，原应用的会话仍存在时继续使用它，
+    /// 新应用持续超过宽限期才切换，避免播放器短暂失去会话时歌词闪烁（0 表示立即切换）
+    pub fn with_session_switch_grace(self, grace: Duration) -> Self {
+        Self {
+            session_selector: Mutex::new(SessionSelector::new(grace)),
+            ..self
+        }
+    }
+
+    /// 只读取指定应用的会话，避免浏览器广告、通知等其他会话抢占歌词（为空表示不限制）
+    ///
+    /// 引擎使用 `MediaFilter::allowed_apps`，与歌词过滤共用同一份配置
+    pub fn with_allowed_apps(self, allowed_apps: Vec<String>) -> Self {
+        Self { allowed_apps, ..self }
+    }
+
+    /// 切歌时是否读取专辑封面（写入 `MediaInfo::thumbnail`）
+    pub fn with_read_thumbnails(self, read_thumbnails: bool) -> Self {
+        Self { read_thumbnails, ..self }
+    }
+
+    /// 按配置设置切换宽限期和封面读取（应用白名单见 `with_allowed_apps`）
+    pub fn with_config(self, config: MediaMonitorConfig) -> Self {
+        self.with_session_switch_grace(config.session_switch_grace)
+            .with_read_thumbnails(config.read_thumbnails)
+    }
+
     /// 异步初始化媒体监测器
     pub async fn initialize(&mut self) -> Result<()> {
         match GlobalSystemMediaTransportControlsSessionManager::RequestAsync() {
@@ -103,12 +323,21 @@ impl MediaMonitor {
         }
     }
 
-    /// 开始媒体信息同步循环
+    /// 开始媒体信息同步循环：系统媒体事件到达时立即更新，否则每隔 `interval` 兜底轮询一次
     pub async fn start_monitoring(&mut self, interval: Duration) -> Result<()> {
-        if self.session_manager.is_none() {
+        let Some(manager) = self.session_manager.clone() else {
             let error_msg = "媒体监测器未初始化".to_string();
             let _ = self.event_sender.send(MediaEvent::Error(error_msg.clone()));
             return Err(Error::from_hresult(HRESULT(-1)));
+        };
+
+        // 切换播放器后读取新会话，并在新会话上重新注册回调
+        if self.current_session_token.is_none() {
+            let notify = self.changed.clone();
+            self.current_session_token = manager.CurrentSessionChanged(&TypedEventHandler::new(move |_, _| {
+                notify.notify_one();
+                Ok(())
+            })).ok();
         }
 
         self.is_running = true;
@@ -125,9 +354,20 @@ impl MediaMonitor {
                 }
             }
 
-            tokio::time::sleep(interval).await;
+            let control = tokio::select! {
+                _ = self.changed.notified() => None,
+                _ = tokio::time::sleep(interval) => None,
+                control = self.control_receiver.recv() => control,
+            };
+            // 播放器执行命令后会触发会话变化事件，下一轮即读取新状态
+            if let Some(control) = control
+                && let Err(e) = self.control(control).await
+            {
+                warn!("播放控制失败: {:?} - {}", control, e);
+            }
         }
 
+        self.unregister_handlers();
         let _ = self.event_sender.send(MediaEvent::Stopped);
         Ok(())
     }
@@ -135,9 +375,68 @@ impl MediaMonitor {
     /// 停止监控循环
     pub fn stop(&mut self) {
         self.is_running = false;
+        self.unregister_handlers();
         let _ = self.event_sender.send(MediaEvent::Stopped);
     }
 
+    /// 注销所有系统媒体事件回调
+    fn unregister_handlers(&mut self) {
+        if let Ok(mut session_watch) = self.session_watch.lock() {
+            session_watch.take();
+        }
+        if let (Some(manager), Some(token)) = (&self.session_manager, self.current_session_token.take()) {
+            let _ = manager.RemoveCurrentSessionChanged(token);
+        }
+    }
+
+    /// 确保回调注册在正在读取的会话上（会话变化时注销旧会话的回调）
+    fn watch_session(&self, session: &GlobalSystemMediaTransportControlsSession) {
+        let Ok(mut session_watch) = self.session_watch.lock() else {
+            return;
+        };
+        if session_watch.as_ref().is_some_and(|watch| watch.session == *session) {
+            return;
+        }
+
+        session_watch.take();
+        *session_watch = SessionWatch::register(session.clone(), &self.changed).ok();
+    }
+
+    /// 播放控制命令的发送端（命令由 `start_monitoring` 循环执行）
+    pub fn control_sender(&self) -> mpsc::UnboundedSender<MediaControl> {
+        self.control_sender.clone()
+    }
+
+    /// 播放/暂停当前会话
+    pub async fn toggle_play_pause(&self) -> Result<bool> {
+        self.control(MediaControl::TogglePlayPause).await
+    }
+
+    /// 当前会话切换到下一首
+    pub async fn next(&self) -> Result<bool> {
+        self.control(MediaControl::Next).await
+    }
+
+    /// 当前会话切换到上一首
+    pub async fn previous(&self) -> Result<bool> {
+        self.control(MediaControl::Previous).await
+    }
+
+    /// 对正在读取的会话执行播放控制，返回播放器是否接受了命令（没有会话时为 false）
+    pub async fn control(&self, control: MediaControl) -> Result<bool> {
+        let watched = self.session_watch.lock().ok()
+            .and_then(|session_watch| session_watch.as_ref().map(|watch| watch.session.clone()));
+        let Some(session) = watched.or_else(|| self.session_manager.as_ref().and_then(|manager| self.select_session(manager))) else {
+            return Ok(false);
+        };
+
+        match control {
+            MediaControl::TogglePlayPause => session.TryTogglePlayPauseAsync()?.await,
+            MediaControl::Next => session.TrySkipNextAsync()?.await,
+            MediaControl::Previous => session.TrySkipPreviousAsync()?.await,
+        }
+    }
+
     /// 检查是否已初始化
     pub fn is_initialized(&self) -> bool {
         self.session_manager.is_some()
@@ -151,11 +450,9 @@ impl MediaMonitor {
 
         let manager = self.session_manager.as_ref()?;
 
-        // 获取当前会话
-        let session = match manager.GetCurrentSession() {
-            Ok(session) => session,
-            Err(_) => return None,
-        };
+        // 获取当前会话（宽限期内可能继续使用原应用的会话）
+        let session = self.select_session(manager)?;
+        self.watch_session(&session);
 
         // 获取媒体属性
         let session_properties = match session.TryGetMediaPropertiesAsync() {
@@ -171,12 +468,29 @@ impl MediaMonitor {
         // 获取基本信息
         let title = session_properties.Title().ok()?.to_string();
         let artist = session_properties.Artist().ok()?.to_string();
+        let album_artist = session_properties.AlbumArtist()
+            .map(|album_artist| album_artist.to_string())
+            .unwrap_or_default();
+        // 媒体会话没有专门的歌词属性，部分播放器把整首歌词放在副标题中
+        let embedded_lyrics = session_properties.Subtitle()
+            .map(|subtitle| subtitle.to_string())
+            .ok()
+            .filter(|subtitle| subtitle.lines().count() > 1);
+        let is_video = session_properties.PlaybackType()
+            .and_then(|playback_type| playback_type.Value())
+            .is_ok_and(|playback_type| playback_type == MediaPlaybackType::Video);
+        let genres = session_properties.Genres()
+            .map(|genres| genres.into_iter().map(|genre| genre.to_string()).collect())
+            .unwrap_or_default();
 
         // 检查歌曲信息是否有效
         if title.trim().is_empty() || artist.trim().is_empty() {
             return None;
         }
 
+        let app_name = session_app_id(&session).unwrap_or_default();
+        let thumbnail = self.thumbnail_for(&session_properties, &format!("{}|{}|{}", app_name, title, artist)).await;
+
         // 获取播放状态和时间信息
         let playback_info = session.GetPlaybackInfo().ok()?;
         let timeline_props = session.GetTimelineProperties().ok()?;
@@ -186,22 +500,204 @@ impl MediaMonitor {
         let end_time = timeline_props.EndTime().ok()?;
         let position = timeline_props.Position().ok()?;
         
-        let duration = Duration::from_nanos(end_time.Duration as u64 * 100);
-        let current_position = Duration::from_nanos(position.Duration as u64 * 100);
+        // 部分播放器会上报负值或异常巨大的时间，需要过滤
+        let duration = timespan_to_duration(end_time.Duration);
+        let current_position = timespan_to_duration(position.Duration).unwrap_or(Duration::ZERO);
 
         Some(MediaInfo {
-            app_name: String::new(),
+            app_name,
             title: title.trim().to_string(), // 去除首尾空格
             artist: artist.trim().to_string(), // 去除首尾空格
-            duration: Some(duration),
+            album_artist: album_artist.trim().to_string(),
+            is_video,
+            genres,
+            embedded_lyrics,
+            duration,
             position: Some(current_position),
             playback_status,
+            thumbnail,
             last_updated: Some(Instant::now()),
         })
     }
 
+    /// 获取歌曲的封面：与上次读取的是同一首歌时直接复用，否则读取缩略图流
+    async fn thumbnail_for(
+        &self,
+        properties: &GlobalSystemMediaTransportControlsSessionMediaProperties,
+        song_key: &str,
+    ) -> Option<Arc<Vec<u8>>> {
+        if !self.read_thumbnails {
+            return None;
+        }
+        if let Ok(cache) = self.thumbnail_cache.lock()
+            && let Some(cached) = cache.as_ref()
+            && cached.song_key == song_key
+        {
+            return cached.thumbnail.clone();
+        }
+
+        let thumbnail = read_thumbnail(properties).await.map(Arc::new);
+        if let Ok(mut cache) = self.thumbnail_cache.lock() {
+            *cache = Some(CachedThumbnail { song_key: song_key.to_string(), thumbnail: thumbnail.clone() });
+        }
+        thumbnail
+    }
+
+    /// 按应用白名单和切换宽限期选择要读取的会话
+    fn select_session(&self, manager: &GlobalSystemMediaTransportControlsSessionManager) -> Option<GlobalSystemMediaTransportControlsSession> {
+        let current = manager.GetCurrentSession().ok()
+            .filter(|session| self.is_session_allowed(session))
+            .or_else(|| self.first_allowed_session(manager));
+        let current_app = current.as_ref().and_then(session_app_id);
+
+        let mut selector = self.session_selector.lock().ok()?;
+        let previous = selector.committed.clone()
+            .filter(|app| current_app.as_ref() != Some(app))
+            .and_then(|app| find_session(manager, &app));
+
+        let selected = selector.select(current_app.as_deref(), previous.is_some(), Instant::now());
+        if selected == current_app { current } else { previous }
+    }
+
+    /// 会话所属应用是否在白名单内
+    fn is_session_allowed(&self, session: &GlobalSystemMediaTransportControlsSession) -> bool {
+        self.allowed_apps.is_empty()
+            || session_app_id(session).is_some_and(|app_id| app_id_matches(&self.allowed_apps, &app_id))
+    }
+
+    /// 系统当前会话不在白名单内时，按系统顺序使用第一个允许的会话
+    fn first_allowed_session(&self, manager: &GlobalSystemMediaTransportControlsSessionManager) -> Option<GlobalSystemMediaTransportControlsSession> {
+        if self.allowed_apps.is_empty() {
+            return None;
+        }
+        manager.GetSessions().ok()?
+            .into_iter()
+            .find(|session| self.is_session_allowed(session))
+    }
+
     /// 订阅媒体事件
     pub fn subscribe(&self) -> watch::Receiver<MediaEvent> {
         self.event_sender.subscribe()
     }
 }
+
+/// 会话所属应用的 ID
+fn session_app_id(session: &GlobalSystemMediaTransportControlsSession) -> Option<String> {
+    session.SourceAppUserModelId().ok().map(|app_id| app_id.to_string())
+}
+
+/// 应用 ID 是否匹配列表中的某一项（按子串匹配，不区分大小写，如 `spotify` 匹配 `Spotify.exe`）
+///
+/// 会话选择的白名单和歌词过滤（`MediaFilter`）共用此规则，同一项配置在两处的效果一致
+pub fn app_id_matches(apps: &[String], app_id: &str) -> bool {
+    let app_id = app_id.to_lowercase();
+    apps.iter()
+        .map(|app| app.trim())
+        .any(|app| !app.is_empty() && app_id.contains(&app.to_lowercase()))
+}
+
+/// 查找指定应用的会话
+fn find_session(manager: &GlobalSystemMediaTransportControlsSessionManager, app_id: &str) -> Option<GlobalSystemMediaTransportControlsSession> {
+    manager.GetSessions().ok()?
+        .into_iter()
+        .find(|session| session_app_id(session).as_deref() == Some(app_id))
+}
+
+/// 封面图片的大小上限，超过则视为异常数据
+const MAX_THUMBNAIL_BYTES: u64 = 8 * 1024 * 1024;
+
+/// 读取媒体属性中的缩略图流（没有封面或读取失败时为 None）
+async fn read_thumbnail(properties: &GlobalSystemMediaTransportControlsSessionMediaProperties) -> Option<Vec<u8>> {
+    let stream_op = properties.Thumbnail().ok()?.OpenReadAsync().ok()?;
+    // 流对象不能跨线程持有，创建读取器后即释放
+    let (reader, size) = {
+        let stream = stream_op.await.ok()?;
+        let size = stream.Size().ok().filter(|size| *size > 0 && *size <= MAX_THUMBNAIL_BYTES)?;
+        (DataReader::CreateDataReader(&stream).ok()?, size)
+    };
+
+    let loaded = reader.LoadAsync(size as u32).ok()?.await.ok()?;
+    let mut bytes = vec![0u8; loaded as usize];
+    reader.ReadBytes(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+/// 合理的媒体时长上限（24小时），超过则视为异常数据
+const MAX_MEDIA_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 将 WinRT TimeSpan（100纳秒为单位）转换为 Duration，负值或超出合理范围时返回 None
+fn timespan_to_duration(ticks: i64) -> Option<Duration> {
+    let ticks = u64::try_from(ticks).ok()?;
+    let duration = Duration::from_nanos(ticks.checked_mul(100)?);
+    (duration <= MAX_MEDIA_DURATION).then_some(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timespan_to_duration() {
+        assert_eq!(timespan_to_duration(30_000_000), Some(Duration::from_secs(3)));
+        assert_eq!(timespan_to_duration(0), Some(Duration::ZERO));
+        assert_eq!(timespan_to_duration(-1), None);
+        assert_eq!(timespan_to_duration(i64::MIN), None);
+        assert_eq!(timespan_to_duration(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_media_content_eq_ignores_position() {
+        let media = MediaInfo {
+            title: "晴天".to_string(),
+            artist: "周杰伦".to_string(),
+            position: Some(Duration::from_secs(10)),
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        };
+        let ticked = MediaInfo {
+            position: Some(Duration::from_secs(13)),
+            last_updated: Some(Instant::now()),
+            ..media.clone()
+        };
+        assert!(media.content_eq(&ticked));
+        assert_ne!(media, ticked);
+
+        let paused = MediaInfo { playback_status: PlaybackStatus::Paused, ..media.clone() };
+        assert!(!media.content_eq(&paused));
+        let other_song = MediaInfo { title: "七里香".to_string(), ..media.clone() };
+        assert!(!media.content_eq(&other_song));
+    }
+
+    #[test]
+    fn test_allowed_apps_matching() {
+        let allowed = vec!["Spotify.exe".to_string(), "cloudmusic.exe".to_string()];
+        assert!(app_id_matches(&allowed, "Spotify.exe"));
+        assert!(app_id_matches(&allowed, "spotify.exe"));
+        assert!(app_id_matches(&allowed, "C:\\Program Files\\Netease\\CloudMusic\\cloudmusic.exe"));
+        assert!(!app_id_matches(&allowed, "MSEdge"));
+        assert!(!app_id_matches(&allowed, "SpotifyHelper.exe"));
+        assert!(app_id_matches(&["spotify".to_string()], "Spotify.exe"));
+        assert!(!app_id_matches(&[" ".to_string()], "Spotify.exe"));
+    }
+
+    #[test]
+    fn test_session_selector_grace() {
+        let start = Instant::now();
+        let mut selector = SessionSelector::new(Duration::from_secs(3));
+        assert_eq!(selector.select(Some("spotify"), false, start).as_deref(), Some("spotify"));
+
+        // 宽限期内原应用会话仍在，继续使用
+        assert_eq!(selector.select(Some("edge"), true, start + Duration::from_secs(1)).as_deref(), Some("spotify"));
+        // 原应用短暂失去会话后恢复，不切换
+        assert_eq!(selector.select(Some("spotify"), true, start + Duration::from_secs(2)).as_deref(), Some("spotify"));
+        assert_eq!(selector.select(Some("edge"), true, start + Duration::from_secs(3)).as_deref(), Some("spotify"));
+        assert_eq!(selector.select(Some("edge"), true, start + Duration::from_secs(6)).as_deref(), Some("edge"));
+
+        // 原应用的会话已不存在时立即切换
+        assert_eq!(selector.select(None, false, start + Duration::from_secs(7)), None);
+
+        let mut immediate = SessionSelector::new(Duration::ZERO);
+        immediate.select(Some("spotify"), false, start);
+        assert_eq!(immediate.select(Some("edge"), true, start).as_deref(), Some("edge"));
+    }
+}
@@ -2,36 +2,55 @@ mod taskbar;
 mod events;
 mod media;
 mod playback_timer;
+mod power;
+mod tray;
+#[cfg(feature = "settings-window")]
+mod settings_window;
 
 pub use taskbar::*;
 pub use events::*;
 pub use media::*;
 pub use playback_timer::*;
+pub use power::*;
+pub use tray::*;
+#[cfg(feature = "settings-window")]
+pub use settings_window::*;
 
 use crate::*;
 
 /// 系统管理器，负责与Windows系统的交互（简化版本）
 /// 媒体监控和歌词服务现在由App直接管理
 pub struct SystemManager {
+    /// 停靠的任务栏编号：0 为主任务栏，其余按显示器从左到右排列；对应的显示器不存在时使用主任务栏
+    pub taskbar_index: usize,
     pub taskbar_hwnd: HWND,
     taskbar_rect: RECT,
+    taskbar_orientation: TaskbarOrientation,
     event_hook: HWINEVENTHOOK,
 }
 
 impl SystemManager {
     pub fn new() -> Self {
         Self {
+            taskbar_index: 0,
             taskbar_hwnd: HWND::default(),
             taskbar_rect: RECT::default(),
+            taskbar_orientation: TaskbarOrientation::default(),
             event_hook: HWINEVENTHOOK::default(),
         }
     }
 
-    /// 查找任务栏句柄
-    pub fn find_taskbar_handle(&mut self) -> std::result::Result<HWND, String> {
-        let (hwnd, rect) = find_taskbar()?;
+    /// 查找 `taskbar_index` 指定的任务栏句柄
+    pub fn find_taskbar_handle(&mut self) -> InitResult<HWND> {
+        let (hwnd, rect, orientation) = if self.taskbar_index == 0 {
+            find_taskbar()?
+        } else {
+            let taskbars = find_all_taskbars()?;
+            taskbars.get(self.taskbar_index).copied().unwrap_or(taskbars[0])
+        };
         self.taskbar_hwnd = hwnd;
         self.taskbar_rect = rect;
+        self.taskbar_orientation = orientation;
         
         let _taskbar_height = (rect.bottom - rect.top) as u32;
         
@@ -48,13 +67,28 @@ impl SystemManager {
         self.taskbar_rect
     }
 
+    /// 获取任务栏方向
+    pub fn get_taskbar_orientation(&self) -> TaskbarOrientation {
+        self.taskbar_orientation
+    }
+
     /// 获取通知区域矩形
     pub fn get_notify_area_rect(&self) -> RECT {
         get_notification_area_rect(self.taskbar_hwnd)
     }
 
+    /// 获取任务栏应用按钮列表矩形
+    pub fn get_task_list_rect(&self) -> RECT {
+        get_task_list_rect(self.taskbar_hwnd)
+    }
+
+    /// 是否正在预览桌面
+    pub fn is_desktop_peek_active(&self) -> bool {
+        is_desktop_peek_active(self.taskbar_hwnd)
+    }
+
     /// 设置系统事件钩子
-    pub fn setup_event_hook(&mut self) -> std::result::Result<(), String> {
+    pub fn setup_event_hook(&mut self) -> InitResult<()> {
         let hook = setup_system_event_hook()?;
         self.event_hook = hook;
         Ok(())
@@ -0,0 +1,45 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+/// 窗口过程收到供电状态变化通知、等待 UI 线程重新检查
+static POWER_STATUS_CHANGED: AtomicBool = AtomicBool::new(false);
+
+/// 省电策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerMode {
+    /// 使用电池供电时自动省电
+    #[default]
+    Auto,
+    /// 始终以正常帧率和完整效果绘制
+    Performance,
+    /// 始终省电
+    PowerSaver,
+}
+
+impl PowerMode {
+    /// 在当前供电状态下是否应省电
+    pub fn is_power_saving(self, on_battery: bool) -> bool {
+        match self {
+            PowerMode::Auto => on_battery,
+            PowerMode::Performance => false,
+            PowerMode::PowerSaver => true,
+        }
+    }
+}
+
+/// 当前是否使用电池供电（台式机、查询失败或状态未知时视为接通电源）
+pub fn is_on_battery() -> bool {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // ACLineStatus: 0 为电池，1 为交流电源，255 为未知
+    unsafe { GetSystemPowerStatus(&mut status) }.is_ok() && status.ACLineStatus == 0
+}
+
+/// 记录一次供电状态变化（窗口过程收到 `WM_POWERBROADCAST` / `PBT_APMPOWERSTATUSCHANGE` 时调用）
+pub fn notify_power_status_changed() {
+    POWER_STATUS_CHANGED.store(true, Ordering::Relaxed);
+}
+
+/// 取走自上次调用以来是否收到过供电状态变化通知
+pub fn take_power_status_changed() -> bool {
+    POWER_STATUS_CHANGED.swap(false, Ordering::Relaxed)
+}
@@ -0,0 +1,287 @@
+use crate::*;
+use crate::lyrics::LyricsDisplayMode;
+use crate::graphics::RenderStyle;
+use crate::settings::AppSettings;
+use std::sync::Mutex;
+use windows::Win32::Graphics::Gdi::{GetStockObject, COLOR_WINDOW, DEFAULT_GUI_FONT, HBRUSH, HGDIOBJ};
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Controls::{BST_CHECKED, WC_BUTTON, WC_COMBOBOX, WC_EDIT, WC_STATIC};
+
+/// 样式预设下拉框
+const ID_STYLE: i32 = 100;
+/// 歌词语言下拉框
+const ID_DISPLAY_MODE: i32 = 101;
+/// 字号下拉框
+const ID_FONT_SIZE: i32 = 102;
+/// 歌词偏移输入框
+const ID_OFFSET: i32 = 103;
+/// 第一个复选框，其余依次递增
+const ID_FIRST_CHECKBOX: i32 = 110;
+/// 保存按钮
+const ID_SAVE: i32 = 120;
+
+/// 每行控件的高度和行距
+const ROW_HEIGHT: i32 = 24;
+const ROW_SPACING: i32 = 30;
+const MARGIN: i32 = 12;
+const LABEL_WIDTH: i32 = 80;
+const CONTENT_WIDTH: i32 = 260;
+
+/// 字号选项（显示名称、字号占窗口高度的比例）
+const FONT_SIZES: [(&str, f32); 3] = [
+    ("小", 0.35),
+    ("中", RenderStyle::DEFAULT_FONT_SIZE_RATIO),
+    ("大", 0.5),
+];
+
+/// 复选框对应的设置项
+type SettingsFlag = fn(&mut AppSettings) -> &mut bool;
+
+/// 复选框（按显示顺序）
+const CHECKBOXES: [(&str, SettingsFlag); 6] = [
+    ("贴靠任务栏左侧", |settings| &mut settings.show_on_left),
+    ("预览下一句", |settings| &mut settings.show_next_line),
+    ("暂停时隐藏", |settings| &mut settings.hide_when_paused),
+    ("保持置顶", |settings| &mut settings.keep_topmost),
+    ("网易云音乐歌词", |settings| &mut settings.enable_netease),
+    ("QQ音乐歌词", |settings| &mut settings.enable_qqmusic),
+];
+
+/// 点击保存后、等待 UI 线程应用的设置
+static SAVED_SETTINGS: Mutex<Option<AppSettings>> = Mutex::new(None);
+
+/// 原生设置窗口：样式、歌词语言、字号、歌词偏移、位置和歌词源
+///
+/// 点击保存后设置暂存并关闭窗口，由 UI 线程通过 `take_saved` 取走并应用
+pub struct SettingsWindow {
+    hwnd: HWND,
+}
+
+impl SettingsWindow {
+    /// 创建设置窗口并按当前设置初始化控件
+    pub fn open(settings: &AppSettings) -> std::result::Result<Self, String> {
+        unsafe {
+            let instance: HINSTANCE = GetModuleHandleW(None)
+                .map_err(|e| format!("获取模块句柄失败: {}", e))?
+                .into();
+            let class_name = w!("TaskbarLrcSettings");
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(settings_window_proc),
+                hInstance: instance,
+                lpszClassName: class_name,
+                hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+                hbrBackground: HBRUSH((COLOR_WINDOW.0 + 1) as *mut _),
+                ..Default::default()
+            };
+            // 重复注册会失败，但已注册的类仍可使用
+            RegisterClassW(&class);
+
+            let rows = 4 + CHECKBOXES.len() as i32 + 1;
+            let hwnd = CreateWindowExW(
+                WS_EX_TOOLWINDOW | WS_EX_TOPMOST,
+                class_name,
+                w!("任务栏歌词设置"),
+                WS_CAPTION | WS_SYSMENU,
+                CW_USEDEFAULT, CW_USEDEFAULT,
+                CONTENT_WIDTH + MARGIN * 3, rows * ROW_SPACING + MARGIN * 4,
+                None,
+                None,
+                Some(instance),
+                None,
+            ).map_err(|e| format!("创建设置窗口失败: {}", e))?;
+
+            let window = Self { hwnd };
+            window.create_controls(settings)
+                .inspect_err(|_| { let _ = DestroyWindow(hwnd); })?;
+            let _ = ShowWindow(hwnd, SW_SHOW);
+            let _ = SetForegroundWindow(hwnd);
+            Ok(window)
+        }
+    }
+
+    /// 窗口是否仍未关闭
+    pub fn is_open(&self) -> bool {
+        unsafe { IsWindow(Some(self.hwnd)).as_bool() }
+    }
+
+    /// 把已打开的窗口切到前台
+    pub fn focus(&self) {
+        unsafe {
+            let _ = SetForegroundWindow(self.hwnd);
+        }
+    }
+
+    /// 取走用户保存的设置
+    pub fn take_saved(&self) -> Option<AppSettings> {
+        SAVED_SETTINGS.lock().ok().and_then(|mut saved| saved.take())
+    }
+
+    /// 创建所有控件
+    unsafe fn create_controls(&self, settings: &AppSettings) -> std::result::Result<(), String> {
+        unsafe {
+            let font = GetStockObject(DEFAULT_GUI_FONT);
+            let field_x = MARGIN + LABEL_WIDTH;
+            let field_width = CONTENT_WIDTH - LABEL_WIDTH;
+            let combo_style = WS_TABSTOP | WS_VSCROLL | WINDOW_STYLE(CBS_DROPDOWNLIST as u32);
+
+            let mut y = MARGIN;
+            self.create_control(WC_STATIC, "样式", WINDOW_STYLE::default(), (MARGIN, y + 4, LABEL_WIDTH, ROW_HEIGHT), 0, font)?;
+            let style = self.create_control(WC_COMBOBOX, "", combo_style, (field_x, y, field_width, ROW_HEIGHT * 6), ID_STYLE, font)?;
+            for name in RenderStyle::preset_names() {
+                add_combo_item(style, name);
+            }
+            select_combo_item(style, settings.style_preset_index());
+
+            y += ROW_SPACING;
+            self.create_control(WC_STATIC, "歌词语言", WINDOW_STYLE::default(), (MARGIN, y + 4, LABEL_WIDTH, ROW_HEIGHT), 0, font)?;
+            let display_mode = self.create_control(WC_COMBOBOX, "", combo_style, (field_x, y, field_width, ROW_HEIGHT * 6), ID_DISPLAY_MODE, font)?;
+            for mode in LyricsDisplayMode::CYCLE {
+                add_combo_item(display_mode, mode.display_name());
+            }
+            select_combo_item(display_mode, LyricsDisplayMode::CYCLE.iter().position(|mode| *mode == settings.display_mode).unwrap_or(0));
+
+            y += ROW_SPACING;
+            self.create_control(WC_STATIC, "字号", WINDOW_STYLE::default(), (MARGIN, y + 4, LABEL_WIDTH, ROW_HEIGHT), 0, font)?;
+            let font_size = self.create_control(WC_COMBOBOX, "", combo_style, (field_x, y, field_width, ROW_HEIGHT * 6), ID_FONT_SIZE, font)?;
+            for (name, _) in FONT_SIZES {
+                add_combo_item(font_size, name);
+            }
+            select_combo_item(font_size, font_size_index(settings.font_size_ratio));
+
+            y += ROW_SPACING;
+            self.create_control(WC_STATIC, "偏移(毫秒)", WINDOW_STYLE::default(), (MARGIN, y + 4, LABEL_WIDTH, ROW_HEIGHT), 0, font)?;
+            self.create_control(WC_EDIT, &settings.lyrics_offset_ms.to_string(), WS_TABSTOP | WS_BORDER | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+                (field_x, y, field_width, ROW_HEIGHT), ID_OFFSET, font)?;
+
+            let mut editing = settings.clone();
+            let checkbox_style = WS_TABSTOP | WINDOW_STYLE(BS_AUTOCHECKBOX as u32);
+            for (index, (label, flag)) in CHECKBOXES.iter().enumerate() {
+                y += ROW_SPACING;
+                let checkbox = self.create_control(WC_BUTTON, label, checkbox_style, (MARGIN, y, CONTENT_WIDTH, ROW_HEIGHT), ID_FIRST_CHECKBOX + index as i32, font)?;
+                if *flag(&mut editing) {
+                    SendMessageW(checkbox, BM_SETCHECK, Some(WPARAM(BST_CHECKED.0 as usize)), None);
+                }
+            }
+
+            y += ROW_SPACING + MARGIN / 2;
+            let button_width = 80;
+            self.create_control(WC_BUTTON, "保存", WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+                (MARGIN + CONTENT_WIDTH - button_width, y, button_width, ROW_HEIGHT + 4), ID_SAVE, font)?;
+            Ok(())
+        }
+    }
+
+    /// 创建一个子控件并使用系统界面字体
+    unsafe fn create_control(
+        &self,
+        class: PCWSTR,
+        text: &str,
+        style: WINDOW_STYLE,
+        (x, y, width, height): (i32, i32, i32, i32),
+        id: i32,
+        font: HGDIOBJ,
+    ) -> std::result::Result<HWND, String> {
+        unsafe {
+            let text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+            let control = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class,
+                PCWSTR(text.as_ptr()),
+                WS_CHILD | WS_VISIBLE | style,
+                x, y, width, height,
+                Some(self.hwnd),
+                Some(HMENU(id as isize as *mut _)),
+                None,
+                None,
+            ).map_err(|e| format!("创建设置控件失败: {}", e))?;
+            SendMessageW(control, WM_SETFONT, Some(WPARAM(font.0 as usize)), Some(LPARAM(1)));
+            Ok(control)
+        }
+    }
+}
+
+impl Drop for SettingsWindow {
+    fn drop(&mut self) {
+        if self.is_open() {
+            unsafe {
+                let _ = DestroyWindow(self.hwnd);
+            }
+        }
+    }
+}
+
+/// 向下拉框添加一项
+unsafe fn add_combo_item(combo: HWND, text: &str) {
+    let text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        SendMessageW(combo, CB_ADDSTRING, None, Some(LPARAM(text.as_ptr() as isize)));
+    }
+}
+
+/// 选中下拉框的指定项
+unsafe fn select_combo_item(combo: HWND, index: usize) {
+    unsafe {
+        SendMessageW(combo, CB_SETCURSEL, Some(WPARAM(index)), None);
+    }
+}
+
+/// 下拉框当前选中的项（未选择时为 None）
+unsafe fn selected_combo_item(dialog: HWND, id: i32) -> Option<usize> {
+    unsafe {
+        let combo = GetDlgItem(Some(dialog), id).ok()?;
+        usize::try_from(SendMessageW(combo, CB_GETCURSEL, None, None).0).ok()
+    }
+}
+
+/// 与给定字号比例最接近的字号选项
+fn font_size_index(ratio: f32) -> usize {
+    FONT_SIZES.iter()
+        .enumerate()
+        .min_by(|(_, (_, a)), (_, (_, b))| (a - ratio).abs().total_cmp(&(b - ratio).abs()))
+        .map(|(index, _)| index)
+        .unwrap_or(1)
+}
+
+/// 从控件读取设置
+unsafe fn read_settings(dialog: HWND) -> AppSettings {
+    unsafe {
+        let mut settings = AppSettings::default();
+        if let Some(name) = selected_combo_item(dialog, ID_STYLE).and_then(|index| RenderStyle::preset_names().get(index)) {
+            settings.style_preset = name.to_string();
+        }
+        if let Some(mode) = selected_combo_item(dialog, ID_DISPLAY_MODE).and_then(|index| LyricsDisplayMode::CYCLE.get(index)) {
+            settings.display_mode = *mode;
+        }
+        if let Some((_, ratio)) = selected_combo_item(dialog, ID_FONT_SIZE).and_then(|index| FONT_SIZES.get(index)) {
+            settings.font_size_ratio = *ratio;
+        }
+        // 无法解析的偏移按 0 处理
+        if let Ok(edit) = GetDlgItem(Some(dialog), ID_OFFSET) {
+            let mut text = [0u16; 32];
+            let len = GetWindowTextW(edit, &mut text).max(0) as usize;
+            settings.lyrics_offset_ms = String::from_utf16_lossy(&text[..len]).trim().parse().unwrap_or(0);
+        }
+        for (index, (_, flag)) in CHECKBOXES.iter().enumerate() {
+            if let Ok(checkbox) = GetDlgItem(Some(dialog), ID_FIRST_CHECKBOX + index as i32) {
+                *flag(&mut settings) = SendMessageW(checkbox, BM_GETCHECK, None, None).0 == BST_CHECKED.0 as isize;
+            }
+        }
+        settings
+    }
+}
+
+/// 设置窗口的窗口过程
+unsafe extern "system" fn settings_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    unsafe {
+        // 低位为控件编号，高位为通知码
+        if msg == WM_COMMAND && (wparam.0 & 0xFFFF) as i32 == ID_SAVE && ((wparam.0 >> 16) & 0xFFFF) as u32 == BN_CLICKED {
+            let settings = read_settings(hwnd);
+            if let Ok(mut saved) = SAVED_SETTINGS.lock() {
+                *saved = Some(settings);
+            }
+            let _ = DestroyWindow(hwnd);
+            return LRESULT(0);
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+}
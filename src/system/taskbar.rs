@@ -1,7 +1,28 @@
 use crate::*;
 
+/// 任务栏方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskbarOrientation {
+    /// 停靠在屏幕顶部或底部
+    #[default]
+    Horizontal,
+    /// 停靠在屏幕左侧或右侧
+    Vertical,
+}
+
+impl TaskbarOrientation {
+    /// 根据任务栏矩形的宽高判断方向（高大于宽为竖向）
+    pub fn from_rect(rect: RECT) -> Self {
+        if rect.bottom - rect.top > rect.right - rect.left {
+            TaskbarOrientation::Vertical
+        } else {
+            TaskbarOrientation::Horizontal
+        }
+    }
+}
+
 /// 查找任务栏窗口并获取其信息
-pub fn find_taskbar() -> std::result::Result<(HWND, RECT), String> {
+pub fn find_taskbar() -> InitResult<(HWND, RECT, TaskbarOrientation)> {
     // Windows 10/11 主任务栏
     let taskbar = unsafe { FindWindowW(w!("Shell_TrayWnd"), None) };
     
@@ -10,14 +31,41 @@ pub fn find_taskbar() -> std::result::Result<(HWND, RECT), String> {
             let mut rect = RECT::default();
             unsafe { 
                 if let Err(_) = GetWindowRect(hwnd, &mut rect) {
-                    return Err("无法获取任务栏区域".to_string());
+                    return Err(InitError::TaskbarNotFound("无法获取任务栏区域".to_string()));
                 }
             };
             
-            Ok((hwnd, rect))
+            Ok((hwnd, rect, TaskbarOrientation::from_rect(rect)))
+        }
+        _ => Err(InitError::TaskbarNotFound("找不到 Shell_TrayWnd 窗口".to_string()))
+    }
+}
+
+/// 查找所有显示器上的任务栏：主任务栏在前，其余显示器的任务栏（`Shell_SecondaryTrayWnd`）按位置从左到右、从上到下排列
+pub fn find_all_taskbars() -> InitResult<Vec<(HWND, RECT, TaskbarOrientation)>> {
+    let primary = find_taskbar()?;
+
+    let mut secondary = Vec::new();
+    let mut after = None;
+    while let Ok(hwnd) = unsafe { FindWindowExW(None, after, w!("Shell_SecondaryTrayWnd"), None) } {
+        if hwnd.0.is_null() {
+            break;
+        }
+        after = Some(hwnd);
+
+        let mut rect = RECT::default();
+        if unsafe { GetWindowRect(hwnd, &mut rect) }.is_ok() {
+            secondary.push((hwnd, rect, TaskbarOrientation::from_rect(rect)));
         }
-        _ => Err("找不到任务栏窗口".to_string())
     }
+    sort_by_position(&mut secondary);
+
+    Ok(std::iter::once(primary).chain(secondary).collect())
+}
+
+/// 按任务栏位置排序，使编号与显示器的排列顺序一致
+fn sort_by_position(taskbars: &mut [(HWND, RECT, TaskbarOrientation)]) {
+    taskbars.sort_by_key(|(_, rect, _)| (rect.left, rect.top));
 }
 
 /// 获取通知区域的矩形
@@ -37,6 +85,53 @@ pub fn get_notification_area_rect(taskbar_hwnd: HWND) -> RECT {
     notify_rect
 }
 
+/// 获取任务栏应用按钮列表（MSTaskListWClass）的矩形，找不到时返回空矩形
+/// Windows 11 的任务栏由 XAML 绘制，通常不存在该窗口
+pub fn get_task_list_rect(taskbar_hwnd: HWND) -> RECT {
+    let find_child = |parent: HWND, class_name: PCWSTR| unsafe {
+        FindWindowExW(Some(parent), None, class_name, None)
+            .ok()
+            .filter(|hwnd| !hwnd.0.is_null())
+    };
+    
+    let task_list_hwnd = find_child(taskbar_hwnd, w!("ReBarWindow32"))
+        .and_then(|rebar| find_child(rebar, w!("MSTaskSwWClass")))
+        .and_then(|task_switch| find_child(task_switch, w!("MSTaskListWClass")));
+    
+    let mut task_list_rect = RECT::default();
+    if let Some(hwnd) = task_list_hwnd {
+        unsafe {
+            let _ = GetWindowRect(hwnd, &mut task_list_rect);
+        };
+    }
+    
+    task_list_rect
+}
+
+/// 检查是否正在通过任务栏最右侧的“显示桌面”按钮预览桌面（鼠标悬停在该按钮上）
+/// 系统没有公开的预览状态接口，找不到按钮时视为未预览
+pub fn is_desktop_peek_active(taskbar_hwnd: HWND) -> bool {
+    let show_desktop_hwnd = unsafe {
+        FindWindowExW(Some(taskbar_hwnd), None, w!("TrayNotifyWnd"), None)
+            .and_then(|notify| FindWindowExW(Some(notify), None, w!("TrayShowDesktopButtonWClass"), None))
+            .unwrap_or_default()
+    };
+    if show_desktop_hwnd.0.is_null() {
+        return false;
+    }
+
+    let mut button_rect = RECT::default();
+    let mut cursor = POINT::default();
+    unsafe {
+        if GetWindowRect(show_desktop_hwnd, &mut button_rect).is_err() || GetCursorPos(&mut cursor).is_err() {
+            return false;
+        }
+    }
+
+    cursor.x >= button_rect.left && cursor.x < button_rect.right &&
+        cursor.y >= button_rect.top && cursor.y < button_rect.bottom
+}
+
 /// 检查窗口是否与任务栏相关
 pub fn is_taskbar_related(hwnd: HWND) -> bool {
     if hwnd.0.is_null() {
@@ -50,19 +145,52 @@ pub fn is_taskbar_related(hwnd: HWND) -> bool {
         if len > 0 {
             let class_name_str = String::from_utf16_lossy(&class_name[..len as usize]);
             
-            // 检查是否是任务栏相关的窗口类
-            let is_related = class_name_str.contains("Shell_TrayWnd") || 
-                           class_name_str.contains("TrayNotifyWnd") ||
-                           class_name_str.contains("TrayClockWClass") ||
-                           class_name_str.contains("SysPager") ||
-                           class_name_str.contains("ToolbarWindow32") ||
-                           class_name_str.contains("NotifyIconOverflowWindow") ||
-                           class_name_str.contains("TopLevelWindowForOverflowXamlIsland");
-            
             // 静默检测，不输出日志
-            return is_related;
+            return is_taskbar_class(&class_name_str);
         }
     }
     // 静默检测失败
     false
 }
+
+/// 是否是任务栏相关的窗口类（包括其他显示器上的任务栏）
+fn is_taskbar_class(class_name: &str) -> bool {
+    class_name.contains("Shell_TrayWnd") ||
+        class_name.contains("Shell_SecondaryTrayWnd") ||
+        class_name.contains("TrayNotifyWnd") ||
+        class_name.contains("TrayClockWClass") ||
+        class_name.contains("SysPager") ||
+        class_name.contains("ToolbarWindow32") ||
+        class_name.contains("NotifyIconOverflowWindow") ||
+        class_name.contains("TopLevelWindowForOverflowXamlIsland")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secondary_taskbars_sorted_by_position() {
+        let taskbar = |left: i32, top: i32| {
+            let rect = RECT { left, top, right: left + 1920, bottom: top + 48 };
+            (HWND::default(), rect, TaskbarOrientation::from_rect(rect))
+        };
+        let mut taskbars = vec![taskbar(3840, 1032), taskbar(-1920, 1032), taskbar(1920, 1032), taskbar(1920, -48)];
+        sort_by_position(&mut taskbars);
+        let positions: Vec<(i32, i32)> = taskbars.iter().map(|(_, rect, _)| (rect.left, rect.top)).collect();
+        assert_eq!(positions, vec![(-1920, 1032), (1920, -48), (1920, 1032), (3840, 1032)]);
+
+        assert!(is_taskbar_class("Shell_SecondaryTrayWnd"));
+        assert!(is_taskbar_class("Shell_TrayWnd"));
+        assert!(!is_taskbar_class("Chrome_WidgetWin_1"));
+    }
+
+    #[test]
+    fn test_orientation_from_rect() {
+        let bottom = RECT { left: 0, top: 1040, right: 1920, bottom: 1080 };
+        let right = RECT { left: 1858, top: 0, right: 1920, bottom: 1080 };
+        assert_eq!(TaskbarOrientation::from_rect(bottom), TaskbarOrientation::Horizontal);
+        assert_eq!(TaskbarOrientation::from_rect(right), TaskbarOrientation::Vertical);
+        assert_eq!(TaskbarOrientation::from_rect(RECT::default()), TaskbarOrientation::Horizontal);
+    }
+}
@@ -0,0 +1,291 @@
+use crate::*;
+use std::sync::{Mutex, OnceLock};
+use tracing::warn;
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Shell::{
+    Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE, NIM_MODIFY, NOTIFYICONDATAW,
+};
+
+/// 托盘图标的回调消息
+const WM_TRAY_ICON: u32 = WM_APP + 1;
+
+/// 托盘图标在所属窗口内的编号
+const TRAY_ICON_ID: u32 = 1;
+
+/// 托盘提示文字的最大长度（含结尾的 0）
+const TOOLTIP_LEN: usize = 128;
+
+/// 窗口过程中收到、等待 UI 线程处理的托盘命令
+static PENDING_COMMANDS: Mutex<Vec<TrayCommand>> = Mutex::new(Vec::new());
+
+/// 当前的提示文字，资源管理器重启后重新添加图标时使用
+static CURRENT_TOOLTIP: Mutex<String> = Mutex::new(String::new());
+
+/// 托盘菜单命令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayCommand {
+    /// 重新加载当前歌曲的歌词
+    Refresh,
+    /// 显示或隐藏歌词窗口
+    ToggleVisibility,
+    /// 切换到下一个网络歌词源并重新加载
+    SwitchSource,
+    /// 打开设置窗口
+    #[cfg(feature = "settings-window")]
+    OpenSettings,
+    /// 退出程序
+    Quit,
+}
+
+impl TrayCommand {
+    /// 菜单中的所有命令（按显示顺序）
+    const MENU: &[TrayCommand] = &[
+        TrayCommand::Refresh,
+        TrayCommand::ToggleVisibility,
+        TrayCommand::SwitchSource,
+        #[cfg(feature = "settings-window")]
+        TrayCommand::OpenSettings,
+        TrayCommand::Quit,
+    ];
+
+    /// 菜单项文字
+    pub fn label(&self) -> &'static str {
+        match self {
+            TrayCommand::Refresh => "刷新歌词",
+            TrayCommand::ToggleVisibility => "显示/隐藏",
+            TrayCommand::SwitchSource => "切换歌词源",
+            #[cfg(feature = "settings-window")]
+            TrayCommand::OpenSettings => "设置...",
+            TrayCommand::Quit => "退出",
+        }
+    }
+
+    /// 菜单项编号（0 表示未选择，因此从 1 开始）
+    fn menu_id(&self) -> usize {
+        Self::MENU.iter().position(|command| command == self).map_or(0, |index| index + 1)
+    }
+
+    /// 根据菜单项编号查找命令
+    fn from_menu_id(id: usize) -> Option<TrayCommand> {
+        id.checked_sub(1).and_then(|index| Self::MENU.get(index)).copied()
+    }
+}
+
+/// 通知区域图标，左键切换显示，右键弹出菜单
+///
+/// 回调消息由隐藏的窗口接收，命令暂存后由 UI 线程通过 `take_commands` 取走；
+/// 该窗口同时接收供电状态变化的广播
+pub struct TrayIcon {
+    hwnd: HWND,
+}
+
+impl TrayIcon {
+    /// 创建接收回调消息的隐藏窗口并添加托盘图标
+    pub fn new(tooltip: &str) -> std::result::Result<Self, String> {
+        unsafe {
+            let instance: HINSTANCE = GetModuleHandleW(None)
+                .map_err(|e| format!("获取模块句柄失败: {}", e))?
+                .into();
+            let class_name = w!("TaskbarLrcTray");
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(tray_window_proc),
+                hInstance: instance,
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            // 重复注册会失败，但已注册的类仍可使用
+            RegisterClassW(&class);
+
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class_name,
+                w!("TaskbarLrcTray"),
+                WINDOW_STYLE::default(),
+                0, 0, 0, 0,
+                None,
+                None,
+                Some(instance),
+                None,
+            ).map_err(|e| format!("创建托盘消息窗口失败: {}", e))?;
+
+            let tray = Self { hwnd };
+            remember_tooltip(tooltip);
+            if !add_icon(hwnd, tooltip) {
+                return Err("添加托盘图标失败".to_string());
+            }
+
+            Ok(tray)
+        }
+    }
+
+    /// 更新鼠标悬停时的提示文字（如当前歌曲）
+    pub fn set_tooltip(&self, tooltip: &str) {
+        remember_tooltip(tooltip);
+        let mut data = notify_data(self.hwnd);
+        data.uFlags = NIF_TIP;
+        data.szTip = tooltip_buffer(tooltip);
+        unsafe {
+            let _ = Shell_NotifyIconW(NIM_MODIFY, &data);
+        }
+    }
+
+    /// 取走自上次调用以来用户选择的命令
+    pub fn take_commands(&self) -> Vec<TrayCommand> {
+        PENDING_COMMANDS.lock()
+            .map(|mut commands| std::mem::take(&mut *commands))
+            .unwrap_or_default()
+    }
+
+}
+
+impl Drop for TrayIcon {
+    fn drop(&mut self) {
+        let data = notify_data(self.hwnd);
+        unsafe {
+            let _ = Shell_NotifyIconW(NIM_DELETE, &data);
+            let _ = DestroyWindow(self.hwnd);
+        }
+    }
+}
+
+/// 标识托盘图标的基础数据
+fn notify_data(hwnd: HWND) -> NOTIFYICONDATAW {
+    NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: hwnd,
+        uID: TRAY_ICON_ID,
+        ..Default::default()
+    }
+}
+
+/// 向通知区域添加图标
+fn add_icon(hwnd: HWND, tooltip: &str) -> bool {
+    let mut data = notify_data(hwnd);
+    data.uFlags = NIF_ICON | NIF_MESSAGE | NIF_TIP;
+    data.uCallbackMessage = WM_TRAY_ICON;
+    data.szTip = tooltip_buffer(tooltip);
+    unsafe {
+        data.hIcon = LoadIconW(None, IDI_APPLICATION).unwrap_or_default();
+        Shell_NotifyIconW(NIM_ADD, &data).as_bool()
+    }
+}
+
+/// 记录当前的提示文字
+fn remember_tooltip(tooltip: &str) {
+    if let Ok(mut current) = CURRENT_TOOLTIP.lock() {
+        *current = tooltip.to_string();
+    }
+}
+
+/// 资源管理器（任务栏）重新创建时广播的消息
+fn taskbar_created_message() -> u32 {
+    static MESSAGE: OnceLock<u32> = OnceLock::new();
+    *MESSAGE.get_or_init(|| unsafe { RegisterWindowMessageW(w!("TaskbarCreated")) })
+}
+
+/// 把提示文字转换为定长 UTF-16 缓冲区，过长时截断
+fn tooltip_buffer(text: &str) -> [u16; TOOLTIP_LEN] {
+    let mut buffer = [0u16; TOOLTIP_LEN];
+    for (slot, unit) in buffer.iter_mut().zip(text.encode_utf16().take(TOOLTIP_LEN - 1)) {
+        *slot = unit;
+    }
+    buffer
+}
+
+/// 记录一条待处理的命令
+fn push_command(command: TrayCommand) {
+    if let Ok(mut commands) = PENDING_COMMANDS.lock() {
+        commands.push(command);
+    }
+}
+
+/// 在鼠标位置弹出托盘菜单，返回选择的命令
+unsafe fn show_menu(hwnd: HWND) -> Option<TrayCommand> {
+    unsafe {
+        let menu = CreatePopupMenu().ok()?;
+        for &command in TrayCommand::MENU {
+            if command == TrayCommand::Quit {
+                let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+            }
+            let label: Vec<u16> = command.label().encode_utf16().chain(std::iter::once(0)).collect();
+            let _ = AppendMenuW(menu, MF_STRING, command.menu_id(), PCWSTR(label.as_ptr()));
+        }
+
+        let mut cursor = POINT::default();
+        let _ = GetCursorPos(&mut cursor);
+        // 菜单窗口需要位于前台，否则点击菜单外部时不会关闭
+        let _ = SetForegroundWindow(hwnd);
+        let selected = TrackPopupMenu(
+            menu,
+            TPM_RETURNCMD | TPM_NONOTIFY | TPM_RIGHTBUTTON,
+            cursor.x,
+            cursor.y,
+            None,
+            hwnd,
+            None,
+        );
+        let _ = DestroyMenu(menu);
+
+        TrayCommand::from_menu_id(selected.0 as usize)
+    }
+}
+
+/// 托盘消息窗口的窗口过程
+unsafe extern "system" fn tray_window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    unsafe {
+        // 资源管理器重启后原有图标已丢失，需要重新添加
+        if msg == taskbar_created_message() {
+            let tooltip = CURRENT_TOOLTIP.lock().map(|tooltip| tooltip.clone()).unwrap_or_default();
+            if !add_icon(hwnd, &tooltip) {
+                warn!("资源管理器重启后重新添加托盘图标失败");
+            }
+            return LRESULT(0);
+        }
+        // 接通或断开电源，由 UI 线程重新检查是否省电
+        if msg == WM_POWERBROADCAST && wparam.0 as u32 == PBT_APMPOWERSTATUSCHANGE {
+            crate::system::notify_power_status_changed();
+        }
+        if msg != WM_TRAY_ICON {
+            return DefWindowProcW(hwnd, msg, wparam, lparam);
+        }
+
+        // 低位为触发的鼠标消息
+        match (lparam.0 as u32) & 0xFFFF {
+            WM_LBUTTONUP => push_command(TrayCommand::ToggleVisibility),
+            WM_RBUTTONUP | WM_CONTEXTMENU => {
+                if let Some(command) = show_menu(hwnd) {
+                    push_command(command);
+                }
+            }
+            _ => {}
+        }
+        LRESULT(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_ids_round_trip() {
+        for &command in TrayCommand::MENU {
+            assert_eq!(TrayCommand::from_menu_id(command.menu_id()), Some(command));
+        }
+        // 0 表示关闭菜单时没有选择
+        assert_eq!(TrayCommand::from_menu_id(0), None);
+        assert_eq!(TrayCommand::from_menu_id(TrayCommand::MENU.len() + 1), None);
+    }
+
+    #[test]
+    fn test_tooltip_is_truncated_and_terminated() {
+        let buffer = tooltip_buffer("周杰伦 - 晴天");
+        assert_eq!(String::from_utf16_lossy(&buffer[..8]), "周杰伦 - 晴天");
+        assert_eq!(buffer[8], 0);
+
+        let long = "a".repeat(200);
+        let buffer = tooltip_buffer(&long);
+        assert_eq!(buffer[TOOLTIP_LEN - 2], u16::from(b'a'));
+        assert_eq!(buffer[TOOLTIP_LEN - 1], 0);
+    }
+}
@@ -1,26 +1,153 @@
 use crate::*;
 use crate::window::WindowManager;
-use crate::graphics::Renderer;
+use crate::graphics::{EdgeSide, FrameDecorations, FrameParams, Renderer, RenderStyle, SecondaryPlacement};
 use crate::font::FontManager;
-use crate::system::SystemManager;
-use crate::window::ensure_taskbar_hidden;
+use crate::system::{is_on_battery, take_power_status_changed, PowerMode, SystemManager, TaskbarOrientation};
+use crate::window::{compute_vertical_widget_position, compute_widget_position, ensure_taskbar_hidden, PositionMode, PositionOffsets, TopmostStats, WidgetAnchor};
+use std::cell::Cell;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
-use crate::lyrics::LyricsData;
-use crate::system::MediaInfo;
+use crate::lyrics::{format_song_display, LyricsData, LyricsDisplayMode, WordTimedLine, DEFAULT_SONG_DISPLAY_TEMPLATE};
+use crate::system::{MediaControl, MediaInfo, PlaybackStatus};
 
 use windows::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
 
+/// 歌词滚动模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ScrollMode {
+    /// 文本超出宽度时随歌词行滚动一次
+    #[default]
+    Once,
+    /// 跑马灯：文本前加 ♪ 图标，无论长短都循环从右向左滚动
+    Marquee,
+}
+
+/// 播放时间显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeDisplay {
+    /// 不显示
+    #[default]
+    Off,
+    /// 已播放时间，如 `1:23`
+    Elapsed,
+    /// 剩余时间，如 `-2:10`（时长未知时不显示）
+    Remaining,
+}
+
+/// 暂停后恢复播放时的滚动行为
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollResume {
+    /// 从暂停处继续滚动
+    #[default]
+    Continue,
+    /// 从头开始滚动
+    Restart,
+}
+
+/// 歌词没有时间轴（纯文本）时的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnsyncedDisplay {
+    /// 显示歌曲信息
+    #[default]
+    SongInfo,
+    /// 把整首歌词合并为一行，在歌曲时长内缓慢滚动
+    FullText,
+    /// 把整首歌词合并为一行，滚动位置跟随播放进度（开头对应 0，结尾对应歌曲时长）
+    Ticker,
+}
+
+/// 前奏（第一句歌词开始之前）的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntroDisplay {
+    /// 显示“暂无歌词”提示
+    #[default]
+    Placeholder,
+    /// 显示歌曲信息
+    SongInfo,
+    /// 提前显示即将开始的第一句歌词
+    FirstLine,
+    /// 不显示任何内容
+    Blank,
+}
+
+/// 窗口宽度模式（宽度以 100% 缩放为基准，按 `scale_factor` 换算为物理像素）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidthMode {
+    /// 固定宽度
+    Fixed(u32),
+    /// 按当前显示的文本调整宽度，限制在 `min` ~ `max` 之间，超出 `max` 时照常滚动
+    AutoFit { min: u32, max: u32 },
+}
+
+impl Default for WidthMode {
+    fn default() -> Self {
+        WidthMode::Fixed(280)
+    }
+}
+
+/// 自动宽度两次调整之间的最小间隔，避免歌词快速切换时窗口反复伸缩
+const AUTO_FIT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 自动宽度模式下容纳文本所需的窗口宽度（物理像素）
+fn auto_fit_width(text_width: f32, padding: f32, min: u32, max: u32, scale_factor: f64) -> u32 {
+    let min = (min as f64 * scale_factor).round() as u32;
+    let max = ((max as f64 * scale_factor).round() as u32).max(min);
+    ((text_width + padding).ceil() as u32).clamp(min, max)
+}
+
+/// 显示文本转换钩子，在绘制和计算滚动宽度之前作用于最终显示的文本
+///
+/// 可用于繁简转换、拼音标注等自定义处理，例如：
+///
+/// ```ignore
+/// widget.text_transform = Some(Box::new(|text: &str| text.replace('後', '后')));
+/// ```
+pub type TextTransform = Box<dyn Fn(&str) -> String + Send + Sync>;
+
+/// 内置的示例转换：原样返回文本
+pub fn identity_transform() -> TextTransform {
+    Box::new(str::to_string)
+}
+
+/// 窗口左右两侧作为上一首/下一首点击区域的宽度比例
+const MEDIA_CONTROL_EDGE_RATIO: f64 = 0.2;
+
 /// 任务栏小组件的核心结构体
 pub struct TaskbarWidget {
     pub window_manager: WindowManager,
     pub renderer: Renderer,
     pub font_manager: FontManager,
     pub system_manager: SystemManager,
+    /// 配置的窗口宽度（实际宽度见 `effective_width`）
     pub window_width: u32,
     pub window_height: u32,
+    /// 竖向任务栏限制的最大宽度（横向任务栏时为 None）
+    taskbar_width_limit: Option<u32>,
+    /// 窗口宽度模式（修改请使用 `set_width_mode`）
+    pub width_mode: WidthMode,
+    /// 最近一次自动调整宽度的时间
+    width_fitted_at: Option<Instant>,
+    /// 窗口所在显示器的缩放比例
+    pub scale_factor: f64,
     pub show_on_left: bool,
+    /// 窗口定位方式（跟随任务栏或固定在屏幕坐标）
+    pub position_mode: PositionMode,
+    /// 窗口定位的偏移量（以 100% 缩放为基准，定位时按 `scale_factor` 换算为物理像素）
+    pub position_offsets: PositionOffsets,
     pub last_position_update: Instant,
     pub position_update_pending: bool,
+    /// 任务栏布局变化后重新定位窗口的最小间隔
+    pub reposition_interval: Duration,
+    /// 焦点/层级变化触发重新置顶的最小间隔（与重新定位分开节流）
+    pub topmost_interval: Duration,
+    /// 最近一次由系统事件触发置顶的时间
+    last_event_topmost: Option<Instant>,
+    /// 节流期间收到的置顶请求，留到下一帧处理
+    topmost_pending: bool,
+    /// 设置系统事件钩子失败的原因（Some 时改为定时轮询刷新位置和置顶）
+    pub event_hook_error: Option<InitError>,
+    /// 最近一次轮询刷新位置的时间
+    polled_at: Option<Instant>,
     pub last_taskbar_rect: RECT,
     pub last_notify_rect: RECT,
     pub current_lyrics: Option<LyricsData>,
@@ -28,8 +155,92 @@ pub struct TaskbarWidget {
     pub last_lyrics_update: Instant,
     pub lyrics_loading: bool,
     pub current_lyrics_line: Option<String>,
+    /// 下一句歌词
+    pub next_lyrics_line: Option<String>,
+    /// 当前歌词行在时间轴中的索引（重复行重新触发时，文本相同也据此重新开始滚动）
+    pub current_line_index: Option<usize>,
     pub last_rendered_content: String,
+    /// 上次绘制时所有影响画面的状态的哈希（文本、样式、滚动等）
+    last_render_hash: u64,
+    /// 状态同步时已解析好的显示文本，供下一次绘制直接使用（绘制后取走）
+    pub resolved_display_text: Option<String>,
     pub content_changed: bool,
+    /// 加载超过该秒数后显示较慢提示（0 表示不提示）
+    pub loading_timeout_secs: u64,
+    /// 是否持续强制窗口置顶（关闭后仅在创建时置顶一次）
+    pub keep_topmost: bool,
+    /// 置顶操作统计（事件钩子回调中只能拿到共享引用，因此用 Cell）
+    topmost_stats: Cell<TopmostStats>,
+    /// 预览桌面（悬停“显示桌面”按钮）时是否暂时隐藏窗口
+    pub hide_during_peek: bool,
+    /// 当前是否正在预览桌面
+    pub desktop_peek_active: bool,
+    /// 用户是否手动隐藏了窗口（如通过托盘图标）
+    pub user_hidden: bool,
+    /// 暂停时是否隐藏窗口（关闭后以 `paused_opacity` 变暗显示）
+    pub hide_when_paused: bool,
+    /// 暂停时的不透明度倍数（仅在 `hide_when_paused` 关闭时生效）
+    pub paused_opacity: f32,
+    /// 只在有带时间轴的歌词时显示（没有歌词时即使正在播放也隐藏）
+    pub require_lyrics: bool,
+    /// 播放状态为 Unknown 时沿用上一个明确状态的最长时间（0 表示立即按 Unknown 隐藏）
+    pub unknown_status_grace: Duration,
+    /// 最近一次明确的播放状态
+    last_known_status: PlaybackStatus,
+    /// 播放状态开始连续为 Unknown 的时间
+    unknown_status_since: Option<Instant>,
+    /// 切歌后先显示歌曲信息的秒数（0 表示直接显示歌词）
+    pub title_display_secs: u64,
+    /// 最近一次切歌的时间
+    pub song_changed_at: Option<Instant>,
+    /// 歌词加载完成后短暂显示匹配到的歌曲和来源，便于发现匹配错误
+    pub show_match_info: bool,
+    /// 最近一次歌词加载完成的时间
+    pub lyrics_loaded_at: Option<Instant>,
+    /// 歌曲信息显示模板，支持 `{title}` 和 `{artist}` 占位符
+    pub song_display_template: String,
+    /// 渲染样式（文字和背景颜色）
+    pub style: RenderStyle,
+    /// 后台服务不可用时的错误信息
+    pub service_error: Option<String>,
+    /// 播放时间显示方式
+    pub time_display: TimeDisplay,
+    /// 播放时间显示在哪一侧
+    pub time_display_side: EdgeSide,
+    /// 上次绘制的播放时间文本
+    pub last_rendered_time_label: Option<String>,
+    /// 是否在底部显示播放进度条
+    pub show_progress_bar: bool,
+    /// 上次绘制的进度条宽度（像素），用于判断是否需要重绘
+    pub last_rendered_progress_px: Option<u32>,
+    /// 纯文本歌词的显示方式
+    pub unsynced_display: UnsyncedDisplay,
+    /// 前奏期间的显示方式
+    pub intro_display: IntroDisplay,
+    /// 播放位置是否还在第一句歌词之前
+    pub before_first_line: bool,
+    /// 窗口足够高时在当前歌词下方变暗显示下一句
+    pub show_next_line: bool,
+    /// 有逐字歌词时按演唱进度高亮当前行已唱的部分（没有 yrc 时照常显示逐行歌词）
+    pub karaoke_highlight: bool,
+    /// 当前歌词解析出的逐字时间轴
+    word_timed_lines: Vec<WordTimedLine>,
+    /// 解析 `word_timed_lines` 所用的 yrc 原文，用于判断歌词是否更换
+    word_timed_source: Option<String>,
+    /// 省电策略（自动模式下使用电池供电时降低帧率并关闭下一句预览）
+    pub power_mode: PowerMode,
+    /// 最近一次检查时是否使用电池供电
+    pub on_battery: bool,
+    /// 最近一次检查供电状态的时间
+    power_checked_at: Option<Instant>,
+    /// 歌词显示语言（当前歌词没有该语言时显示原文）
+    pub display_mode: LyricsDisplayMode,
+    /// 最近一次切换显示语言的时间，用于短暂提示新的语言
+    display_mode_changed_at: Option<Instant>,
+    /// 显示歌词的简繁转换方式（在 `text_transform` 之前应用）
+    pub chinese_conversion: ChineseConversion,
+    /// 显示文本转换钩子（None 表示不转换）
+    pub text_transform: Option<TextTransform>,
     
     // 滚动相关字段
     pub scroll_offset: f32,
@@ -38,6 +249,15 @@ pub struct TaskbarWidget {
     pub scroll_start_time: Option<Instant>,
     pub text_width: f32,
     pub is_scrolling: bool,
+    pub scroll_mode: ScrollMode,
+    /// 暂停后恢复播放时的滚动行为
+    pub scroll_resume: ScrollResume,
+    /// 滚动暂停的时间（暂停播放时冻结滚动）
+    pub scroll_paused_at: Option<Instant>,
+    /// 跑马灯滚动速度（像素/秒）
+    pub marquee_speed: f32,
+    /// 跑马灯当前滚动的文本
+    pub marquee_text: String,
 }
 
 impl TaskbarWidget {
@@ -49,9 +269,21 @@ impl TaskbarWidget {
             system_manager: SystemManager::new(),
             window_width: 280,
             window_height: 40,
+            taskbar_width_limit: None,
+            width_mode: WidthMode::default(),
+            width_fitted_at: None,
+            scale_factor: 1.0,
             show_on_left: false,
+            position_mode: PositionMode::TaskbarRelative,
+            position_offsets: PositionOffsets::default(),
             last_position_update: Instant::now(),
             position_update_pending: false,
+            reposition_interval: Duration::from_millis(200),
+            topmost_interval: Duration::from_millis(100),
+            last_event_topmost: None,
+            topmost_pending: false,
+            event_hook_error: None,
+            polled_at: None,
             last_taskbar_rect: RECT::default(),
             last_notify_rect: RECT::default(),
             current_lyrics: None,
@@ -59,8 +291,50 @@ impl TaskbarWidget {
             last_lyrics_update: Instant::now(),
             lyrics_loading: false,
             current_lyrics_line: None,
+            next_lyrics_line: None,
+            current_line_index: None,
             last_rendered_content: String::new(),
+            last_render_hash: 0,
+            resolved_display_text: None,
             content_changed: true, // 初始时需要绘制
+            loading_timeout_secs: 15,
+            keep_topmost: true,
+            topmost_stats: Cell::new(TopmostStats::default()),
+            hide_during_peek: true,
+            desktop_peek_active: false,
+            user_hidden: false,
+            hide_when_paused: true,
+            paused_opacity: 0.5,
+            require_lyrics: false,
+            unknown_status_grace: Duration::from_secs(2),
+            last_known_status: PlaybackStatus::Unknown,
+            unknown_status_since: None,
+            title_display_secs: 0,
+            song_changed_at: None,
+            show_match_info: false,
+            lyrics_loaded_at: None,
+            song_display_template: DEFAULT_SONG_DISPLAY_TEMPLATE.to_string(),
+            style: RenderStyle::default(),
+            service_error: None,
+            time_display: TimeDisplay::Off,
+            time_display_side: EdgeSide::Right,
+            last_rendered_time_label: None,
+            show_progress_bar: false,
+            last_rendered_progress_px: None,
+            unsynced_display: UnsyncedDisplay::SongInfo,
+            intro_display: IntroDisplay::Placeholder,
+            before_first_line: false,
+            show_next_line: false,
+            karaoke_highlight: false,
+            word_timed_lines: Vec::new(),
+            word_timed_source: None,
+            power_mode: PowerMode::Auto,
+            on_battery: false,
+            power_checked_at: None,
+            display_mode: LyricsDisplayMode::Original,
+            display_mode_changed_at: None,
+            chinese_conversion: ChineseConversion::None,
+            text_transform: None,
             
             // 滚动相关字段初始化
             scroll_offset: 0.0,
@@ -69,17 +343,29 @@ impl TaskbarWidget {
             scroll_start_time: None,
             text_width: 0.0,
             is_scrolling: false,
+            scroll_mode: ScrollMode::Once,
+            scroll_resume: ScrollResume::Continue,
+            scroll_paused_at: None,
+            marquee_speed: 40.0,
+            marquee_text: String::new(),
         }
     }
 
     /// 初始化小组件
-    pub fn initialize(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) -> std::result::Result<(), String> {
+    pub fn initialize(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) -> InitResult<()> {
+        // 没有字体时无法显示任何内容
+        if !self.font_manager.has_font() {
+            return Err(InitError::FontLoad);
+        }
+
         // 查找任务栏并获取高度
         self.system_manager.find_taskbar_handle()?;
         
-        // 根据任务栏高度调整窗口高度
-        let taskbar_height = self.system_manager.get_taskbar_height();
-        self.window_height = taskbar_height.max(32).min(100);
+        // 根据任务栏高度调整窗口高度（竖向任务栏的高度是整个屏幕，保持默认高度）
+        if self.system_manager.get_taskbar_orientation() == TaskbarOrientation::Horizontal {
+            let taskbar_height = self.system_manager.get_taskbar_height();
+            self.window_height = taskbar_height.max(32).min(100);
+        }
         
         // 创建窗口
         self.window_manager.create_window(
@@ -98,10 +384,11 @@ impl TaskbarWidget {
         self.last_notify_rect = self.system_manager.get_notify_area_rect();
         
         // 调整窗口位置
-        self.adjust_window_position()?;
+        self.adjust_window_position().map_err(InitError::WindowCreation)?;
         
-        // 设置系统事件钩子
-        self.system_manager.setup_event_hook()?;
+        // 设置系统事件钩子，失败时不影响显示，改为定时轮询刷新位置和置顶
+        self.event_hook_error = self.system_manager.setup_event_hook().err();
+        self.polled_at = None;
         
         // 确保窗口在最上层
         self.ensure_topmost();
@@ -114,6 +401,14 @@ impl TaskbarWidget {
         let _window = self.window_manager.get_window()
             .ok_or("窗口未创建")?;
         
+        // 固定位置不跟踪任务栏，但仍保持置顶
+        if let PositionMode::Fixed(x, y) = self.position_mode {
+            self.taskbar_width_limit = None;
+            self.window_manager.set_position(x, y, self.window_width, self.window_height)?;
+            self.ensure_topmost();
+            return Ok(());
+        }
+        
         // 重新获取任务栏信息，确保使用最新数据
         let _ = self.system_manager.find_taskbar_handle();
             
@@ -139,20 +434,44 @@ impl TaskbarWidget {
         self.last_notify_rect = notify_rect;
         
         // 计算窗口位置
-        let new_x = if self.show_on_left {
-            taskbar_rect.left + 60
-        } else {
-            if notify_rect.left != 0 {
-                notify_rect.left - self.window_width as i32 - 5
-            } else {
-                taskbar_rect.right - self.window_width as i32 - 60
+        let anchor = if self.show_on_left { WidgetAnchor::Left } else { WidgetAnchor::Right };
+        let is_vertical = self.system_manager.get_taskbar_orientation() == TaskbarOrientation::Vertical;
+        // 竖向任务栏：窗口不超出任务栏宽度（只限制实际宽度，配置的宽度保持不变，换回横向后恢复）
+        let width_limit = is_vertical.then(|| (taskbar_rect.right - taskbar_rect.left).max(1) as u32);
+        if width_limit != self.taskbar_width_limit {
+            self.taskbar_width_limit = width_limit;
+            self.mark_content_changed();
+        }
+        let width = self.effective_width();
+        if is_vertical {
+            // 叠放在时钟上方
+            let (new_x, new_y) = compute_vertical_widget_position(
+                taskbar_rect,
+                notify_rect,
+                width,
+                self.window_height,
+                anchor,
+                &self.position_offsets.scaled(self.scale_factor),
+            );
+            self.window_manager.set_position(new_x, new_y, width, self.window_height)?;
+            self.ensure_topmost();
+            if let Some(window) = self.window_manager.get_window() {
+                ensure_taskbar_hidden(window);
             }
-        };
-        
-        let new_y = taskbar_rect.top;
+            return Ok(());
+        }
+        let task_list_rect = self.system_manager.get_task_list_rect();
+        let (new_x, new_y) = compute_widget_position(
+            taskbar_rect,
+            notify_rect,
+            task_list_rect,
+            width,
+            anchor,
+            &self.position_offsets.scaled(self.scale_factor),
+        );
         
         // 使用窗口管理器设置位置
-        self.window_manager.set_position(new_x, new_y, self.window_width, self.window_height)?;
+        self.window_manager.set_position(new_x, new_y, width, self.window_height)?;
         
         // 位置更新后立即确保最上层，防止被其他窗口遮挡
         self.ensure_topmost();
@@ -165,9 +484,14 @@ impl TaskbarWidget {
         Ok(())
     }
 
-    /// 确保窗口始终在最上层
+    /// 确保窗口始终在最上层（`keep_topmost` 关闭时只保证任务栏图标隐藏）
     pub fn ensure_topmost(&self) {
-        self.window_manager.ensure_topmost();
+        let changed = self.keep_topmost.then(|| self.window_manager.ensure_topmost()).flatten();
+        if let Some(changed) = changed {
+            let mut stats = self.topmost_stats.get();
+            stats.record(changed);
+            self.topmost_stats.set(stats);
+        }
         
         // 同时确保任务栏图标隐藏
         if let Some(window) = self.window_manager.get_window() {
@@ -175,6 +499,54 @@ impl TaskbarWidget {
         }
     }
 
+    /// 实际的窗口宽度：配置的宽度，竖向任务栏上不超过任务栏宽度
+    pub fn effective_width(&self) -> u32 {
+        self.taskbar_width_limit.map_or(self.window_width, |limit| self.window_width.min(limit))
+    }
+
+    /// 点击位置（窗口内的横坐标）对应的播放控制：左侧上一首，右侧下一首，中间播放/暂停
+    pub fn media_control_at(&self, x: f64) -> MediaControl {
+        let width = self.effective_width() as f64;
+        let edge = width * MEDIA_CONTROL_EDGE_RATIO;
+        if x < edge {
+            MediaControl::Previous
+        } else if x >= width - edge {
+            MediaControl::Next
+        } else {
+            MediaControl::TogglePlayPause
+        }
+    }
+
+    /// 系统事件（焦点、层级变化）触发的置顶，按 `topmost_interval` 节流
+    pub fn request_topmost(&mut self) {
+        if self.topmost_due(Instant::now()) {
+            self.ensure_topmost();
+        }
+    }
+
+    /// 处理节流期间积压的置顶请求
+    pub fn flush_pending_topmost(&mut self) {
+        if self.topmost_pending {
+            self.request_topmost();
+        }
+    }
+
+    /// 是否可以立即置顶，节流期间记下待处理的请求
+    fn topmost_due(&mut self, now: Instant) -> bool {
+        if self.last_event_topmost.is_some_and(|at| now.duration_since(at) < self.topmost_interval) {
+            self.topmost_pending = true;
+            return false;
+        }
+        self.last_event_topmost = Some(now);
+        self.topmost_pending = false;
+        true
+    }
+
+    /// 置顶操作统计，用于判断是否需要 `keep_topmost`
+    pub fn topmost_stats(&self) -> TopmostStats {
+        self.topmost_stats.get()
+    }
+
     /// 绘制内容
     pub fn draw_content(&mut self) -> std::result::Result<(), String> {
         // 先检查并更新窗口可见性
@@ -188,27 +560,37 @@ impl TaskbarWidget {
         // 更新滚动状态
         self.update_scroll();
         
-        // 获取要显示的歌词文本
-        let text = self.get_display_lyrics();
-        let margin = (self.window_height as f32 * 0.25) as u32;
-        let font_size = (self.window_height as f32 * 0.4) as f32; // 稍微小一点适应歌词
-
-        // 使用黑色
-        let color = 0xFF000000;
+        // 获取要显示的歌词文本（优先使用状态同步时解析好的文本，系统触发的重绘则重新解析）
+        let text = self.resolved_display_text.take()
+            .unwrap_or_else(|| self.get_display_lyrics());
+        self.fit_width_to_text(&text);
+        let margin = self.style.margin_for(self.window_height);
+        let font_size = self.style.font_size_for(self.window_height);
         
         // 获取滚动偏移量
         let scroll_offset = self.get_scroll_offset();
         
-        let result = self.renderer.draw_frame(
-            &text,
-            &self.font_manager,
+        let time_label = self.get_time_label();
+        let next_line = self.secondary_row();
+        let decorations = FrameDecorations {
+            edge_label: time_label.as_deref().map(|label| (label, self.time_display_side)),
+            progress: self.get_progress(),
+            secondary_line: next_line.as_ref().map(|(line, _)| line.as_str()),
+            secondary_placement: next_line.as_ref().map(|(_, placement)| *placement).unwrap_or_default(),
+            highlight: self.karaoke_fraction(&text),
+        };
+        
+        let style = self.current_render_style();
+        let frame = FrameParams {
+            text: &text,
             font_size,
-            color,
-            self.window_width,
-            self.window_height,
+            style: &style,
             margin,
             scroll_offset,
-        );
+            force_scroll: self.scroll_mode == ScrollMode::Marquee,
+            decorations,
+        };
+        let result = self.renderer.draw_frame(&self.font_manager, self.effective_width(), self.window_height, &frame);
         
         // 绘制完成后标记重绘完成
         if result.is_ok() {
@@ -218,12 +600,247 @@ impl TaskbarWidget {
         result
     }
 
+    /// 设置窗口宽度模式，固定宽度立即生效，自动宽度在下一次绘制时按文本调整
+    pub fn set_width_mode(&mut self, mode: WidthMode) {
+        self.width_mode = mode;
+        self.width_fitted_at = None;
+        if let WidthMode::Fixed(width) = mode {
+            let width = (width as f64 * self.scale_factor).round() as u32;
+            self.apply_width(width);
+        }
+        self.mark_content_changed();
+    }
+
+    /// 自动宽度模式下按显示文本调整窗口宽度，返回是否调整了宽度
+    ///
+    /// 滚动已经开始后不调整（避免窗口伸缩打乱滚动动画），两次调整之间至少间隔 `AUTO_FIT_DEBOUNCE`
+    fn fit_width_to_text(&mut self, text: &str) -> bool {
+        let WidthMode::AutoFit { min, max } = self.width_mode else {
+            return false;
+        };
+        if self.is_scrolling && self.scroll_offset > 0.0 {
+            return false;
+        }
+        if self.width_fitted_at.is_some_and(|fitted_at| fitted_at.elapsed() < AUTO_FIT_DEBOUNCE) {
+            return false;
+        }
+
+        let text_width = self.calculate_text_width(text);
+        let padding = self.window_height as f32 * 0.5;
+        let width = auto_fit_width(text_width, padding, min, max, self.scale_factor);
+        if width == self.window_width {
+            return false;
+        }
+
+        self.width_fitted_at = Some(Instant::now());
+        self.apply_width(width);
+
+        // 刚开始的滚动按新宽度重新计算（文本可能已经放得下）
+        if self.is_scrolling && let Some(line) = self.current_lyrics_line.clone() {
+            self.init_scroll_for_text(&line, self.scroll_target_time);
+        }
+        true
+    }
+
+    /// 更新窗口宽度并保持贴靠通知区域
+    fn apply_width(&mut self, width: u32) {
+        if width == self.window_width {
+            return;
+        }
+        self.window_width = width.max(1);
+        // 任务栏位置没变，但窗口宽度变了
+        self.reposition();
+    }
+
+    /// 强制按当前宽度和贴靠设置重新定位窗口
+    pub fn reposition(&mut self) {
+        self.last_taskbar_rect = RECT::default();
+        let _ = self.adjust_window_position();
+    }
+
+    /// 事件钩子不可用时轮询刷新位置和置顶的间隔
+    pub const POLLING_FALLBACK_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// 是否因事件钩子设置失败而使用轮询刷新
+    pub fn uses_polling_fallback(&self) -> bool {
+        self.event_hook_error.is_some()
+    }
+
+    /// 是否到了轮询刷新的时间（未使用轮询时始终为 false）
+    fn polling_due(&mut self, now: Instant) -> bool {
+        if !self.uses_polling_fallback()
+            || self.polled_at.is_some_and(|at| now.duration_since(at) < Self::POLLING_FALLBACK_INTERVAL)
+        {
+            return false;
+        }
+        self.polled_at = Some(now);
+        true
+    }
+
+    /// 事件钩子不可用时定期重新定位窗口并置顶，代替钩子回调
+    pub fn poll_position_fallback(&mut self) {
+        if !self.polling_due(Instant::now()) {
+            return;
+        }
+        let _ = self.adjust_window_position();
+        self.ensure_topmost();
+    }
+
+    /// 正常绘制时的帧间隔（20fps）
+    pub const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+    /// 省电时的帧间隔（10fps）
+    pub const POWER_SAVING_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+    /// 兜底重新检查供电状态的间隔（托盘窗口创建失败时收不到供电状态变化通知）
+    const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// 收到供电状态变化通知时重新检查供电状态（另以较长间隔兜底），切换电源时返回 true
+    pub fn update_power_source(&mut self) -> bool {
+        let now = Instant::now();
+        let notified = take_power_status_changed();
+        if !notified && self.power_checked_at.is_some_and(|at| now.duration_since(at) < Self::POWER_CHECK_INTERVAL) {
+            return false;
+        }
+        self.power_checked_at = Some(now);
+
+        let on_battery = is_on_battery();
+        let changed = on_battery != self.on_battery;
+        if changed {
+            self.on_battery = on_battery;
+            self.mark_content_changed();
+        }
+        changed
+    }
+
+    /// 当前是否处于省电状态
+    pub fn is_power_saving(&self) -> bool {
+        self.power_mode.is_power_saving(self.on_battery)
+    }
+
+    /// 播放或滚动时的帧间隔（省电时降低帧率）
+    pub fn frame_interval(&self) -> Duration {
+        if self.is_power_saving() {
+            Self::POWER_SAVING_FRAME_INTERVAL
+        } else {
+            Self::FRAME_INTERVAL
+        }
+    }
+
+    /// 歌词更换后重新解析逐字时间轴（未开启高亮时不解析）
+    pub fn sync_word_timing(&mut self) {
+        let yrc = self.current_lyrics.as_ref()
+            .filter(|_| self.karaoke_highlight)
+            .and_then(|lyrics| lyrics.yrc.as_deref());
+        if yrc == self.word_timed_source.as_deref() {
+            return;
+        }
+
+        self.word_timed_source = yrc.map(str::to_string);
+        self.word_timed_lines = self.current_lyrics.as_ref()
+            .filter(|_| yrc.is_some())
+            .and_then(LyricsData::word_timed_lines)
+            .unwrap_or_default();
+    }
+
+    /// 当前行已唱部分的比例，仅在显示的正是逐字时间轴中的当前行时返回
+    fn karaoke_fraction(&self, text: &str) -> Option<f32> {
+        let current_line = self.current_lyrics_line.as_deref()
+            .filter(|_| self.karaoke_highlight && self.current_render_style().word_highlight)?;
+        if text != self.transform_text(current_line) {
+            return None;
+        }
+
+        let position_ms = self.current_media.as_ref()?.position?.as_millis() as u64;
+        self.word_timed_lines.iter()
+            .find(|line| line.contains(position_ms) && line.text().trim() == current_line.trim())
+            .map(|line| line.sung_fraction(position_ms))
+    }
+
+    /// 显示两行歌词所需的最小窗口高度（像素）
+    pub const NEXT_LINE_MIN_HEIGHT: u32 = 56;
+
+    /// 要在第二行预览的下一句歌词（未开启、省电、窗口不够高或当前没有显示歌词行时为 None）
+    pub fn next_line_preview(&self) -> Option<&str> {
+        self.next_line_layout()
+            .filter(|(_, placement)| *placement == SecondaryPlacement::Below)
+            .map(|(line, _)| line)
+    }
+
+    /// 要预览的下一句歌词及其位置：窗口足够高时显示在下方，否则并排显示在当前歌词右侧
+    /// （宽度放不下两句时由渲染器只绘制当前歌词；最后一句之后没有下一句，不会重复当前行）
+    pub fn next_line_layout(&self) -> Option<(&str, SecondaryPlacement)> {
+        if !self.show_next_line || self.is_power_saving() {
+            return None;
+        }
+        let placement = if self.window_height >= Self::NEXT_LINE_MIN_HEIGHT {
+            SecondaryPlacement::Below
+        } else {
+            SecondaryPlacement::Beside
+        };
+
+        // 只在正常显示歌词行时预览，提示信息和歌曲信息保持单行
+        let showing_line = self.is_showing_lyrics_line();
+        self.next_lyrics_line.as_deref()
+            .filter(|line| showing_line && !line.trim().is_empty())
+            .map(|line| (line, placement))
+    }
+
+    /// 是否正在正常显示歌词行（没有错误、加载提示或歌曲信息）
+    fn is_showing_lyrics_line(&self) -> bool {
+        self.service_error.is_none()
+            && !self.lyrics_loading
+            && !self.is_showing_song_title()
+            && self.current_lyrics_line.as_ref().is_some_and(|line| !line.trim().is_empty())
+    }
+
+    /// 双语模式下窗口足够高时原文和翻译分两行显示（原文在上，翻译在下，代替下一句预览）
+    fn is_bilingual_stacked(&self) -> bool {
+        self.effective_display_mode() == LyricsDisplayMode::Bilingual
+            && self.window_height >= Self::NEXT_LINE_MIN_HEIGHT
+    }
+
+    /// 双语分两行显示时第二行的翻译（当前行没有翻译或与原文相同时为 None）
+    fn bilingual_translation_row(&self) -> Option<String> {
+        if !self.is_bilingual_stacked() || !self.is_showing_lyrics_line() {
+            return None;
+        }
+        let current_line = self.current_lyrics_line.as_deref()?;
+        let position = self.current_media.as_ref().and_then(|media| media.position).unwrap_or(Duration::ZERO);
+        let translated = self.current_lyrics.as_ref()?
+            .line_for_mode(LyricsDisplayMode::Translated, current_line, position);
+        (translated != current_line).then_some(translated)
+    }
+
+    /// 第二行的内容（已转换）及位置：双语分两行显示时为翻译，否则为下一句预览
+    fn secondary_row(&self) -> Option<(String, SecondaryPlacement)> {
+        if let Some(translated) = self.bilingual_translation_row() {
+            return Some((self.transform_text(&translated), SecondaryPlacement::Below));
+        }
+        self.next_line_layout().map(|(line, placement)| (self.transform_text(line), placement))
+    }
+
+    /// 当前应使用的渲染样式（省电时关闭逐字高亮；暂停且不隐藏时变暗）
+    fn current_render_style(&self) -> RenderStyle {
+        let style = if self.is_power_saving() {
+            self.style.power_saving()
+        } else {
+            self.style
+        };
+
+        let is_paused = self.current_media.as_ref()
+            .is_some_and(|media| media.playback_status == PlaybackStatus::Paused);
+        if is_paused && !self.hide_when_paused {
+            style.dimmed(self.paused_opacity)
+        } else {
+            style
+        }
+    }
+
     /// 检查是否需要重新计算位置（防抖逻辑）
     pub fn should_update_position(&mut self) -> bool {
         let now = Instant::now();
         let duration_since_last = now.duration_since(self.last_position_update);
         
-        if duration_since_last >= Duration::from_millis(200) {
+        if duration_since_last >= self.reposition_interval {
             self.last_position_update = now;
             true
         } else if self.position_update_pending {
@@ -281,19 +898,54 @@ impl TaskbarWidget {
         }
     }
 
-    /// 检查是否应该显示窗口（根据播放状态）
+    /// 检查是否应该显示窗口（根据播放状态和桌面预览状态）
     pub fn should_show_window(&self) -> bool {
-        if let Some(media) = &self.current_media {
-            use crate::system::PlaybackStatus;
-            match media.playback_status {
-                PlaybackStatus::Playing => true,
-                PlaybackStatus::Paused | PlaybackStatus::Stopped | PlaybackStatus::Unknown => false,
+        if self.user_hidden || (self.hide_during_peek && self.desktop_peek_active) {
+            return false;
+        }
+
+        // 前奏、间奏等没有当前行的时段仍然显示，避免窗口频繁闪现
+        let has_lyrics = self.current_lyrics.as_ref()
+            .is_some_and(|lyrics| lyrics.is_synced && lyrics.has_any_content());
+        if self.require_lyrics && !has_lyrics {
+            return false;
+        }
+
+        match self.effective_playback_status() {
+            Some(PlaybackStatus::Playing) => true,
+            Some(PlaybackStatus::Paused) => !self.hide_when_paused,
+            Some(PlaybackStatus::Stopped | PlaybackStatus::Unknown) => false,
+            None => false, // 没有媒体信息时隐藏窗口
+        }
+    }
+
+    /// 同步媒体信息后记录播放状态，供 Unknown 宽限期使用
+    pub fn track_playback_status(&mut self) {
+        match self.current_media.as_ref().map(|media| &media.playback_status) {
+            Some(PlaybackStatus::Unknown) => {
+                self.unknown_status_since.get_or_insert_with(Instant::now);
             }
-        } else {
-            false // 没有媒体信息时隐藏窗口
+            Some(status) => {
+                self.last_known_status = status.clone();
+                self.unknown_status_since = None;
+            }
+            None => self.unknown_status_since = None,
         }
     }
 
+    /// 用于显示判断的播放状态：Unknown 在宽限期内沿用上一个明确状态
+    fn effective_playback_status(&self) -> Option<PlaybackStatus> {
+        let status = self.current_media.as_ref()?.playback_status.clone();
+        let in_grace = status == PlaybackStatus::Unknown
+            && self.unknown_status_since.is_some_and(|since| since.elapsed() < self.unknown_status_grace);
+        Some(if in_grace { self.last_known_status.clone() } else { status })
+    }
+
+    /// 重新检测桌面预览状态（`hide_during_peek` 关闭时不检测）
+    pub fn update_desktop_peek(&mut self) {
+        self.desktop_peek_active = self.hide_during_peek && self.system_manager.is_desktop_peek_active();
+    }
+
     /// 更新窗口显示状态（根据播放状态自动显示或隐藏）
     pub fn update_window_visibility(&self) {
         if self.should_show_window() {
@@ -306,16 +958,44 @@ impl TaskbarWidget {
     /// 检查并更新内容变化状态
     pub fn check_content_changed(&mut self) -> bool {
         let current_content = self.get_display_lyrics();
-        let content_changed = current_content != self.last_rendered_content;
+        let time_label = self.get_time_label();
+        let progress_px = self.get_progress()
+            .map(|progress| (progress * self.effective_width() as f32) as u32);
+        let render_hash = self.render_hash(&current_content, time_label.as_deref(), progress_px);
+        let content_changed = render_hash != self.last_render_hash;
         
+        self.resolved_display_text = Some(current_content.clone());
         if content_changed {
+            self.last_render_hash = render_hash;
             self.last_rendered_content = current_content;
+            self.last_rendered_time_label = time_label;
+            self.last_rendered_progress_px = progress_px;
             self.content_changed = true;
         }
         
         content_changed
     }
 
+    /// 绘制所用全部状态的哈希：样式、滚动模式或窗口尺寸等变化时即使文本相同也需要重绘
+    fn render_hash(&self, text: &str, time_label: Option<&str>, progress_px: Option<u32>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        time_label.hash(&mut hasher);
+        progress_px.hash(&mut hasher);
+        // 高亮边界按整像素比较
+        self.karaoke_fraction(text)
+            .map(|fraction| (fraction * self.effective_width() as f32).round() as i64)
+            .hash(&mut hasher);
+        self.time_display_side.hash(&mut hasher);
+        self.secondary_row().hash(&mut hasher);
+        self.current_render_style().hash(&mut hasher);
+        self.scroll_mode.hash(&mut hasher);
+        // 按整像素比较，避免浮点抖动导致的多余重绘
+        (self.scroll_offset.round() as i64).hash(&mut hasher);
+        (self.effective_width(), self.window_height).hash(&mut hasher);
+        hasher.finish()
+    }
+
     /// 检查是否需要重绘（内容有变化或窗口可见性有变化）
     pub fn should_redraw(&mut self) -> bool {
         let visibility_changed = self.should_show_window();
@@ -325,6 +1005,23 @@ impl TaskbarWidget {
         (visibility_changed && content_changed) || self.content_changed
     }
 
+    /// 设置渲染样式（颜色、底板、字号和边距比例）
+    pub fn set_style(&mut self, style: RenderStyle) {
+        if style != self.style {
+            self.style = style;
+            self.mark_content_changed();
+        }
+    }
+
+    /// 按预设名称设置渲染样式（如 "light"、"dark"、"high-contrast"）
+    pub fn set_style_preset(&mut self, name: &str) -> std::result::Result<(), String> {
+        let style = RenderStyle::preset(name).ok_or_else(|| {
+            format!("未知的样式预设: {}（可选: {}）", name, RenderStyle::preset_names().join(", "))
+        })?;
+        self.set_style(style);
+        Ok(())
+    }
+
     /// 标记内容发生变化（在更新歌词或媒体信息时调用）
     pub fn mark_content_changed(&mut self) {
         self.content_changed = true;
@@ -337,39 +1034,231 @@ impl TaskbarWidget {
     
     /// 获取要显示的歌词文本
     pub fn get_display_lyrics(&self) -> String {
+        let text = self.transform_text(&self.resolve_display_lyrics());
+        
+        match self.scroll_mode {
+            ScrollMode::Marquee => format!("♪ {}", text),
+            ScrollMode::Once => text,
+        }
+    }
+
+    /// 应用简繁转换和显示文本转换钩子
+    fn transform_text(&self, text: &str) -> String {
+        let text = self.chinese_conversion.apply(text);
+        match &self.text_transform {
+            Some(transform) => transform(&text),
+            None => text,
+        }
+    }
+
+    /// 根据当前状态确定显示内容
+    fn resolve_display_lyrics(&self) -> String {
+        // 后台服务已停止时提示错误，避免显示过期内容
+        if let Some(error) = &self.service_error {
+            return format!("⚠ {}", error);
+        }
+        
+        // 切歌后的一段时间内优先显示歌曲信息
+        if let Some(media) = self.current_media.as_ref().filter(|_| self.is_showing_song_title()) {
+            return self.format_song_info(media);
+        }
+        
         if self.lyrics_loading {
-            return "正在加载歌词...".to_string();
+            return self.get_loading_text();
+        }
+        
+        if let Some(match_info) = self.match_info_text() {
+            return match_info;
+        }
+        
+        if let Some(mode_text) = self.display_mode_toast() {
+            return mode_text;
         }
         
         if let Some(media) = &self.current_media {
             // 优先使用预计算的当前歌词行
             if let Some(ref current_line) = self.current_lyrics_line {
                 if !current_line.trim().is_empty() {
-                    return current_line.clone();
+                    return self.line_in_display_mode(current_line);
                 }
             }
             
             // 检查是否有歌词数据但没有当前行（可能是状态不一致）
             if let Some(ref lyrics_data) = self.current_lyrics {
+                // 纯文本歌词无法按时间匹配，按配置显示整首歌词或歌曲信息
+                if lyrics_data.has_any_content() && !lyrics_data.is_synced {
+                    return self.unsynced_full_text()
+                        .unwrap_or_else(|| self.format_song_info(media));
+                }
+                
+                // 第一句歌词之前（前奏）按配置显示
+                if lyrics_data.has_any_content() && self.before_first_line {
+                    return self.intro_text(media);
+                }
+                
                 if lyrics_data.has_any_content() {
                     // 有歌词数据但没有当前行，可能是时间不匹配
                     return "♪ 暂无歌词 ♪".to_string();
                 } else {
                     // 歌词数据存在但为空，可能是无歌词歌曲
-                    return format!("{} - {}", media.artist, media.title);
+                    return self.format_song_info(media);
                 }
             } else {
                 // 正在播放但没有歌词数据，显示歌曲信息
-                return format!("{} - {}", media.artist, media.title);
+                return self.format_song_info(media);
             }
         }
         
         "等待播放音乐...".to_string()
     }
 
+    /// 前奏期间显示的内容
+    fn intro_text(&self, media: &MediaInfo) -> String {
+        match self.intro_display {
+            IntroDisplay::Placeholder => "♪ 暂无歌词 ♪".to_string(),
+            IntroDisplay::SongInfo => self.format_song_info(media),
+            IntroDisplay::FirstLine => match self.next_lyrics_line.as_deref().filter(|line| !line.trim().is_empty()) {
+                Some(first_line) => self.line_in_display_mode(first_line),
+                None => self.format_song_info(media),
+            },
+            IntroDisplay::Blank => String::new(),
+        }
+    }
+
+    /// 以整行显示的纯文本歌词（未选择 `FullText` 或歌词带时间轴时为 None）
+    pub fn unsynced_full_text(&self) -> Option<String> {
+        if self.unsynced_display == UnsyncedDisplay::SongInfo {
+            return None;
+        }
+        self.current_lyrics.as_ref()
+            .filter(|lyrics| !lyrics.is_synced)
+            .and_then(LyricsData::plain_text)
+    }
+
+    /// `Ticker` 模式下纯文本歌词的播放进度（0.0 ~ 1.0），不适用或时长未知时为 None
+    fn ticker_progress(&self) -> Option<f32> {
+        if self.unsynced_display != UnsyncedDisplay::Ticker {
+            return None;
+        }
+        self.current_lyrics.as_ref().filter(|lyrics| !lyrics.is_synced && lyrics.has_any_content())?;
+
+        let media = self.current_media.as_ref()?;
+        let duration = media.duration.filter(|duration| !duration.is_zero())?;
+        let position = media.position.unwrap_or(Duration::ZERO);
+        Some((position.as_secs_f64() / duration.as_secs_f64()).clamp(0.0, 1.0) as f32)
+    }
+
+    /// 获取播放时间文本（未开启或无媒体时为 None）
+    pub fn get_time_label(&self) -> Option<String> {
+        let media = self.current_media.as_ref()?;
+        let position = media.position.unwrap_or(Duration::ZERO);
+        
+        match self.time_display {
+            TimeDisplay::Off => None,
+            TimeDisplay::Elapsed => Some(format_clock(position)),
+            TimeDisplay::Remaining => media.duration
+                .filter(|duration| !duration.is_zero())
+                .map(|duration| format!("-{}", format_clock(duration.saturating_sub(position)))),
+        }
+    }
+
+    /// 获取播放进度（0.0 ~ 1.0），未开启进度条或时长未知时为 None
+    pub fn get_progress(&self) -> Option<f32> {
+        if !self.show_progress_bar {
+            return None;
+        }
+        
+        let media = self.current_media.as_ref()?;
+        let duration = media.duration.filter(|duration| !duration.is_zero())?;
+        let position = media.position.unwrap_or(Duration::ZERO);
+        Some((position.as_secs_f64() / duration.as_secs_f64()).clamp(0.0, 1.0) as f32)
+    }
+
+    /// 按模板格式化当前媒体的歌曲信息
+    fn format_song_info(&self, media: &MediaInfo) -> String {
+        format_song_display(&self.song_display_template, &media.title, &media.artist)
+    }
+
+    /// 匹配信息的显示时长
+    pub const MATCH_INFO_DISPLAY: Duration = Duration::from_secs(3);
+
+    /// 歌词加载完成后的匹配信息，如 `✓ 网易云音乐: 艺术家 - 歌名 (Live)`（未开启、已过显示期或没有搜索结果时为 None）
+    fn match_info_text(&self) -> Option<String> {
+        let loaded_at = self.lyrics_loaded_at.filter(|_| self.show_match_info)?;
+        if loaded_at.elapsed() >= Self::MATCH_INFO_DISPLAY {
+            return None;
+        }
+        let lyrics = self.current_lyrics.as_ref()?;
+        let matched = lyrics.matched.as_ref()?;
+        let song = format_song_display(&self.song_display_template, &matched.title, &matched.artist);
+        Some(format!("✓ {}: {}", lyrics.source.display_name(), song))
+    }
+
+    /// 切换显示语言后提示的显示时间
+    pub const DISPLAY_MODE_TOAST: Duration = Duration::from_millis(1500);
+
+    /// 按顺序切换到下一个当前歌词提供的显示语言，返回切换后的语言
+    pub fn cycle_display_mode(&mut self) -> LyricsDisplayMode {
+        let next = match &self.current_lyrics {
+            Some(lyrics) => self.effective_display_mode().next_available(lyrics),
+            None => LyricsDisplayMode::Original,
+        };
+        if next != self.display_mode {
+            self.display_mode = next;
+            self.display_mode_changed_at = Some(Instant::now());
+            // 新语言的文本宽度不同，重新计算是否需要滚动
+            self.init_scroll_for_current_line(Some(Self::LINE_SCROLL_DURATION));
+            self.mark_content_changed();
+        }
+        next
+    }
+
+    /// 实际使用的显示语言（当前歌词没有所选语言时为原文）
+    pub fn effective_display_mode(&self) -> LyricsDisplayMode {
+        match &self.current_lyrics {
+            Some(lyrics) if self.display_mode.is_available(lyrics) => self.display_mode,
+            _ => LyricsDisplayMode::Original,
+        }
+    }
+
+    /// 把按原文时间轴选出的当前行换成所选语言（双语分两行显示时第一行只显示原文）
+    fn line_in_display_mode(&self, current_line: &str) -> String {
+        let Some(lyrics) = self.current_lyrics.as_ref().filter(|_| !self.is_bilingual_stacked()) else {
+            return current_line.to_string();
+        };
+        let position = self.current_media.as_ref().and_then(|media| media.position).unwrap_or(Duration::ZERO);
+        lyrics.line_for_mode(self.effective_display_mode(), current_line, position)
+    }
+
+    /// 切换显示语言后的短暂提示，如 `♪ 翻译`（已过显示期时为 None）
+    fn display_mode_toast(&self) -> Option<String> {
+        let changed_at = self.display_mode_changed_at?;
+        (changed_at.elapsed() < Self::DISPLAY_MODE_TOAST)
+            .then(|| format!("♪ {}", self.display_mode.display_name()))
+    }
+
+    /// 检查是否处于切歌后的歌曲信息展示期
+    fn is_showing_song_title(&self) -> bool {
+        self.title_display_secs > 0 && self.song_changed_at.is_some_and(|changed_at| {
+            changed_at.elapsed() < Duration::from_secs(self.title_display_secs)
+        })
+    }
+
+    /// 获取加载提示文本（省略号随时间循环，加载过久时给出提示）
+    fn get_loading_text(&self) -> String {
+        let elapsed = self.last_lyrics_update.elapsed();
+        
+        if self.loading_timeout_secs > 0 && elapsed >= Duration::from_secs(self.loading_timeout_secs) {
+            return "歌词加载较慢，请稍候...".to_string();
+        }
+        
+        let dots = (elapsed.as_millis() / 500 % 3) as usize + 1;
+        format!("正在加载歌词{}", ".".repeat(dots))
+    }
+
     /// 计算文本宽度
     pub fn calculate_text_width(&mut self, text: &str) -> f32 {
-        let font_size = (self.window_height as f32 * 0.4) as f32;
+        let font_size = self.style.font_size_for(self.window_height);
         
         if let Some(font) = self.font_manager.get_font() {
             use crate::font::get_layout_text_width;
@@ -382,10 +1271,29 @@ impl TaskbarWidget {
         }
     }
 
+    /// 歌词行超出宽度时滚动完整行所用的时间
+    pub const LINE_SCROLL_DURATION: Duration = Duration::from_secs(8);
+
+    /// 按所选显示语言初始化当前歌词行的滚动（测量实际绘制的文本，如翻译或双语拼接的整行）
+    pub fn init_scroll_for_current_line(&mut self, time_to_next_line: Option<Duration>) {
+        let Some(current_line) = self.current_lyrics_line.as_deref().filter(|line| !line.trim().is_empty()) else {
+            return;
+        };
+        let text = self.line_in_display_mode(current_line);
+        self.init_scroll_for_text(&text, time_to_next_line);
+    }
+
     /// 初始化滚动（当歌词内容变化时调用）
     pub fn init_scroll_for_text(&mut self, text: &str, time_to_next_line: Option<Duration>) {
-        self.text_width = self.calculate_text_width(text);
-        let available_width = self.window_width as f32 - (self.window_height as f32 * 0.5); // 左右留出一些边距
+        // 跑马灯模式由 update_scroll 根据显示内容自行管理
+        if self.scroll_mode == ScrollMode::Marquee {
+            return;
+        }
+        
+        // 按转换后的文本计算宽度，与实际绘制的内容保持一致
+        let text = self.transform_text(text);
+        self.text_width = self.calculate_text_width(&text);
+        let available_width = self.effective_width() as f32 - (self.window_height as f32 * 0.5); // 左右留出一些边距
         
         // 只有在状态变化时才输出调试信息
         let _was_scrolling = self.is_scrolling;
@@ -413,20 +1321,73 @@ impl TaskbarWidget {
         }
     }
 
+    /// 同步播放状态：暂停时冻结滚动，恢复时按 `scroll_resume` 继续或重新开始
+    pub fn set_playing(&mut self, is_playing: bool) {
+        let now = Instant::now();
+        if is_playing {
+            self.resume_scroll_at(now);
+        } else {
+            self.pause_scroll_at(now);
+        }
+    }
+
+    /// 在指定时间暂停滚动
+    fn pause_scroll_at(&mut self, now: Instant) {
+        if self.scroll_paused_at.is_none() {
+            self.scroll_paused_at = Some(now);
+        }
+    }
+
+    /// 在指定时间恢复滚动
+    fn resume_scroll_at(&mut self, now: Instant) {
+        let Some(paused_at) = self.scroll_paused_at.take() else {
+            return;
+        };
+        
+        match self.scroll_resume {
+            ScrollResume::Continue => {
+                // 起始时间后移暂停的时长，偏移量保持不变
+                let paused_for = now.duration_since(paused_at);
+                self.scroll_start_time = self.scroll_start_time.map(|start_time| start_time + paused_for);
+            }
+            ScrollResume::Restart => {
+                self.scroll_offset = 0.0;
+                if self.scroll_start_time.is_some() {
+                    self.scroll_start_time = Some(now);
+                }
+            }
+        }
+    }
+
     /// 更新滚动位置（在每帧调用）
     pub fn update_scroll(&mut self) {
+        // 暂停播放时保持当前滚动位置
+        if self.scroll_paused_at.is_some() {
+            return;
+        }
+        
+        if self.scroll_mode == ScrollMode::Marquee {
+            self.update_marquee_scroll();
+            return;
+        }
+        
         if !self.is_scrolling {
             return;
         }
         
+        // 纯文本歌词跑马灯：滚动位置由播放进度决定，拖动进度或暂停后也能对上
+        if let Some(progress) = self.ticker_progress() {
+            self.scroll_offset = progress * self.max_scroll_offset();
+            return;
+        }
+        
         let now = Instant::now();
         if let Some(start_time) = self.scroll_start_time {
             let elapsed = now.duration_since(start_time).as_secs_f32();
             
             if let Some(target_time) = self.scroll_target_time {
                 if elapsed >= target_time.as_secs_f32() {
-                    let available_width = self.window_width as f32 - (self.window_height as f32 * 0.5);
-                    self.scroll_offset = (self.text_width - available_width + 50.0).max(0.0);
+                    self.scroll_offset = self.max_scroll_offset();
                     self.is_scrolling = false;
                     return;
                 }
@@ -436,8 +1397,7 @@ impl TaskbarWidget {
             self.scroll_offset = elapsed * self.scroll_speed;
             
             // 防止过度滚动
-            let available_width = self.window_width as f32 - (self.window_height as f32 * 0.5);
-            let max_scroll = (self.text_width - available_width + 50.0).max(0.0);
+            let max_scroll = self.max_scroll_offset();
             if self.scroll_offset >= max_scroll {
                 self.scroll_offset = max_scroll;
                 self.is_scrolling = false;
@@ -445,15 +1405,104 @@ impl TaskbarWidget {
         }
     }
 
+    /// 更新跑马灯滚动：文本从右边缘进入，完全移出左边缘后重新开始
+    fn update_marquee_scroll(&mut self) {
+        let text = self.get_display_lyrics();
+        if text != self.marquee_text || self.scroll_start_time.is_none() {
+            self.text_width = self.calculate_text_width(&text);
+            self.marquee_text = text;
+            self.scroll_start_time = Some(Instant::now());
+            self.scroll_speed = self.marquee_speed;
+            self.scroll_target_time = None;
+        }
+        
+        self.is_scrolling = true;
+        
+        let available_width = self.effective_width() as f32 - (self.window_height as f32 * 0.5);
+        let cycle_length = (self.text_width + available_width).max(1.0);
+        let elapsed = self.scroll_start_time
+            .map_or(0.0, |start_time| start_time.elapsed().as_secs_f32());
+        self.scroll_offset = (elapsed * self.scroll_speed) % cycle_length - available_width;
+    }
+
+    /// 最大滚动偏移量（文本超出可用宽度的部分）
+    fn max_scroll_offset(&self) -> f32 {
+        let available_width = self.effective_width() as f32 - (self.window_height as f32 * 0.5);
+        (self.text_width - available_width + 50.0).max(0.0)
+    }
+
     /// 获取当前滚动偏移量
     pub fn get_scroll_offset(&self) -> f32 {
         // 始终返回当前的滚动偏移量，无论是否正在滚动
         self.scroll_offset
     }
 
+    /// 获取归一化的滚动进度（0.0 ~ 1.0，无需滚动时为 0）
+    pub fn get_scroll_progress(&self) -> f32 {
+        let max_scroll = self.max_scroll_offset();
+        if max_scroll > 0.0 {
+            (self.scroll_offset / max_scroll).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// 显示器缩放比例变化（如移动到另一块屏幕）后重新计算窗口尺寸并重新定位，返回新的窗口大小
+    pub fn apply_scale_factor(&mut self, scale_factor: f64) -> (u32, u32) {
+        let ratio = scale_factor / self.scale_factor;
+        self.scale_factor = scale_factor;
+
+        // 高度跟随任务栏（任务栏高度同样随缩放变化），找不到任务栏时按比例缩放
+        let new_height = match self.system_manager.find_taskbar_handle() {
+            Ok(_) if self.system_manager.get_taskbar_orientation() == TaskbarOrientation::Horizontal => {
+                self.system_manager.get_taskbar_height()
+            }
+            _ => (self.window_height as f64 * ratio).round() as u32,
+        };
+        let new_width = (self.window_width as f64 * ratio).round() as u32;
+        self.resize_window(new_width, new_height.clamp(32, 100));
+        self.renderer.clear_caches();
+
+        // 强制重新定位（任务栏位置可能没变，但窗口尺寸变了）
+        self.last_taskbar_rect = RECT::default();
+        let _ = self.adjust_window_position();
+
+        (self.window_width, self.window_height)
+    }
+
+    /// 更新窗口尺寸，字体大小和边距随高度变化，滚动状态按比例换算
+    fn resize_window(&mut self, width: u32, height: u32) {
+        let font_ratio = height as f32 / self.window_height as f32;
+        self.window_width = width.max(1);
+        self.window_height = height;
+
+        self.text_width *= font_ratio;
+        self.scroll_offset *= font_ratio;
+        self.scroll_speed *= font_ratio;
+        self.mark_content_changed();
+    }
+
+    /// 重新加载系统字体（如安装了新字体后），返回是否有可用字体
+    pub fn reload_font(&mut self) -> bool {
+        self.font_manager.reload_font();
+        self.renderer.clear_caches();
+        self.mark_content_changed();
+        self.font_manager.has_font()
+    }
+
+    /// 重新创建渲染表面（如显示器配置变化导致表面失效）
+    pub fn reinitialize_renderer(&mut self) -> InitResult<()> {
+        let window = self.window_manager.get_window()
+            .ok_or_else(|| InitError::WindowCreation("窗口未创建".to_string()))?;
+        self.renderer.reinitialize(window)?;
+        self.mark_content_changed();
+        Ok(())
+    }
+
     /// 清理资源
     pub fn cleanup(&mut self) {
         self.system_manager.cleanup();
+        self.renderer.release();
     }
 
     /// 请求重绘
@@ -469,3 +1518,633 @@ impl Default for TaskbarWidget {
         Self::new()
     }
 }
+
+/// 将时长格式化为 `m:ss`（超过一小时为 `h:mm:ss`）
+fn format_clock(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    let (hours, minutes, seconds) = (total_secs / 3600, total_secs / 60 % 60, total_secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lyrics::{LyricsSource, SearchResult};
+
+    fn scrolling_widget(resume: ScrollResume, start_time: Instant) -> TaskbarWidget {
+        let mut widget = TaskbarWidget::new();
+        widget.scroll_resume = resume;
+        widget.is_scrolling = true;
+        widget.scroll_offset = 30.0;
+        widget.scroll_start_time = Some(start_time);
+        widget
+    }
+
+    #[test]
+    fn test_auto_fit_width_is_clamped() {
+        assert_eq!(auto_fit_width(100.0, 20.0, 150, 500, 1.0), 150);
+        assert_eq!(auto_fit_width(300.4, 20.0, 150, 500, 1.0), 321);
+        assert_eq!(auto_fit_width(900.0, 20.0, 150, 500, 1.0), 500);
+        // 上下限按缩放比例换算
+        assert_eq!(auto_fit_width(900.0, 20.0, 150, 500, 1.5), 750);
+        // max 小于 min 时以 min 为准
+        assert_eq!(auto_fit_width(900.0, 20.0, 300, 200, 1.0), 300);
+    }
+
+    #[test]
+    fn test_fixed_width_mode_scales() {
+        let mut widget = TaskbarWidget::new();
+        widget.scale_factor = 1.5;
+        widget.set_width_mode(WidthMode::Fixed(200));
+        assert_eq!(widget.window_width, 300);
+        assert_eq!(widget.width_mode, WidthMode::Fixed(200));
+    }
+
+    #[test]
+    fn test_media_control_click_regions() {
+        let mut widget = TaskbarWidget::new();
+        widget.window_width = 300;
+        assert_eq!(widget.media_control_at(10.0), MediaControl::Previous);
+        assert_eq!(widget.media_control_at(150.0), MediaControl::TogglePlayPause);
+        assert_eq!(widget.media_control_at(290.0), MediaControl::Next);
+        assert_eq!(widget.media_control_at(240.0), MediaControl::Next);
+        assert_eq!(widget.media_control_at(239.0), MediaControl::TogglePlayPause);
+    }
+
+    #[test]
+    fn test_vertical_taskbar_limits_width_without_changing_config() {
+        let mut widget = TaskbarWidget::new();
+        widget.window_width = 280;
+        widget.taskbar_width_limit = Some(62);
+        assert_eq!(widget.effective_width(), 62);
+        assert_eq!(widget.window_width, 280);
+        assert_eq!(widget.media_control_at(61.0), MediaControl::Next);
+
+        // 换回横向任务栏后恢复配置的宽度
+        widget.taskbar_width_limit = None;
+        assert_eq!(widget.effective_width(), 280);
+    }
+
+    #[test]
+    fn test_resize_window_scales_scroll_state() {
+        let start = Instant::now();
+        let mut widget = scrolling_widget(ScrollResume::Continue, start);
+        widget.window_height = 40;
+        widget.scroll_speed = 20.0;
+        widget.text_width = 400.0;
+
+        widget.resize_window(420, 60);
+        assert_eq!((widget.window_width, widget.window_height), (420, 60));
+        assert_eq!(widget.scroll_offset, 45.0);
+        assert_eq!(widget.scroll_speed, 30.0);
+        assert_eq!(widget.text_width, 600.0);
+        assert!(widget.content_changed);
+    }
+
+    #[test]
+    fn test_scroll_resume_continue_shifts_start_time() {
+        let start = Instant::now();
+        let mut widget = scrolling_widget(ScrollResume::Continue, start);
+
+        widget.pause_scroll_at(start + Duration::from_secs(2));
+        widget.update_scroll();
+        assert_eq!(widget.scroll_offset, 30.0);
+
+        widget.resume_scroll_at(start + Duration::from_secs(5));
+        assert_eq!(widget.scroll_paused_at, None);
+        assert_eq!(widget.scroll_offset, 30.0);
+        assert_eq!(widget.scroll_start_time, Some(start + Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_scroll_resume_restart_resets_offset() {
+        let start = Instant::now();
+        let mut widget = scrolling_widget(ScrollResume::Restart, start);
+
+        widget.pause_scroll_at(start + Duration::from_secs(2));
+        widget.resume_scroll_at(start + Duration::from_secs(5));
+        assert_eq!(widget.scroll_offset, 0.0);
+        assert_eq!(widget.scroll_start_time, Some(start + Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_time_label() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            position: Some(Duration::from_secs(83)),
+            duration: Some(Duration::from_secs(213)),
+            ..Default::default()
+        });
+
+        assert_eq!(widget.get_time_label(), None);
+
+        widget.time_display = TimeDisplay::Elapsed;
+        assert_eq!(widget.get_time_label(), Some("1:23".to_string()));
+
+        widget.time_display = TimeDisplay::Remaining;
+        assert_eq!(widget.get_time_label(), Some("-2:10".to_string()));
+
+        // 时长未知时不显示剩余时间
+        widget.current_media.as_mut().unwrap().duration = None;
+        assert_eq!(widget.get_time_label(), None);
+
+        assert_eq!(format_clock(Duration::from_secs(3725)), "1:02:05");
+    }
+
+    #[test]
+    fn test_progress_hidden_without_duration() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            position: Some(Duration::from_secs(30)),
+            duration: Some(Duration::from_secs(120)),
+            ..Default::default()
+        });
+
+        assert_eq!(widget.get_progress(), None);
+
+        widget.show_progress_bar = true;
+        assert_eq!(widget.get_progress(), Some(0.25));
+
+        widget.current_media.as_mut().unwrap().duration = None;
+        assert_eq!(widget.get_progress(), None);
+    }
+
+    #[test]
+    fn test_paused_widget_dims_instead_of_hiding() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            playback_status: crate::system::PlaybackStatus::Paused,
+            ..Default::default()
+        });
+        assert!(!widget.should_show_window());
+        assert_eq!(widget.current_render_style(), widget.style);
+
+        widget.hide_when_paused = false;
+        assert!(widget.should_show_window());
+        assert_eq!(widget.current_render_style().opacity, 0.5);
+    }
+
+    #[test]
+    fn test_next_line_preview_needs_option_and_height() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_lyrics_line = Some("current".to_string());
+        widget.next_lyrics_line = Some("next".to_string());
+        widget.window_height = TaskbarWidget::NEXT_LINE_MIN_HEIGHT;
+        assert_eq!(widget.next_line_preview(), None);
+
+        widget.show_next_line = true;
+        assert_eq!(widget.next_line_preview(), Some("next"));
+
+        widget.window_height = 40;
+        assert_eq!(widget.next_line_preview(), None);
+
+        // 加载提示等非歌词内容保持单行
+        widget.window_height = TaskbarWidget::NEXT_LINE_MIN_HEIGHT;
+        widget.lyrics_loading = true;
+        assert_eq!(widget.next_line_preview(), None);
+    }
+
+    #[test]
+    fn test_next_line_beside_on_short_window() {
+        let mut widget = TaskbarWidget::new();
+        widget.show_next_line = true;
+        widget.current_lyrics_line = Some("current".to_string());
+        widget.next_lyrics_line = Some("next".to_string());
+
+        widget.window_height = 40;
+        assert_eq!(widget.next_line_layout(), Some(("next", SecondaryPlacement::Beside)));
+        widget.window_height = TaskbarWidget::NEXT_LINE_MIN_HEIGHT;
+        assert_eq!(widget.next_line_layout(), Some(("next", SecondaryPlacement::Below)));
+
+        // 最后一句之后没有下一句
+        widget.next_lyrics_line = None;
+        assert_eq!(widget.next_line_layout(), None);
+    }
+
+    #[test]
+    fn test_unsynced_lyrics_display() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            ..Default::default()
+        });
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("first\nsecond".to_string()),
+            has_lyrics: true,
+            is_synced: false,
+            ..Default::default()
+        });
+
+        assert_eq!(widget.get_display_lyrics(), widget.format_song_info(widget.current_media.as_ref().unwrap()));
+
+        widget.unsynced_display = UnsyncedDisplay::FullText;
+        assert_eq!(widget.get_display_lyrics(), "first  second");
+    }
+
+    #[test]
+    fn test_ticker_offset_follows_playback_position() {
+        let mut widget = TaskbarWidget::new();
+        widget.unsynced_display = UnsyncedDisplay::Ticker;
+        widget.current_media = Some(MediaInfo {
+            position: Some(Duration::from_secs(60)),
+            duration: Some(Duration::from_secs(240)),
+            ..Default::default()
+        });
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("plain lyrics".to_string()),
+            has_lyrics: true,
+            is_synced: false,
+            ..Default::default()
+        });
+        widget.is_scrolling = true;
+        widget.text_width = 2000.0;
+
+        widget.update_scroll();
+        assert_eq!(widget.scroll_offset, widget.max_scroll_offset() * 0.25);
+
+        widget.current_media.as_mut().unwrap().position = Some(Duration::from_secs(240));
+        widget.update_scroll();
+        assert_eq!(widget.scroll_offset, widget.max_scroll_offset());
+        assert!(widget.is_scrolling);
+    }
+
+    #[test]
+    fn test_hidden_during_desktop_peek() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            playback_status: crate::system::PlaybackStatus::Playing,
+            ..Default::default()
+        });
+        assert!(widget.should_show_window());
+
+        widget.desktop_peek_active = true;
+        assert!(!widget.should_show_window());
+
+        widget.hide_during_peek = false;
+        assert!(widget.should_show_window());
+    }
+
+    #[test]
+    fn test_user_hidden_overrides_playback() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        });
+        widget.user_hidden = true;
+        assert!(!widget.should_show_window());
+
+        widget.user_hidden = false;
+        assert!(widget.should_show_window());
+    }
+
+    #[test]
+    fn test_text_transform_applies_to_display_and_width() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            ..Default::default()
+        });
+        widget.current_lyrics_line = Some("short".to_string());
+
+        widget.text_transform = Some(identity_transform());
+        assert_eq!(widget.get_display_lyrics(), "short");
+        widget.init_scroll_for_text("short", None);
+        assert!(!widget.is_scrolling);
+
+        widget.text_transform = Some(Box::new(|text: &str| text.repeat(40)));
+        assert_eq!(widget.get_display_lyrics(), "short".repeat(40));
+        widget.init_scroll_for_text("short", None);
+        assert!(widget.is_scrolling);
+    }
+
+    #[test]
+    fn test_power_saving_lowers_frame_rate_and_drops_preview() {
+        let mut widget = TaskbarWidget::new();
+        widget.window_height = TaskbarWidget::NEXT_LINE_MIN_HEIGHT;
+        widget.show_next_line = true;
+        widget.current_lyrics_line = Some("current".to_string());
+        widget.next_lyrics_line = Some("next".to_string());
+        assert_eq!(widget.frame_interval(), TaskbarWidget::FRAME_INTERVAL);
+        assert_eq!(widget.next_line_preview(), Some("next"));
+
+        widget.on_battery = true;
+        assert_eq!(widget.frame_interval(), TaskbarWidget::POWER_SAVING_FRAME_INTERVAL);
+        assert_eq!(widget.next_line_preview(), None);
+        assert!(!widget.current_render_style().word_highlight);
+
+        // 暂停变暗不属于耗电效果，省电时仍然保留
+        widget.hide_when_paused = false;
+        widget.current_media = Some(MediaInfo { playback_status: PlaybackStatus::Paused, ..Default::default() });
+        let style = widget.current_render_style();
+        assert!(!style.word_highlight);
+        assert!((style.opacity - widget.paused_opacity).abs() < f32::EPSILON);
+
+        widget.power_mode = PowerMode::Performance;
+        assert!(!widget.is_power_saving());
+        widget.power_mode = PowerMode::PowerSaver;
+        widget.on_battery = false;
+        assert!(widget.is_power_saving());
+    }
+
+    #[test]
+    fn test_polling_fallback_only_when_event_hook_failed() {
+        let mut widget = TaskbarWidget::new();
+        let now = Instant::now();
+        assert!(!widget.uses_polling_fallback());
+        assert!(!widget.polling_due(now));
+
+        widget.event_hook_error = Some(InitError::EventHook("access denied".to_string()));
+        assert!(widget.uses_polling_fallback());
+        assert!(widget.polling_due(now));
+        assert!(!widget.polling_due(now + Duration::from_millis(500)));
+        assert!(widget.polling_due(now + TaskbarWidget::POLLING_FALLBACK_INTERVAL));
+    }
+
+    #[test]
+    fn test_topmost_throttle_is_independent_of_reposition() {
+        let mut widget = TaskbarWidget::new();
+        widget.topmost_interval = Duration::from_millis(100);
+        widget.reposition_interval = Duration::from_secs(5);
+        let now = Instant::now();
+
+        assert!(widget.topmost_due(now));
+        assert!(!widget.topmost_due(now + Duration::from_millis(50)));
+        assert!(widget.topmost_pending);
+        assert!(widget.topmost_due(now + Duration::from_millis(100)));
+        assert!(!widget.topmost_pending);
+
+        // 置顶节流不影响重新定位的节流
+        widget.last_position_update = now - Duration::from_secs(1);
+        assert!(!widget.should_update_position());
+    }
+
+    #[test]
+    fn test_scroll_measures_line_in_display_mode() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            position: Some(Duration::from_secs(2)),
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        });
+        let long_translation = "很长的翻译".repeat(20);
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("[00:01.00]hi".to_string()),
+            translated: Some(format!("[00:01.00]{}", long_translation)),
+            ..Default::default()
+        });
+        widget.current_lyrics_line = Some("hi".to_string());
+
+        widget.init_scroll_for_current_line(Some(TaskbarWidget::LINE_SCROLL_DURATION));
+        assert!(!widget.is_scrolling);
+
+        // 切换到翻译后按翻译的宽度重新计算滚动
+        assert_eq!(widget.cycle_display_mode(), LyricsDisplayMode::Translated);
+        assert!(widget.is_scrolling);
+        let expected_width = widget.calculate_text_width(&long_translation);
+        assert_eq!(widget.text_width, expected_width);
+    }
+
+    #[test]
+    fn test_bilingual_stacks_translation_when_tall() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            position: Some(Duration::from_secs(2)),
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        });
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("[00:01.00]hello".to_string()),
+            translated: Some("[00:01.00]你好".to_string()),
+            ..Default::default()
+        });
+        widget.current_lyrics_line = Some("hello".to_string());
+        widget.next_lyrics_line = Some("next".to_string());
+        widget.show_next_line = true;
+        widget.display_mode = LyricsDisplayMode::Bilingual;
+
+        // 单行任务栏：原文和翻译拼接为一行，滚动按整行测量
+        assert_eq!(widget.get_display_lyrics(), "hello  你好");
+        assert_eq!(widget.secondary_row(), Some(("next".to_string(), SecondaryPlacement::Beside)));
+
+        // 窗口足够高：原文在上，翻译在下（代替下一句预览），滚动只测量原文
+        widget.window_height = TaskbarWidget::NEXT_LINE_MIN_HEIGHT;
+        assert_eq!(widget.get_display_lyrics(), "hello");
+        assert_eq!(widget.secondary_row(), Some(("你好".to_string(), SecondaryPlacement::Below)));
+        widget.init_scroll_for_current_line(None);
+        let expected_width = widget.calculate_text_width("hello");
+        assert_eq!(widget.text_width, expected_width);
+
+        // 当前行没有翻译时照常预览下一句
+        widget.current_lyrics.as_mut().unwrap().translated = Some("[00:01.00]hello".to_string());
+        assert_eq!(widget.secondary_row(), Some(("next".to_string(), SecondaryPlacement::Below)));
+    }
+
+    #[test]
+    fn test_cycle_display_mode_shows_selected_language() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            position: Some(Duration::from_secs(2)),
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        });
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("[00:01.00]hello".to_string()),
+            translated: Some("[00:01.00]你好".to_string()),
+            ..Default::default()
+        });
+        widget.current_lyrics_line = Some("hello".to_string());
+
+        assert_eq!(widget.cycle_display_mode(), LyricsDisplayMode::Translated);
+        assert_eq!(widget.resolve_display_lyrics(), "♪ 翻译");
+        widget.display_mode_changed_at = None;
+        assert_eq!(widget.resolve_display_lyrics(), "你好");
+
+        // 没有罗马音，直接跳到双语
+        assert_eq!(widget.cycle_display_mode(), LyricsDisplayMode::Bilingual);
+        assert_eq!(widget.cycle_display_mode(), LyricsDisplayMode::Original);
+
+        // 换成没有翻译的歌词后回退到原文
+        widget.display_mode = LyricsDisplayMode::Translated;
+        widget.current_lyrics.as_mut().unwrap().translated = None;
+        assert_eq!(widget.effective_display_mode(), LyricsDisplayMode::Original);
+    }
+
+    #[test]
+    fn test_render_state_change_without_text_change_is_detected() {
+        let mut widget = TaskbarWidget::new();
+        assert!(widget.check_content_changed());
+        assert!(!widget.check_content_changed());
+
+        // 文本不变，只换了样式
+        widget.style = RenderStyle::DARK_TASKBAR;
+        assert!(widget.check_content_changed());
+        assert!(!widget.check_content_changed());
+
+        widget.scroll_mode = ScrollMode::Marquee;
+        assert!(widget.check_content_changed());
+
+        widget.window_height += 10;
+        assert!(widget.check_content_changed());
+    }
+
+    #[test]
+    fn test_intro_display_before_first_line() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        });
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("[00:12.00]first".to_string()),
+            has_lyrics: true,
+            is_synced: true,
+            ..Default::default()
+        });
+        widget.next_lyrics_line = Some("first".to_string());
+        widget.before_first_line = true;
+
+        assert_eq!(widget.resolve_display_lyrics(), "♪ 暂无歌词 ♪");
+        widget.intro_display = IntroDisplay::SongInfo;
+        assert_eq!(widget.resolve_display_lyrics(), "Artist - Song");
+        widget.intro_display = IntroDisplay::FirstLine;
+        assert_eq!(widget.resolve_display_lyrics(), "first");
+        widget.intro_display = IntroDisplay::Blank;
+        assert_eq!(widget.resolve_display_lyrics(), "");
+
+        // 前奏结束后的间奏仍显示提示
+        widget.before_first_line = false;
+        assert_eq!(widget.resolve_display_lyrics(), "♪ 暂无歌词 ♪");
+    }
+
+    #[test]
+    fn test_karaoke_fraction_follows_word_timings() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("[00:01.00]ab".to_string()),
+            yrc: Some("[1000,2000](1000,1000,0)a(2000,1000,0)b".to_string()),
+            ..Default::default()
+        });
+        widget.current_lyrics_line = Some("ab".to_string());
+        widget.current_media = Some(MediaInfo {
+            position: Some(Duration::from_millis(2500)),
+            ..Default::default()
+        });
+
+        // 未开启时不解析也不高亮
+        widget.sync_word_timing();
+        assert_eq!(widget.karaoke_fraction("ab"), None);
+
+        widget.karaoke_highlight = true;
+        widget.sync_word_timing();
+        assert_eq!(widget.karaoke_fraction("ab"), Some(0.75));
+        // 显示的不是当前行（如提示信息）时不高亮
+        assert_eq!(widget.karaoke_fraction("♪ 翻译"), None);
+
+        // 没有 yrc 时回退为普通显示
+        widget.current_lyrics.as_mut().unwrap().yrc = None;
+        widget.sync_word_timing();
+        assert_eq!(widget.karaoke_fraction("ab"), None);
+    }
+
+    #[test]
+    fn test_match_info_shown_briefly_after_load() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            ..Default::default()
+        });
+        widget.current_lyrics_line = Some("first line".to_string());
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("[00:01.00]first line".to_string()),
+            has_lyrics: true,
+            source: LyricsSource::NetEase,
+            matched: Some(SearchResult {
+                id: "1".to_string(),
+                title: "Song (Live)".to_string(),
+                artist: "Artist".to_string(),
+                duration: None,
+            }),
+            ..Default::default()
+        });
+        widget.lyrics_loaded_at = Some(Instant::now());
+        assert_eq!(widget.get_display_lyrics(), "first line");
+
+        widget.show_match_info = true;
+        assert_eq!(widget.get_display_lyrics(), "✓ 网易云音乐: Artist - Song (Live)");
+
+        widget.lyrics_loaded_at = Instant::now().checked_sub(TaskbarWidget::MATCH_INFO_DISPLAY);
+        assert_eq!(widget.get_display_lyrics(), "first line");
+    }
+
+    #[test]
+    fn test_unknown_status_keeps_previous_visibility() {
+        let mut widget = TaskbarWidget::new();
+        let media = |playback_status| MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            playback_status,
+            ..Default::default()
+        };
+
+        // 启动时 Unknown 没有可沿用的状态，保持隐藏；变为 Playing 后显示
+        widget.current_media = Some(media(PlaybackStatus::Unknown));
+        widget.track_playback_status();
+        assert!(!widget.should_show_window());
+        widget.current_media = Some(media(PlaybackStatus::Playing));
+        widget.track_playback_status();
+        assert!(widget.should_show_window());
+
+        // 播放中短暂上报 Unknown：宽限期内继续显示
+        widget.current_media = Some(media(PlaybackStatus::Unknown));
+        widget.track_playback_status();
+        assert!(widget.should_show_window());
+
+        // 超过宽限期后按 Unknown 隐藏
+        widget.unknown_status_since = Instant::now().checked_sub(widget.unknown_status_grace);
+        assert!(!widget.should_show_window());
+    }
+
+    #[test]
+    fn test_require_lyrics_hides_without_synced_lyrics() {
+        let mut widget = TaskbarWidget::new();
+        widget.current_media = Some(MediaInfo {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            playback_status: PlaybackStatus::Playing,
+            ..Default::default()
+        });
+        assert!(widget.should_show_window());
+
+        widget.require_lyrics = true;
+        assert!(!widget.should_show_window());
+
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("plain text".to_string()),
+            has_lyrics: true,
+            is_synced: false,
+            ..Default::default()
+        });
+        assert!(!widget.should_show_window());
+
+        widget.current_lyrics = Some(LyricsData {
+            original: Some("[00:10.00]first".to_string()),
+            has_lyrics: true,
+            is_synced: true,
+            ..Default::default()
+        });
+        // 前奏中还没有当前行也显示
+        assert!(widget.should_show_window());
+    }
+}
@@ -5,7 +5,7 @@ pub fn create_widget_window(
     event_loop: &winit::event_loop::ActiveEventLoop,
     width: u32,
     height: u32,
-) -> std::result::Result<Rc<Window>, String> {
+) -> InitResult<Rc<Window>> {
     let window_attributes = Window::default_attributes()
         .with_title("Taskbar LRC Widget")
         .with_inner_size(PhysicalSize::new(width, height))
@@ -17,7 +17,7 @@ pub fn create_widget_window(
 
     let window = event_loop
         .create_window(window_attributes)
-        .map_err(|e| format!("创建窗口失败: {}", e))?;
+        .map_err(|e| InitError::WindowCreation(e.to_string()))?;
 
     // 立即设置窗口扩展样式，隐藏任务栏图标
     hide_from_taskbar(&window);
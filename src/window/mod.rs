@@ -24,7 +24,7 @@ impl WindowManager {
         event_loop: &winit::event_loop::ActiveEventLoop,
         width: u32,
         height: u32,
-    ) -> std::result::Result<(), String> {
+    ) -> InitResult<()> {
         let window = create_widget_window(event_loop, width, height)?;
         self.window = Some(window);
         Ok(())
@@ -44,11 +44,9 @@ impl WindowManager {
         }
     }
 
-    /// 确保窗口在最上层
-    pub fn ensure_topmost(&self) {
-        if let Some(window) = &self.window {
-            ensure_window_topmost(window);
-        }
+    /// 确保窗口在最上层，返回层级是否真的发生了变化（窗口未创建时为 None）
+    pub fn ensure_topmost(&self) -> Option<bool> {
+        self.window.as_ref().map(|window| ensure_window_topmost(window))
     }
 
     /// 获取窗口的Windows句柄
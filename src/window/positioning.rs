@@ -1,6 +1,9 @@
 use crate::*;
+use super::get_window_hwnd;
 
 /// 设置窗口位置
+///
+/// 坐标和尺寸均为物理像素（与 `GetWindowRect`、`SetWindowPos` 一致），不经过 winit 的缩放换算。
 pub fn set_window_position(
     window: &Window,
     x: i32,
@@ -8,43 +11,321 @@ pub fn set_window_position(
     width: u32,
     height: u32,
 ) -> std::result::Result<(), String> {
-    // 使用winit设置窗口位置
-    window.set_outer_position(PhysicalPosition::new(x, y));
-    
-    // 通过Windows API直接设置位置确保精确
-    if let Ok(handle) = window.window_handle() {
-        if let RawWindowHandle::Win32(win32_handle) = handle.as_raw() {
-            let hwnd = HWND(win32_handle.hwnd.get() as *mut _);
-            unsafe {
-                let _ = SetWindowPos(
-                    hwnd,
-                    Some(HWND_TOPMOST),
-                    x,
-                    y,
-                    width as i32,
-                    height as i32,
-                    SWP_SHOWWINDOW | SWP_NOACTIVATE,
-                );
-            }
+    // 通过Windows API直接设置位置，避免 winit 按缩放比例再换算一次
+    if let Some(hwnd) = get_window_hwnd(window) {
+        unsafe {
+            let _ = SetWindowPos(
+                hwnd,
+                Some(HWND_TOPMOST),
+                x,
+                y,
+                width as i32,
+                height as i32,
+                SWP_SHOWWINDOW | SWP_NOACTIVATE,
+            );
         }
+    } else {
+        // 拿不到原生句柄时退回 winit（同样使用物理像素）
+        window.set_outer_position(PhysicalPosition::new(x, y));
     }
     
     Ok(())
 }
 
-/// 确保窗口始终在最上层
-pub fn ensure_window_topmost(window: &Window) {
-    if let Ok(handle) = window.window_handle() {
-        if let RawWindowHandle::Win32(win32_handle) = handle.as_raw() {
-            let hwnd = HWND(win32_handle.hwnd.get() as *mut _);
-            unsafe {
-                let _ = SetWindowPos(
-                    hwnd,
-                    Some(HWND_TOPMOST),
-                    0, 0, 0, 0,
-                    SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
-                );
+/// 窗口定位方式（可序列化，便于保存到配置）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PositionMode {
+    /// 跟随任务栏，随任务栏和通知区域变化自动调整
+    #[default]
+    TaskbarRelative,
+    /// 固定在屏幕坐标（物理像素）上，如作为屏幕顶部的桌面歌词
+    Fixed(i32, i32),
+}
+
+/// 窗口在任务栏上的停靠位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WidgetAnchor {
+    /// 靠左（任务栏左侧留出偏移）
+    Left,
+    /// 靠右（应用按钮列表与通知区域之间）
+    #[default]
+    Right,
+}
+
+/// 窗口定位使用的偏移量（像素）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionOffsets {
+    /// 靠左时与任务栏左边缘的距离
+    pub left_margin: i32,
+    /// 与通知区域之间的最小间距
+    pub notify_gap: i32,
+    /// 无法获取通知区域时与任务栏右边缘的距离
+    pub right_margin: i32,
+}
+
+impl PositionOffsets {
+    /// 按显示器缩放比例换算为物理像素（偏移量以 100% 缩放为基准）
+    pub fn scaled(&self, scale_factor: f64) -> Self {
+        let scale = |value: i32| (value as f64 * scale_factor).round() as i32;
+        Self {
+            left_margin: scale(self.left_margin),
+            notify_gap: scale(self.notify_gap),
+            right_margin: scale(self.right_margin),
+        }
+    }
+}
+
+impl Default for PositionOffsets {
+    fn default() -> Self {
+        Self {
+            left_margin: 60,
+            notify_gap: 5,
+            right_margin: 60,
+        }
+    }
+}
+
+/// 计算窗口左上角坐标（纯计算，不调用系统接口）
+///
+/// 靠右时，能测量到应用按钮列表与通知区域之间的空隙且放得下窗口（两侧各留 `notify_gap`）时，
+/// 居中放在空隙中；否则贴近通知区域左侧，拿不到通知区域时按任务栏右边缘偏移。
+/// 结果限制在任务栏范围内，任务栏过窄时贴左边缘。`RECT` 为零表示未获取到。
+pub fn compute_widget_position(
+    taskbar_rect: RECT,
+    notify_rect: RECT,
+    task_list_rect: RECT,
+    window_width: u32,
+    anchor: WidgetAnchor,
+    offsets: &PositionOffsets,
+) -> (i32, i32) {
+    let window_width = window_width as i32;
+
+    let x = match anchor {
+        WidgetAnchor::Left => taskbar_rect.left + offsets.left_margin,
+        WidgetAnchor::Right => {
+            let free_space = notify_rect.left - task_list_rect.right;
+            let fits_gap = notify_rect.left != 0
+                && task_list_rect.right != 0
+                && free_space >= window_width + offsets.notify_gap * 2;
+
+            if fits_gap {
+                task_list_rect.right + (free_space - window_width) / 2
+            } else if notify_rect.left != 0 {
+                notify_rect.left - window_width - offsets.notify_gap
+            } else {
+                taskbar_rect.right - window_width - offsets.right_margin
             }
         }
+    };
+
+    // 不超出任务栏（min 在前，任务栏比窗口还窄时以左边缘为准）
+    let x = x.min(taskbar_rect.right - window_width).max(taskbar_rect.left);
+    (x, taskbar_rect.top)
+}
+
+/// 计算竖向任务栏（停靠在屏幕左右两侧）上的窗口左上角坐标（纯计算，不调用系统接口）
+///
+/// 窗口在任务栏内水平居中；靠右（默认）时竖直方向叠放在通知区域（时钟）上方，
+/// 拿不到通知区域时按任务栏下边缘偏移；靠左时放在任务栏顶部。结果限制在任务栏范围内。
+pub fn compute_vertical_widget_position(
+    taskbar_rect: RECT,
+    notify_rect: RECT,
+    window_width: u32,
+    window_height: u32,
+    anchor: WidgetAnchor,
+    offsets: &PositionOffsets,
+) -> (i32, i32) {
+    let window_width = window_width as i32;
+    let window_height = window_height as i32;
+
+    let x = taskbar_rect.left + (taskbar_rect.right - taskbar_rect.left - window_width) / 2;
+    let y = match anchor {
+        WidgetAnchor::Left => taskbar_rect.top + offsets.left_margin,
+        WidgetAnchor::Right if notify_rect.top != 0 => notify_rect.top - window_height - offsets.notify_gap,
+        WidgetAnchor::Right => taskbar_rect.bottom - window_height - offsets.right_margin,
+    };
+
+    let x = x.max(taskbar_rect.left);
+    let y = y.min(taskbar_rect.bottom - window_height).max(taskbar_rect.top);
+    (x, y)
+}
+
+/// 计算靠右显示时的窗口横坐标（使用默认偏移）
+pub fn calculate_right_side_x(
+    taskbar_rect: RECT,
+    notify_rect: RECT,
+    task_list_rect: RECT,
+    window_width: u32,
+) -> i32 {
+    let offsets = PositionOffsets::default();
+    compute_widget_position(taskbar_rect, notify_rect, task_list_rect, window_width, WidgetAnchor::Right, &offsets).0
+}
+
+/// 置顶操作统计：区分真正改变了层级的次数和多余的调用
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TopmostStats {
+    /// 窗口被其他窗口遮挡、重新置顶后层级发生变化的次数
+    pub reasserted: u64,
+    /// 窗口本来就在最上层的次数
+    pub redundant: u64,
+}
+
+impl TopmostStats {
+    /// 记录一次置顶操作
+    pub fn record(&mut self, changed: bool) {
+        if changed {
+            self.reasserted += 1;
+        } else {
+            self.redundant += 1;
+        }
+    }
+
+    /// 总调用次数
+    pub fn total(&self) -> u64 {
+        self.reasserted + self.redundant
+    }
+
+    /// 真正需要重新置顶的比例（没有调用时为 0）
+    ///
+    /// 接近 0 说明可以关闭 `keep_topmost`；比例很高说明有其他窗口在争夺最上层
+    pub fn reassert_ratio(&self) -> f32 {
+        match self.total() {
+            0 => 0.0,
+            total => self.reasserted as f32 / total as f32,
+        }
+    }
+}
+
+/// 确保窗口始终在最上层，返回层级是否真的发生了变化
+pub fn ensure_window_topmost(window: &Window) -> bool {
+    let Some(hwnd) = get_window_hwnd(window) else {
+        return false;
+    };
+
+    let covered = is_covered_by_visible_window(hwnd);
+    unsafe {
+        let _ = SetWindowPos(
+            hwnd,
+            Some(HWND_TOPMOST),
+            0, 0, 0, 0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        );
+    }
+    covered
+}
+
+/// Z序中是否有可见窗口排在该窗口之前（置顶窗口之前只可能是其他置顶窗口）
+fn is_covered_by_visible_window(hwnd: HWND) -> bool {
+    let mut current = hwnd;
+    while let Ok(prev) = unsafe { GetWindow(current, GW_HWNDPREV) } {
+        if prev.0.is_null() {
+            break;
+        }
+        if unsafe { IsWindowVisible(prev) }.as_bool() {
+            return true;
+        }
+        current = prev;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: i32, right: i32) -> RECT {
+        RECT { left, top: 0, right, bottom: 40 }
+    }
+
+    #[test]
+    fn test_calculate_right_side_x() {
+        let taskbar = rect(0, 1920);
+        let notify = rect(1700, 1920);
+
+        // 空隙足够时居中
+        assert_eq!(calculate_right_side_x(taskbar, notify, rect(100, 1000), 280), 1000 + (700 - 280) / 2);
+        // 空隙不足时贴近通知区域
+        assert_eq!(calculate_right_side_x(taskbar, notify, rect(100, 1600), 280), 1700 - 280 - 5);
+        // 无法测量应用按钮列表时使用固定偏移
+        assert_eq!(calculate_right_side_x(taskbar, notify, RECT::default(), 280), 1700 - 280 - 5);
+        assert_eq!(calculate_right_side_x(taskbar, RECT::default(), RECT::default(), 280), 1920 - 280 - 60);
+    }
+
+    #[test]
+    fn test_compute_widget_position() {
+        let offsets = PositionOffsets::default();
+        let taskbar = RECT { left: 0, top: 1040, right: 1920, bottom: 1080 };
+        let notify = rect(1700, 1920);
+        let position = |notify, task_list, width, anchor| {
+            compute_widget_position(taskbar, notify, task_list, width, anchor, &offsets)
+        };
+
+        // 有通知区域：居中于空隙 / 空隙不足时贴近通知区域
+        assert_eq!(position(notify, rect(100, 1000), 280, WidgetAnchor::Right), (1210, 1040));
+        assert_eq!(position(notify, rect(100, 1600), 280, WidgetAnchor::Right), (1415, 1040));
+        // 空隙刚好等于窗口宽度时两侧没有间距，改为贴近通知区域
+        assert_eq!(position(notify, rect(100, 1420), 280, WidgetAnchor::Right), (1415, 1040));
+        // 没有通知区域时按任务栏右边缘偏移
+        assert_eq!(position(RECT::default(), RECT::default(), 280, WidgetAnchor::Right), (1580, 1040));
+        // 靠左
+        assert_eq!(position(notify, rect(100, 1000), 280, WidgetAnchor::Left), (60, 1040));
+
+        // 任务栏过窄时不超出左边缘
+        let narrow = RECT { left: 100, top: 0, right: 400, bottom: 40 };
+        assert_eq!(compute_widget_position(narrow, rect(300, 400), RECT::default(), 280, WidgetAnchor::Right, &offsets), (100, 0));
+        assert_eq!(compute_widget_position(narrow, RECT::default(), RECT::default(), 280, WidgetAnchor::Left, &offsets), (120, 0));
+    }
+
+    #[test]
+    fn test_compute_vertical_widget_position() {
+        let offsets = PositionOffsets::default();
+        // 停靠在屏幕右侧、宽 62 像素的任务栏，时钟在底部
+        let taskbar = RECT { left: 1858, top: 0, right: 1920, bottom: 1080 };
+        let notify = RECT { left: 1858, top: 960, right: 1920, bottom: 1080 };
+        let position = |notify, width, height, anchor| {
+            compute_vertical_widget_position(taskbar, notify, width, height, anchor, &offsets)
+        };
+
+        // 叠放在时钟上方并水平居中
+        assert_eq!(position(notify, 62, 40, WidgetAnchor::Right), (1858, 960 - 40 - 5));
+        assert_eq!(position(notify, 50, 40, WidgetAnchor::Right), (1864, 915));
+        // 没有通知区域时按任务栏下边缘偏移
+        assert_eq!(position(RECT::default(), 62, 40, WidgetAnchor::Right), (1858, 1080 - 40 - 60));
+        // 靠左放在任务栏顶部
+        assert_eq!(position(notify, 62, 40, WidgetAnchor::Left), (1858, 60));
+        // 窗口比任务栏宽时贴左边缘，任务栏过短时不超出上边缘
+        assert_eq!(position(notify, 280, 40, WidgetAnchor::Right), (1858, 915));
+        let short = RECT { left: 0, top: 0, right: 62, bottom: 30 };
+        assert_eq!(compute_vertical_widget_position(short, RECT::default(), 62, 40, WidgetAnchor::Right, &offsets), (0, 0));
+    }
+
+    #[test]
+    fn test_compute_widget_position_scales_with_dpi() {
+        // 150% 缩放：任务栏、通知区域和窗口宽度都是物理像素，偏移量同样按比例换算
+        let scale = 1.5;
+        let offsets = PositionOffsets::default().scaled(scale);
+        assert_eq!(offsets, PositionOffsets { left_margin: 90, notify_gap: 8, right_margin: 90 });
+
+        let taskbar = RECT { left: 0, top: 1548, right: 2880, bottom: 1620 };
+        let position = |notify, task_list, anchor| {
+            compute_widget_position(taskbar, notify, task_list, 420, anchor, &offsets)
+        };
+        assert_eq!(position(rect(2550, 2880), rect(150, 1500), WidgetAnchor::Right), (1815, 1548));
+        assert_eq!(position(rect(2550, 2880), RECT::default(), WidgetAnchor::Right), (2550 - 420 - 8, 1548));
+        assert_eq!(position(RECT::default(), RECT::default(), WidgetAnchor::Right), (2880 - 420 - 90, 1548));
+        assert_eq!(position(RECT::default(), RECT::default(), WidgetAnchor::Left), (90, 1548));
+    }
+
+    #[test]
+    fn test_topmost_stats_ratio() {
+        let mut stats = TopmostStats::default();
+        assert_eq!(stats.reassert_ratio(), 0.0);
+
+        stats.record(true);
+        stats.record(false);
+        stats.record(false);
+        stats.record(false);
+        assert_eq!(stats.total(), 4);
+        assert_eq!(stats.reassert_ratio(), 0.25);
     }
 }